@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE signups
+DROP COLUMN created_at,
+DROP COLUMN waitlisted;
+ALTER TABLE raids
+DROP COLUMN capacity;
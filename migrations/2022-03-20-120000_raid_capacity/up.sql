@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TABLE raids
+ADD capacity INT DEFAULT NULL,
+ADD CONSTRAINT raids_capacity_check CHECK (capacity > 0);
+ALTER TABLE signups
+ADD waitlisted BOOL NOT NULL DEFAULT FALSE,
+ADD created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc');
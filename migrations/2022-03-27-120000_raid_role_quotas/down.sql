@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raid_roles
+DROP COLUMN quota;
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE raid_roles
+ADD quota INT DEFAULT NULL,
+ADD CONSTRAINT raid_roles_quota_check CHECK (quota >= 0);
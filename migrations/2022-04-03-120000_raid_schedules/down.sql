@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP COLUMN schedule_id;
+DROP TABLE raid_schedule_skips;
+DROP TABLE raid_schedule_bosses;
+DROP TABLE raid_schedule_roles;
+DROP TABLE raid_schedules;
@@ -0,0 +1,39 @@
+-- Your SQL goes here
+CREATE TABLE raid_schedules (
+	id SERIAL PRIMARY KEY,
+	title TEXT NOT NULL,
+	weekday SMALLINT NOT NULL,
+	time TIME NOT NULL,
+	tier_id INT,
+	capacity INT DEFAULT NULL,
+	lookahead SMALLINT NOT NULL DEFAULT 2,
+	active BOOL NOT NULL DEFAULT TRUE,
+	FOREIGN KEY(tier_id) REFERENCES tiers(id) ON DELETE SET NULL,
+	CONSTRAINT raid_schedules_weekday_check CHECK (weekday >= 0 AND weekday <= 6),
+	CONSTRAINT raid_schedules_capacity_check CHECK (capacity > 0),
+	CONSTRAINT raid_schedules_lookahead_check CHECK (lookahead > 0)
+);
+CREATE TABLE raid_schedule_roles (
+	schedule_id INT NOT NULL,
+	role_id INT NOT NULL,
+	quota INT DEFAULT NULL,
+	FOREIGN KEY(schedule_id) REFERENCES raid_schedules(id) ON DELETE CASCADE,
+	FOREIGN KEY(role_id) REFERENCES roles(id),
+	PRIMARY KEY(schedule_id, role_id),
+	CONSTRAINT raid_schedule_roles_quota_check CHECK (quota >= 0)
+);
+CREATE TABLE raid_schedule_bosses (
+	schedule_id INT NOT NULL,
+	raid_boss_id INT NOT NULL,
+	FOREIGN KEY(schedule_id) REFERENCES raid_schedules(id) ON DELETE CASCADE,
+	FOREIGN KEY(raid_boss_id) REFERENCES raid_bosses(id) ON DELETE CASCADE,
+	PRIMARY KEY(schedule_id, raid_boss_id)
+);
+CREATE TABLE raid_schedule_skips (
+	schedule_id INT NOT NULL,
+	day DATE NOT NULL,
+	FOREIGN KEY(schedule_id) REFERENCES raid_schedules(id) ON DELETE CASCADE,
+	PRIMARY KEY(schedule_id, day)
+);
+ALTER TABLE raids
+ADD schedule_id INT REFERENCES raid_schedules(id) ON DELETE SET NULL;
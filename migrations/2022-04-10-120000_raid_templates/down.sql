@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE raid_template_bosses;
+DROP TABLE raid_template_roles;
+DROP TABLE raid_templates;
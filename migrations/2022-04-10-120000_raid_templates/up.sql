@@ -0,0 +1,27 @@
+-- Your SQL goes here
+CREATE TABLE raid_templates (
+	id SERIAL PRIMARY KEY,
+	name TEXT UNIQUE NOT NULL,
+	title TEXT NOT NULL,
+	description TEXT DEFAULT NULL,
+	tier_id INT,
+	capacity INT DEFAULT NULL,
+	FOREIGN KEY(tier_id) REFERENCES tiers(id) ON DELETE SET NULL,
+	CONSTRAINT raid_templates_capacity_check CHECK (capacity > 0)
+);
+CREATE TABLE raid_template_roles (
+	template_id INT NOT NULL,
+	role_id INT NOT NULL,
+	quota INT DEFAULT NULL,
+	FOREIGN KEY(template_id) REFERENCES raid_templates(id) ON DELETE CASCADE,
+	FOREIGN KEY(role_id) REFERENCES roles(id),
+	PRIMARY KEY(template_id, role_id),
+	CONSTRAINT raid_template_roles_quota_check CHECK (quota >= 0)
+);
+CREATE TABLE raid_template_bosses (
+	template_id INT NOT NULL,
+	raid_boss_id INT NOT NULL,
+	FOREIGN KEY(template_id) REFERENCES raid_templates(id) ON DELETE CASCADE,
+	FOREIGN KEY(raid_boss_id) REFERENCES raid_bosses(id) ON DELETE CASCADE,
+	PRIMARY KEY(template_id, raid_boss_id)
+);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE raids
+ADD open_at TIMESTAMP DEFAULT NULL;
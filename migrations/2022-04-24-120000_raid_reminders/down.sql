@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP COLUMN reminded;
+ALTER TABLE users
+DROP COLUMN reminders;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE users
+ADD reminders BOOL NOT NULL DEFAULT TRUE;
+ALTER TABLE raids
+ADD reminded BOOL NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE signups
+ADD needs_role_update BOOL NOT NULL DEFAULT FALSE;
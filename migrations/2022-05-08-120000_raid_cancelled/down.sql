@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP COLUMN cancel_reason;
+-- there is no state before cancelled existed, finished is the closest
+UPDATE raids SET state = 'finished' WHERE state = 'cancelled';
+ALTER TYPE raid_state RENAME TO raid_state_old;
+CREATE TYPE raid_state AS ENUM ('created', 'open', 'closed', 'started', 'finished');
+ALTER TABLE raids ALTER COLUMN state DROP DEFAULT;
+ALTER TABLE raids ALTER COLUMN state TYPE raid_state USING state::text::raid_state;
+ALTER TABLE raids ALTER COLUMN state SET DEFAULT 'created';
+DROP TYPE raid_state_old;
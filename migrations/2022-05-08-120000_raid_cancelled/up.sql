@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- ALTER TYPE ... ADD VALUE can not run inside a transaction before Postgres 12
+-- and diesel runs every migration in one. So the type is recreated instead
+ALTER TYPE raid_state RENAME TO raid_state_old;
+CREATE TYPE raid_state AS ENUM ('created', 'open', 'closed', 'started', 'finished', 'cancelled');
+ALTER TABLE raids ALTER COLUMN state DROP DEFAULT;
+ALTER TABLE raids ALTER COLUMN state TYPE raid_state USING state::text::raid_state;
+ALTER TABLE raids ALTER COLUMN state SET DEFAULT 'created';
+DROP TYPE raid_state_old;
+ALTER TABLE raids
+ADD cancel_reason TEXT;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TYPE attendance_status AS ENUM ('attended', 'no_show', 'late', 'substitute');
+CREATE TABLE attendances (
+	signup_id INT PRIMARY KEY,
+	status attendance_status NOT NULL DEFAULT 'attended',
+	recorded_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+	FOREIGN KEY(signup_id) REFERENCES signups(id) ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE signouts (
+	id SERIAL PRIMARY KEY,
+	user_id INT NOT NULL,
+	raid_id INT NOT NULL,
+	created_at TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc'),
+	FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+	FOREIGN KEY(raid_id) REFERENCES raids(id) ON DELETE CASCADE
+);
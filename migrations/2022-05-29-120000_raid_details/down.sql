@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP CONSTRAINT raids_duration_check,
+DROP COLUMN description,
+DROP COLUMN leader_id,
+DROP COLUMN voice_channel_id,
+DROP COLUMN duration;
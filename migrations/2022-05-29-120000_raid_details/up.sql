@@ -0,0 +1,7 @@
+-- Your SQL goes here
+ALTER TABLE raids
+ADD description TEXT,
+ADD leader_id BIGINT,
+ADD voice_channel_id BIGINT,
+ADD duration INT NOT NULL DEFAULT 120,
+ADD CONSTRAINT raids_duration_check CHECK (duration > 0);
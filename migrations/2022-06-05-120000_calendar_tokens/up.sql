@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE users
+ADD calendar_token TEXT UNIQUE;
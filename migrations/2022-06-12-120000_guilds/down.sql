@@ -0,0 +1,36 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE config
+DROP CONSTRAINT config_pkey,
+DROP COLUMN guild_id,
+ADD PRIMARY KEY (name);
+
+ALTER TABLE raid_templates
+DROP CONSTRAINT raid_templates_guild_id_name_key,
+DROP COLUMN guild_id,
+ADD UNIQUE (name);
+
+ALTER TABLE raid_schedules
+DROP COLUMN guild_id;
+
+ALTER TABLE tiers
+DROP CONSTRAINT tiers_guild_id_name_key,
+DROP COLUMN guild_id,
+ADD UNIQUE (name);
+
+ALTER TABLE raid_bosses
+DROP CONSTRAINT raid_bosses_guild_id_repr_key,
+DROP CONSTRAINT raid_bosses_guild_id_wing_position_key,
+DROP COLUMN guild_id,
+ADD UNIQUE (repr),
+ADD UNIQUE (wing, position);
+
+DROP INDEX roles_active_constraint;
+ALTER TABLE roles
+DROP COLUMN guild_id;
+CREATE UNIQUE INDEX roles_active_constraint ON roles(repr) WHERE active;
+
+DROP INDEX raids_guild_id_idx;
+ALTER TABLE raids
+DROP COLUMN guild_id;
+
+DROP TABLE guilds;
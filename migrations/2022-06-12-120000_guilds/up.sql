@@ -0,0 +1,57 @@
+-- Your SQL goes here
+CREATE TABLE guilds (
+	id BIGINT PRIMARY KEY,
+	admin_role_id BIGINT NOT NULL,
+	squadmaker_role_id BIGINT NOT NULL,
+	emoji_guild_id BIGINT NOT NULL
+);
+
+-- Existing rows belong to guild 0 until they are adopted by the guild
+-- configured with MAIN_GUILD_ID
+ALTER TABLE raids
+ADD guild_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE raids
+ALTER COLUMN guild_id DROP DEFAULT;
+CREATE INDEX raids_guild_id_idx ON raids(guild_id);
+
+ALTER TABLE roles
+ADD guild_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE roles
+ALTER COLUMN guild_id DROP DEFAULT;
+DROP INDEX roles_active_constraint;
+CREATE UNIQUE INDEX roles_active_constraint ON roles(guild_id, repr) WHERE active;
+
+ALTER TABLE raid_bosses
+ADD guild_id BIGINT NOT NULL DEFAULT 0,
+DROP CONSTRAINT raid_bosses_repr_key,
+DROP CONSTRAINT raid_bosses_wing_position_key,
+ADD UNIQUE (guild_id, repr),
+ADD UNIQUE (guild_id, wing, position);
+ALTER TABLE raid_bosses
+ALTER COLUMN guild_id DROP DEFAULT;
+
+ALTER TABLE tiers
+ADD guild_id BIGINT NOT NULL DEFAULT 0,
+DROP CONSTRAINT tiers_name_key,
+ADD UNIQUE (guild_id, name);
+ALTER TABLE tiers
+ALTER COLUMN guild_id DROP DEFAULT;
+
+ALTER TABLE raid_schedules
+ADD guild_id BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE raid_schedules
+ALTER COLUMN guild_id DROP DEFAULT;
+
+ALTER TABLE raid_templates
+ADD guild_id BIGINT NOT NULL DEFAULT 0,
+DROP CONSTRAINT raid_templates_name_key,
+ADD UNIQUE (guild_id, name);
+ALTER TABLE raid_templates
+ALTER COLUMN guild_id DROP DEFAULT;
+
+ALTER TABLE config
+ADD guild_id BIGINT NOT NULL DEFAULT 0,
+DROP CONSTRAINT config_pkey,
+ADD PRIMARY KEY (guild_id, name);
+ALTER TABLE config
+ALTER COLUMN guild_id DROP DEFAULT;
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP COLUMN board_channel_id;
+DROP TABLE signup_board_channels;
+CREATE TABLE signup_board_channels (
+	day DATE PRIMARY KEY,
+	channel_id BIGINT NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+DROP TABLE signup_board_channels;
+CREATE TABLE signup_board_channels (
+	guild_id BIGINT NOT NULL,
+	weekday SMALLINT NOT NULL,
+	channel_id BIGINT NOT NULL,
+	PRIMARY KEY(guild_id, weekday),
+	CONSTRAINT signup_board_channels_weekday_check CHECK (weekday >= 0 AND weekday <= 6)
+);
+ALTER TABLE raids
+ADD board_channel_id BIGINT DEFAULT NULL;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER signup_roles_notify ON signup_roles;
+DROP TRIGGER signups_notify ON signups;
+DROP TRIGGER raids_update_notify ON raids;
+DROP TRIGGER raids_notify ON raids;
+DROP FUNCTION signup_roles_notify();
+DROP FUNCTION signups_notify();
+DROP FUNCTION raids_notify();
+DROP FUNCTION notify_raid_change(INTEGER);
@@ -0,0 +1,66 @@
+-- Your SQL goes here
+-- Every change of a raid or its sign-ups is sent to the raid_changes channel
+-- with the payload <guild_id>:<raid_id>. Deleted raids that still have a board
+-- message add :<board_channel_id>:<board_message_id>, the bot can not look
+-- them up anymore
+CREATE FUNCTION notify_raid_change(changed_raid_id INTEGER) RETURNS VOID AS $$
+BEGIN
+	PERFORM pg_notify('raid_changes', guild_id || ':' || id)
+	FROM raids
+	WHERE id = changed_raid_id;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION raids_notify() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'DELETE' THEN
+		PERFORM pg_notify('raid_changes', OLD.guild_id || ':' || OLD.id
+			|| COALESCE(':' || OLD.board_channel_id || ':' || OLD.board_message_id, ''));
+	ELSE
+		PERFORM pg_notify('raid_changes', NEW.guild_id || ':' || NEW.id);
+	END IF;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION signups_notify() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'DELETE' THEN
+		PERFORM notify_raid_change(OLD.raid_id);
+	ELSE
+		PERFORM notify_raid_change(NEW.raid_id);
+	END IF;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE FUNCTION signup_roles_notify() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'DELETE' THEN
+		PERFORM notify_raid_change(raid_id) FROM signups WHERE id = OLD.signup_id;
+	ELSE
+		PERFORM notify_raid_change(raid_id) FROM signups WHERE id = NEW.signup_id;
+	END IF;
+	RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+-- The bot saving the board message of a raid is no change worth a notification.
+-- Otherwise every board update would cause another one
+CREATE TRIGGER raids_notify
+AFTER INSERT OR DELETE ON raids
+FOR EACH ROW EXECUTE PROCEDURE raids_notify();
+CREATE TRIGGER raids_update_notify
+AFTER UPDATE ON raids
+FOR EACH ROW
+WHEN (OLD.board_channel_id IS NOT DISTINCT FROM NEW.board_channel_id
+	AND OLD.board_message_id IS NOT DISTINCT FROM NEW.board_message_id)
+EXECUTE PROCEDURE raids_notify();
+
+CREATE TRIGGER signups_notify
+AFTER INSERT OR UPDATE OR DELETE ON signups
+FOR EACH ROW EXECUTE PROCEDURE signups_notify();
+
+CREATE TRIGGER signup_roles_notify
+AFTER INSERT OR UPDATE OR DELETE ON signup_roles
+FOR EACH ROW EXECUTE PROCEDURE signup_roles_notify();
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raids
+DROP COLUMN category_id;
+DROP TABLE raid_categories;
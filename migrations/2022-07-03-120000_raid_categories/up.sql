@@ -0,0 +1,33 @@
+-- Your SQL goes here
+CREATE TABLE raid_categories (
+	id SERIAL PRIMARY KEY,
+	guild_id BIGINT NOT NULL,
+	name TEXT NOT NULL,
+	sort_weight INT NOT NULL DEFAULT 0,
+	color INT DEFAULT NULL,
+	emoji BIGINT DEFAULT NULL,
+	UNIQUE(guild_id, name)
+);
+ALTER TABLE raids
+ADD category_id INT DEFAULT NULL REFERENCES raid_categories(id) ON DELETE SET NULL;
+
+-- raids used to be ordered by keywords in their title
+INSERT INTO raid_categories (guild_id, name, sort_weight)
+SELECT DISTINCT guild_id, 'Beginner', 10 FROM raids WHERE title LIKE '%Beginner%';
+UPDATE raids SET category_id = c.id FROM raid_categories c
+WHERE c.guild_id = raids.guild_id AND c.name = 'Beginner'
+AND raids.title LIKE '%Beginner%' AND raids.category_id IS NULL;
+
+INSERT INTO raid_categories (guild_id, name, sort_weight)
+SELECT DISTINCT guild_id, 'Intermediate', 8 FROM raids
+WHERE title LIKE '%Intermediate%' AND category_id IS NULL;
+UPDATE raids SET category_id = c.id FROM raid_categories c
+WHERE c.guild_id = raids.guild_id AND c.name = 'Intermediate'
+AND raids.title LIKE '%Intermediate%' AND raids.category_id IS NULL;
+
+INSERT INTO raid_categories (guild_id, name, sort_weight)
+SELECT DISTINCT guild_id, 'Practice', 6 FROM raids
+WHERE title LIKE '%Practice%' AND category_id IS NULL;
+UPDATE raids SET category_id = c.id FROM raid_categories c
+WHERE c.guild_id = raids.guild_id AND c.name = 'Practice'
+AND raids.title LIKE '%Practice%' AND raids.category_id IS NULL;
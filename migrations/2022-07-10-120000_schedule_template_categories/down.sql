@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE raid_templates
+DROP COLUMN category_id;
+ALTER TABLE raid_schedules
+DROP COLUMN category_id;
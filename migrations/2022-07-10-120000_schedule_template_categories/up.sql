@@ -0,0 +1,6 @@
+-- Your SQL goes here
+-- raids created from a schedule or template get its category
+ALTER TABLE raid_schedules
+ADD category_id INT DEFAULT NULL REFERENCES raid_categories(id) ON DELETE SET NULL;
+ALTER TABLE raid_templates
+ADD category_id INT DEFAULT NULL REFERENCES raid_categories(id) ON DELETE SET NULL;
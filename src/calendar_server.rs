@@ -0,0 +1,131 @@
+// Serves the calendar feeds of raids so calendar apps can subscribe to them
+use crate::{db, ical};
+use hyper::{
+    header,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serenity::{client::Context, model::id::GuildId};
+use std::{convert::Infallible, net::SocketAddr};
+use tracing::{error, info};
+
+const FEED_PATH: &str = "/calendar/";
+const FEED_EXT: &str = ".ics";
+/// Prefix of the feeds with all upcoming raids of a guild. Can never collide
+/// with a token
+const OPEN_FEED_PREFIX: &str = "open-";
+const TOKEN_LENGTH: usize = 32;
+
+pub fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .collect()
+}
+
+pub fn user_feed_url(base_url: &str, token: &str) -> String {
+    format!(
+        "{}{}{}{}",
+        base_url.trim_end_matches('/'),
+        FEED_PATH,
+        token,
+        FEED_EXT
+    )
+}
+
+pub fn open_feed_url(base_url: &str, guild_id: GuildId) -> String {
+    user_feed_url(base_url, &format!("{}{}", OPEN_FEED_PREFIX, guild_id))
+}
+
+fn response(status: StatusCode, body: String) -> Response<Body> {
+    let mut res = Response::new(Body::from(body));
+    *res.status_mut() = status;
+    res
+}
+
+async fn feed(ctx: &Context, name: &str) -> Result<Option<String>, diesel::result::Error> {
+    let since = ical::export_since();
+    if let Some(guild_id) = name.strip_prefix(OPEN_FEED_PREFIX) {
+        let guild_id = match guild_id.parse::<u64>() {
+            Ok(id) => GuildId::from(id),
+            Err(_) => return Ok(None),
+        };
+        let raids = db::Raid::upcoming(ctx, guild_id, since).await?;
+        return Ok(Some(ical::calendar("Raids", &raids)));
+    }
+
+    let user = match db::User::by_calendar_token(ctx, name.to_string()).await {
+        Ok(u) => u,
+        Err(diesel::NotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let raids = user
+        .active_signups_with_raid(ctx)
+        .await?
+        .into_iter()
+        .map(|(_, r)| r)
+        .filter(|r| r.date >= since)
+        .collect::<Vec<_>>();
+    Ok(Some(ical::calendar("My Raids", &raids)))
+}
+
+async fn handle(ctx: &Context, req: Request<Body>) -> Response<Body> {
+    if req.method() != Method::GET {
+        return response(StatusCode::METHOD_NOT_ALLOWED, String::new());
+    }
+
+    let name = match req
+        .uri()
+        .path()
+        .strip_prefix(FEED_PATH)
+        .and_then(|p| p.strip_suffix(FEED_EXT))
+    {
+        Some(n) if !n.is_empty() => n,
+        _ => return response(StatusCode::NOT_FOUND, String::new()),
+    };
+
+    match feed(ctx, name).await {
+        Ok(Some(cal)) => {
+            let mut res = response(StatusCode::OK, cal);
+            res.headers_mut().insert(
+                header::CONTENT_TYPE,
+                header::HeaderValue::from_static("text/calendar; charset=utf-8"),
+            );
+            res
+        }
+        Ok(None) => response(StatusCode::NOT_FOUND, String::new()),
+        Err(e) => {
+            error!("Failed to create calendar feed: {}", e);
+            response(StatusCode::INTERNAL_SERVER_ERROR, String::new())
+        }
+    }
+}
+
+/// Runs the http server until it fails. Only ever bound to localhost, so it
+/// has to be exposed with a reverse proxy
+pub async fn calendar_server_task(ctx: Context, port: u16) {
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let make_svc = make_service_fn(move |_| {
+        let ctx = ctx.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req| {
+                let ctx = ctx.clone();
+                async move { Ok::<_, Infallible>(handle(&ctx, req).await) }
+            }))
+        }
+    });
+
+    let server = match Server::try_bind(&addr) {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to bind calendar server to {}: {}", addr, e);
+            return;
+        }
+    };
+
+    info!("Calendar server listening on {}", addr);
+    if let Err(e) = server.serve(make_svc).await {
+        error!("Calendar server stopped: {}", e);
+    }
+}
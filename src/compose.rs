@@ -0,0 +1,278 @@
+// builds a squad out of sign-ups. Kept free of any discord or database access
+use std::{cmp::Reverse, collections::HashMap};
+
+use serde::Serialize;
+
+pub const SUBGROUP_SIZE: usize = 5;
+
+/// How many players are needed for a role
+#[derive(Debug, Clone)]
+pub struct RoleNeed {
+    pub role_id: i32,
+    pub priority: i16,
+    pub amount: usize,
+}
+
+/// A player together with all roles they are willing to play
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    pub user_id: i32,
+    pub roles: Vec<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Assignment {
+    pub user_id: i32,
+    pub role_id: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Squad {
+    /// Subgroups with at most SUBGROUP_SIZE players each
+    pub subgroups: Vec<Vec<Assignment>>,
+    /// Players that did not get a role
+    pub bench: Vec<i32>,
+    /// Role id and how many slots could not be filled
+    pub unfilled: Vec<(i32, usize)>,
+}
+
+impl Squad {
+    pub fn assigned_count(&self) -> usize {
+        self.subgroups.iter().map(|g| g.len()).sum()
+    }
+}
+
+/// Assigns exactly one role to as many candidates as possible
+///
+/// Slots of roles with a higher priority are filled first, ties are broken by
+/// how few candidates can play the role. Already placed players are moved to
+/// other roles if that allows an additional slot to be filled. The result only
+/// depends on the input, not on its order
+pub fn solve(needs: &[RoleNeed], candidates: &[Candidate]) -> Squad {
+    let mut candidates = candidates.to_vec();
+    candidates.sort_by_key(|c| c.user_id);
+    candidates.dedup_by_key(|c| c.user_id);
+
+    // How many candidates could play each role
+    let mut supply: HashMap<i32, usize> = HashMap::new();
+    for c in &candidates {
+        for r in &c.roles {
+            *supply.entry(*r).or_default() += 1;
+        }
+    }
+
+    let mut needs = needs.to_vec();
+    needs.sort_by_key(|n| {
+        (
+            Reverse(n.priority),
+            supply.get(&n.role_id).copied().unwrap_or_default(),
+            n.role_id,
+        )
+    });
+
+    // Every needed position is a slot taking exactly one player
+    let slots: Vec<i32> = needs
+        .iter()
+        .flat_map(|n| std::iter::repeat(n.role_id).take(n.amount))
+        .collect();
+
+    // Players with less options are tried first so flexible ones stay available
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|&c| (candidates[c].roles.len(), candidates[c].user_id));
+
+    // Slot index for every candidate
+    let mut matched: Vec<Option<usize>> = vec![None; candidates.len()];
+    for slot in 0..slots.len() {
+        let mut visited = vec![false; candidates.len()];
+        augment(slot, &slots, &candidates, &order, &mut matched, &mut visited);
+    }
+
+    let mut assigned: Vec<(usize, Assignment)> = Vec::new();
+    let mut bench: Vec<i32> = Vec::new();
+    for (c, m) in candidates.iter().zip(&matched) {
+        match m {
+            Some(slot) => assigned.push((
+                *slot,
+                Assignment {
+                    user_id: c.user_id,
+                    role_id: slots[*slot],
+                },
+            )),
+            None => bench.push(c.user_id),
+        }
+    }
+    assigned.sort_by_key(|(slot, _)| *slot);
+
+    let unfilled = needs
+        .iter()
+        .map(|n| {
+            let filled = assigned
+                .iter()
+                .filter(|(_, a)| a.role_id == n.role_id)
+                .count();
+            (n.role_id, n.amount.saturating_sub(filled))
+        })
+        .filter(|(_, missing)| *missing > 0)
+        .collect();
+
+    Squad {
+        subgroups: subgroups(assigned.into_iter().map(|(_, a)| a).collect()),
+        bench,
+        unfilled,
+    }
+}
+
+/// Tries to find a player for the slot. Players that already have a slot are
+/// only taken if they can be moved to another one
+fn augment(
+    slot: usize,
+    slots: &[i32],
+    candidates: &[Candidate],
+    order: &[usize],
+    matched: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &c in order {
+        if visited[c] || !candidates[c].roles.contains(&slots[slot]) {
+            continue;
+        }
+        visited[c] = true;
+        let free = match matched[c] {
+            Some(other) => augment(other, slots, candidates, order, matched, visited),
+            None => true,
+        };
+        if free {
+            matched[c] = Some(slot);
+            return true;
+        }
+    }
+    false
+}
+
+/// Deals the assignments round robin onto as few subgroups as possible.
+/// Since they are ordered by role every role is spread evenly
+fn subgroups(assigned: Vec<Assignment>) -> Vec<Vec<Assignment>> {
+    let count = (assigned.len() + SUBGROUP_SIZE - 1) / SUBGROUP_SIZE;
+    let mut groups: Vec<Vec<Assignment>> = vec![Vec::with_capacity(SUBGROUP_SIZE); count];
+    for (i, a) in assigned.into_iter().enumerate() {
+        groups[i % count].push(a);
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn need(role_id: i32, priority: i16, amount: usize) -> RoleNeed {
+        RoleNeed {
+            role_id,
+            priority,
+            amount,
+        }
+    }
+
+    fn candidate(user_id: i32, roles: &[i32]) -> Candidate {
+        Candidate {
+            user_id,
+            roles: roles.to_vec(),
+        }
+    }
+
+    fn role_of(squad: &Squad, user_id: i32) -> Option<i32> {
+        squad
+            .subgroups
+            .iter()
+            .flatten()
+            .find(|a| a.user_id == user_id)
+            .map(|a| a.role_id)
+    }
+
+    #[test]
+    fn higher_priority_is_filled_first() {
+        let needs = [need(1, 1, 1), need(2, 5, 1)];
+        let squad = solve(&needs, &[candidate(10, &[1, 2])]);
+
+        assert_eq!(role_of(&squad, 10), Some(2));
+        assert_eq!(squad.unfilled, vec![(1, 1)]);
+        assert!(squad.bench.is_empty());
+    }
+
+    #[test]
+    fn placed_players_are_moved_to_fill_more_slots() {
+        // 10 takes role 1 first and has to move to role 2 for 11 to fit in
+        let needs = [need(1, 2, 1), need(2, 1, 1)];
+        let squad = solve(&needs, &[candidate(10, &[1, 2]), candidate(11, &[1, 3])]);
+
+        assert_eq!(role_of(&squad, 10), Some(2));
+        assert_eq!(role_of(&squad, 11), Some(1));
+        assert!(squad.unfilled.is_empty());
+        assert!(squad.bench.is_empty());
+    }
+
+    #[test]
+    fn missing_players_are_reported() {
+        let needs = [need(1, 0, 3), need(2, 0, 1)];
+        let squad = solve(&needs, &[candidate(10, &[1])]);
+
+        assert_eq!(squad.assigned_count(), 1);
+        assert_eq!(squad.unfilled, vec![(2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn surplus_players_are_benched() {
+        let needs = [need(1, 0, 1)];
+        let candidates = [
+            candidate(12, &[1]),
+            candidate(10, &[1]),
+            candidate(11, &[2]),
+        ];
+        let squad = solve(&needs, &candidates);
+
+        assert_eq!(role_of(&squad, 10), Some(1));
+        assert_eq!(squad.bench, vec![11, 12]);
+        assert!(squad.unfilled.is_empty());
+    }
+
+    #[test]
+    fn roles_are_spread_over_subgroups() {
+        let needs = [need(1, 0, 4), need(2, 0, 3)];
+        let candidates = (1..=4)
+            .map(|u| candidate(u, &[1]))
+            .chain((5..=7).map(|u| candidate(u, &[2])))
+            .collect::<Vec<_>>();
+        let squad = solve(&needs, &candidates);
+
+        assert_eq!(squad.subgroups.len(), 2);
+        assert_eq!(squad.subgroups[0].len(), 4);
+        assert_eq!(squad.subgroups[1].len(), 3);
+        for role_id in [1, 2] {
+            let counts = squad
+                .subgroups
+                .iter()
+                .map(|g| g.iter().filter(|a| a.role_id == role_id).count())
+                .collect::<Vec<_>>();
+            assert!(counts.iter().max().unwrap() - counts.iter().min().unwrap() <= 1);
+        }
+    }
+
+    #[test]
+    fn order_of_the_input_does_not_matter() {
+        let needs = [need(1, 1, 2), need(2, 0, 2)];
+        let mut candidates = vec![
+            candidate(10, &[1, 2]),
+            candidate(11, &[1]),
+            candidate(12, &[2]),
+            candidate(13, &[1, 2]),
+            candidate(14, &[2]),
+        ];
+        let squad = solve(&needs, &candidates);
+        candidates.reverse();
+        let mut reversed_needs = needs.to_vec();
+        reversed_needs.reverse();
+
+        assert_eq!(solve(&reversed_needs, &candidates), squad);
+        assert_eq!(squad.assigned_count(), 4);
+        assert_eq!(squad.bench.len(), 1);
+    }
+}
@@ -1,19 +1,20 @@
 use crate::db::DBPool;
-use crate::signup_board::SignupBoard;
+use crate::signup_board::{BoardChanges, SignupBoard};
 use serenity::{model::prelude::*, prelude::*};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+/// Values set on startup. Everything guild specific is stored in the database
 pub struct ConfigValues {
-    pub main_guild_id: GuildId,
-    pub admin_role_id: RoleId,
-    pub squadmaker_role_id: RoleId,
-    pub emoji_guild_id: GuildId,
+    /// Port of the local calendar server. Disabled if not set
+    pub calendar_port: Option<u16>,
+    /// Base url the calendar feeds are reachable under
+    pub calendar_url: Option<String>,
 }
 
 pub static INFO_LOG_NAME: &str = "log_channel_id";
 
 pub struct LogConfig {
-    pub log: Option<ChannelId>,
+    pub log: HashMap<GuildId, ChannelId>,
 }
 
 pub struct ConfigValuesData;
@@ -28,7 +29,12 @@ impl TypeMapKey for LogConfigData {
 
 pub struct SignupBoardData;
 impl TypeMapKey for SignupBoardData {
-    type Value = Arc<RwLock<SignupBoard>>;
+    type Value = Arc<RwLock<HashMap<GuildId, Arc<RwLock<SignupBoard>>>>>;
+}
+
+pub struct BoardChangesData;
+impl TypeMapKey for BoardChangesData {
+    type Value = Arc<BoardChanges>;
 }
 
 pub struct DBPoolData;
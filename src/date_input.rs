@@ -0,0 +1,228 @@
+// Parses the days and times users type into commands. Besides absolute dates
+// relative ones like `tomorrow`, `next wed` or `+3d` are understood. All of
+// them resolve relative to today in the guild time zone
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+
+/// Longer ranges are most likely a typo
+const MAX_RANGE_DAYS: i64 = 31;
+
+/// The first `weekday` on or after `from`
+fn weekday_from(from: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (7 + weekday.num_days_from_monday() - from.weekday().num_days_from_monday()) % 7;
+    from + Duration::days(ahead as i64)
+}
+
+/// Parses a single day
+/// Example: 2022-06-17, today, tomorrow, fri, next wed, +3d, +2w
+pub fn parse_day(input: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let input = input.trim().to_lowercase();
+
+    if let Ok(d) = input.parse::<NaiveDate>() {
+        return Ok(d);
+    }
+
+    match input.as_str() {
+        "today" => return Ok(today),
+        "tomorrow" => return Ok(today.succ()),
+        _ => (),
+    }
+
+    if let Some(offset) = input.strip_prefix('+') {
+        let (amount, factor) = match (offset.strip_suffix('d'), offset.strip_suffix('w')) {
+            (Some(a), _) => (a, 1),
+            (_, Some(a)) => (a, 7),
+            _ => (offset, 1),
+        };
+        let amount = amount
+            .trim()
+            .parse::<u16>()
+            .with_context(|| format!("Invalid offset: {}", input))?;
+        return Ok(today + Duration::days(amount as i64 * factor));
+    }
+
+    // next always skips today, a plain weekday might be today
+    if let Some(weekday) = input.strip_prefix("next ") {
+        let weekday = weekday
+            .trim()
+            .parse::<Weekday>()
+            .map_err(|_| anyhow!("Unknown weekday: {}", weekday.trim()))?;
+        return Ok(weekday_from(today.succ(), weekday));
+    }
+
+    if let Ok(weekday) = input.parse::<Weekday>() {
+        return Ok(weekday_from(today, weekday));
+    }
+
+    bail!("Could not parse date: {}", input)
+}
+
+/// Parses a time. Format: HH:MM or HH:MM:SS
+pub fn parse_time(input: &str) -> Result<NaiveTime> {
+    let input = input.trim();
+    NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .with_context(|| format!("Could not parse time: {}", input))
+}
+
+/// Parses a day optionally followed by a time
+/// Example: fri 19:30, 2022-06-17 19:30:00, tomorrow
+pub fn parse_day_time(input: &str, today: NaiveDate) -> Result<(NaiveDate, Option<NaiveTime>)> {
+    let input = input.trim();
+    if let Some((day, time)) = input.rsplit_once(' ') {
+        if let Ok(time) = parse_time(time) {
+            return Ok((parse_day(day, today)?, Some(time)));
+        }
+    }
+    Ok((parse_day(input, today)?, None))
+}
+
+/// Parses a comma separated list of days and inclusive ranges of days. The end
+/// of a range is relative to its start, so mon..sun is always a full week
+/// Example: today, mon..sun, 2022-06-17..+2d
+pub fn parse_days(input: &str, today: NaiveDate) -> Result<Vec<NaiveDate>> {
+    let mut days = Vec::new();
+    for part in input.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        match part.split_once("..") {
+            Some((from, to)) => {
+                let from = parse_day(from, today)?;
+                let to = parse_day(to, from)?;
+                if to < from {
+                    bail!("{} ends before it starts", part);
+                }
+                if (to - from).num_days() >= MAX_RANGE_DAYS {
+                    bail!("{} is longer than {} days", part, MAX_RANGE_DAYS);
+                }
+                let mut day = from;
+                while day <= to {
+                    days.push(day);
+                    day = day.succ();
+                }
+            }
+            None => days.push(parse_day(part, today)?),
+        }
+    }
+
+    if days.is_empty() {
+        bail!("No day given");
+    }
+
+    days.sort_unstable();
+    days.dedup();
+    Ok(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a wednesday
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd(2022, 6, 15)
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd(2022, 6, d)
+    }
+
+    #[test]
+    fn absolute_dates_and_keywords() {
+        assert_eq!(
+            parse_day("2022-07-01", today()).unwrap(),
+            NaiveDate::from_ymd(2022, 7, 1)
+        );
+        assert_eq!(parse_day(" Today ", today()).unwrap(), day(15));
+        assert_eq!(parse_day("tomorrow", today()).unwrap(), day(16));
+        assert!(parse_day("someday", today()).is_err());
+    }
+
+    #[test]
+    fn plain_weekday_might_be_today() {
+        assert_eq!(parse_day("wed", today()).unwrap(), day(15));
+        assert_eq!(parse_day("fri", today()).unwrap(), day(17));
+        assert_eq!(parse_day("monday", today()).unwrap(), day(20));
+    }
+
+    #[test]
+    fn next_weekday_skips_today() {
+        assert_eq!(parse_day("next wed", today()).unwrap(), day(22));
+        assert_eq!(parse_day("next fri", today()).unwrap(), day(17));
+        assert!(parse_day("next week", today()).is_err());
+    }
+
+    #[test]
+    fn offsets_in_days_and_weeks() {
+        assert_eq!(parse_day("+3d", today()).unwrap(), day(18));
+        assert_eq!(parse_day("+4", today()).unwrap(), day(19));
+        assert_eq!(parse_day("+2w", today()).unwrap(), day(29));
+        assert!(parse_day("+xd", today()).is_err());
+        assert!(parse_day("+-1d", today()).is_err());
+    }
+
+    #[test]
+    fn times_with_and_without_seconds() {
+        assert_eq!(parse_time("19:30").unwrap(), NaiveTime::from_hms(19, 30, 0));
+        assert_eq!(
+            parse_time(" 19:30:15 ").unwrap(),
+            NaiveTime::from_hms(19, 30, 15)
+        );
+        assert!(parse_time("25:00").is_err());
+        assert!(parse_time("7pm").is_err());
+    }
+
+    #[test]
+    fn day_with_optional_time() {
+        let evening = NaiveTime::from_hms(19, 30, 0);
+        assert_eq!(
+            parse_day_time("fri 19:30", today()).unwrap(),
+            (day(17), Some(evening))
+        );
+        assert_eq!(
+            parse_day_time("next wed 19:30", today()).unwrap(),
+            (day(22), Some(evening))
+        );
+        assert_eq!(
+            parse_day_time("2022-06-17 19:30:00", today()).unwrap(),
+            (day(17), Some(evening))
+        );
+        assert_eq!(
+            parse_day_time("next wed", today()).unwrap(),
+            (day(22), None)
+        );
+        assert_eq!(
+            parse_day_time("tomorrow", today()).unwrap(),
+            (day(16), None)
+        );
+        assert!(parse_day_time("fri 19:30 tomorrow", today()).is_err());
+    }
+
+    #[test]
+    fn range_end_is_relative_to_its_start() {
+        let week: Vec<_> = (20..=26).map(day).collect();
+        assert_eq!(parse_days("mon..sun", today()).unwrap(), week);
+        assert_eq!(
+            parse_days("fri..mon", today()).unwrap(),
+            vec![day(17), day(18), day(19), day(20)]
+        );
+        assert_eq!(
+            parse_days("2022-06-17..+2d", today()).unwrap(),
+            vec![day(17), day(18), day(19)]
+        );
+    }
+
+    #[test]
+    fn days_are_sorted_without_duplicates() {
+        assert_eq!(
+            parse_days("tomorrow, today,,today..tomorrow", today()).unwrap(),
+            vec![day(15), day(16)]
+        );
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        assert!(parse_days("2022-06-20..2022-06-17", today()).is_err());
+        assert_eq!(parse_days("today..+30d", today()).unwrap().len(), 31);
+        assert!(parse_days("today..+31d", today()).is_err());
+        assert!(parse_days(" , ", today()).is_err());
+    }
+}
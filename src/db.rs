@@ -3,17 +3,19 @@
 //! is used to hold connections and allowing diesel calls to be move to a blocking thread
 //! with tokio task::spawn_blocking to not block on the executer thread
 
-use crate::data::DBPoolData;
-use chrono::{NaiveDate, NaiveDateTime};
+use crate::{data::DBPoolData, timezone};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use chrono_tz::Tz;
 use diesel::pg::PgConnection;
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
 use diesel::result::QueryResult;
 use serenity::client::Context;
 use serenity::model::{
-    id::{EmojiId, MessageId, UserId},
+    id::{ChannelId, EmojiId, GuildId, MessageId, UserId},
     misc::Mention,
 };
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
 use tokio::task;
@@ -64,12 +66,48 @@ async fn upsert_user(ctx: &Context, user: NewUser) -> QueryResult<User> {
     .unwrap()
 }
 
-async fn insert_raid(ctx: &Context, t: NewRaid) -> QueryResult<Raid> {
+/// The raid is only created if its details, roles and bosses can be saved as
+/// well
+async fn insert_raid(
+    ctx: &Context,
+    t: NewRaid,
+    details: RaidDetails,
+    roles: Vec<(i32, Option<i32>)>,
+    bosses: Vec<i32>,
+) -> QueryResult<Raid> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::insert_into(raids::table)
-            .values(&t)
-            .get_result(&pool.conn())
+        let conn = pool.conn();
+        conn.transaction(|| {
+            let raid: Raid = diesel::insert_into(raids::table)
+                .values(&t)
+                .get_result(&conn)?;
+            let raid: Raid = diesel::update(raids::table.find(raid.id))
+                .set(&details)
+                .get_result(&conn)?;
+            let roles = roles
+                .into_iter()
+                .map(|(role_id, quota)| NewRaidRole {
+                    raid_id: raid.id,
+                    role_id,
+                    quota,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(raid_roles::table)
+                .values(&roles)
+                .execute(&conn)?;
+            let bosses = bosses
+                .into_iter()
+                .map(|raid_boss_id| RaidBossMapping {
+                    raid_id: raid.id,
+                    raid_boss_id,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(raid_boss_mappings::table)
+                .values(&bosses)
+                .execute(&conn)?;
+            Ok(raid)
+        })
     })
     .await
     .unwrap()
@@ -108,26 +146,110 @@ async fn insert_raid_role(ctx: &Context, tr: NewRaidRole) -> QueryResult<RaidRol
     .unwrap()
 }
 
-async fn insert_raid_boss_mapping(
+/// Replaces all boss preferences of the sign-up
+async fn replace_signup_boss_preference_mappings(
     ctx: &Context,
-    tbm: RaidBossMapping,
-) -> QueryResult<RaidBossMapping> {
+    signup_id: i32,
+    raid_boss_ids: Vec<i32>,
+) -> QueryResult<Vec<SignupBossPreferenceMapping>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::insert_into(raid_boss_mappings::table)
-            .values(&tbm)
-            .get_result(&pool.conn())
+        let conn = pool.conn();
+        conn.transaction(|| {
+            diesel::delete(
+                signup_boss_preference_mappings::table
+                    .filter(signup_boss_preference_mappings::signup_id.eq(signup_id)),
+            )
+            .execute(&conn)?;
+            let sbpms = raid_boss_ids
+                .into_iter()
+                .map(|raid_boss_id| SignupBossPreferenceMapping {
+                    signup_id,
+                    raid_boss_id,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(signup_boss_preference_mappings::table)
+                .values(&sbpms)
+                .get_results(&conn)
+        })
     })
     .await
     .unwrap()
 }
 
-async fn insert_signup(ctx: &Context, s: NewSignup) -> QueryResult<Signup> {
+/// Puts the sign-up on the waitlist if the raid is full. The raid row stays
+/// locked until the sign-up is saved, so concurrent sign-ups can not both take
+/// the last slot
+async fn insert_signup(ctx: &Context, user_id: i32, raid_id: i32) -> QueryResult<Signup> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::insert_into(signups::table)
-            .values(&s)
-            .get_result(&pool.conn())
+        let conn = pool.conn();
+        conn.transaction(|| {
+            let capacity: Option<i32> = raids::table
+                .find(raid_id)
+                .select(raids::capacity)
+                .for_update()
+                .first(&conn)?;
+            let waitlisted = match capacity {
+                None => false,
+                Some(cap) => {
+                    let count: i64 = signups::table
+                        .filter(signups::raid_id.eq(raid_id))
+                        .filter(signups::waitlisted.eq(false))
+                        .count()
+                        .get_result(&conn)?;
+                    count >= cap as i64
+                }
+            };
+            let s = NewSignup {
+                user_id,
+                raid_id,
+                waitlisted,
+            };
+            diesel::insert_into(signups::table)
+                .values(&s)
+                .get_result(&conn)
+        })
+    })
+    .await
+    .unwrap()
+}
+
+/// Moves waitlisted sign-ups onto the raid in the order they signed up until
+/// it is full. Locks the raid row like `insert_signup`
+async fn update_signups_promote_waitlist(ctx: &Context, raid_id: i32) -> QueryResult<Vec<Signup>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        let conn = pool.conn();
+        conn.transaction(|| {
+            let capacity: Option<i32> = raids::table
+                .find(raid_id)
+                .select(raids::capacity)
+                .for_update()
+                .first(&conn)?;
+            let count: i64 = signups::table
+                .filter(signups::raid_id.eq(raid_id))
+                .filter(signups::waitlisted.eq(false))
+                .count()
+                .get_result(&conn)?;
+            let mut waitlist = signups::table
+                .filter(signups::raid_id.eq(raid_id))
+                .filter(signups::waitlisted.eq(true))
+                .order_by(signups::created_at)
+                .then_order_by(signups::id)
+                .select(signups::id)
+                .into_boxed();
+            if let Some(cap) = capacity {
+                waitlist = waitlist.limit((cap as i64 - count).max(0));
+            }
+            let ids: Vec<i32> = waitlist.load(&conn)?;
+            let mut promoted: Vec<Signup> = diesel::update(signups::table)
+                .filter(signups::id.eq_any(&ids))
+                .set(signups::waitlisted.eq(false))
+                .get_results(&conn)?;
+            promoted.sort_by_key(|s| ids.iter().position(|id| *id == s.id));
+            Ok(promoted)
+        })
     })
     .await
     .unwrap()
@@ -166,12 +288,183 @@ async fn insert_raid_boss(ctx: &Context, tb: NewRaidBoss) -> QueryResult<RaidBos
     .unwrap()
 }
 
+async fn insert_raid_schedule(ctx: &Context, rs: NewRaidSchedule) -> QueryResult<RaidSchedule> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_schedules::table)
+            .values(&rs)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_schedule_role(
+    ctx: &Context,
+    rsr: RaidScheduleRole,
+) -> QueryResult<RaidScheduleRole> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_schedule_roles::table)
+            .values(&rsr)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_schedule_boss(
+    ctx: &Context,
+    rsb: RaidScheduleBoss,
+) -> QueryResult<RaidScheduleBoss> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_schedule_bosses::table)
+            .values(&rsb)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_schedule_skip(
+    ctx: &Context,
+    rss: RaidScheduleSkip,
+) -> QueryResult<RaidScheduleSkip> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_schedule_skips::table)
+            .values(&rss)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_template(ctx: &Context, rt: NewRaidTemplate) -> QueryResult<RaidTemplate> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_templates::table)
+            .values(&rt)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_category(ctx: &Context, rc: NewRaidCategory) -> QueryResult<RaidCategory> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_categories::table)
+            .values(&rc)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_template_role(
+    ctx: &Context,
+    rtr: RaidTemplateRole,
+) -> QueryResult<RaidTemplateRole> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_template_roles::table)
+            .values(&rtr)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_raid_template_boss(
+    ctx: &Context,
+    rtb: RaidTemplateBoss,
+) -> QueryResult<RaidTemplateBoss> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(raid_template_bosses::table)
+            .values(&rtb)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn upsert_attendance(ctx: &Context, att: NewAttendance) -> QueryResult<Attendance> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(attendances::table)
+            .values(&att)
+            .on_conflict(attendances::signup_id)
+            .do_update()
+            .set((
+                attendances::status.eq(att.status),
+                attendances::recorded_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+/// Marks all sign-ups of the raid not on the waitlist as attended. Already
+/// recorded attendances are kept
+async fn insert_default_attendances_by_raid(ctx: &Context, id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        let conn = pool.conn();
+        let atts = signups::table
+            .filter(signups::raid_id.eq(id))
+            .filter(signups::waitlisted.eq(false))
+            .select(signups::id)
+            .load::<i32>(&conn)?
+            .into_iter()
+            .map(|signup_id| NewAttendance {
+                signup_id,
+                status: AttendanceStatus::Attended,
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_into(attendances::table)
+            .values(&atts)
+            .on_conflict_do_nothing()
+            .execute(&conn)
+    })
+    .await
+    .unwrap()
+}
+
+async fn insert_signout(ctx: &Context, so: NewSignout) -> QueryResult<Signout> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(signouts::table)
+            .values(&so)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn upsert_guild(ctx: &Context, guild: Guild) -> QueryResult<Guild> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(guilds::table)
+            .values(&guild)
+            .on_conflict(guilds::id)
+            .do_update()
+            .set(&guild)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 async fn upsert_config(ctx: &Context, conf: Config) -> QueryResult<Config> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         diesel::insert_into(config::table)
             .values(&conf)
-            .on_conflict(config::name)
+            .on_conflict((config::guild_id, config::name))
             .do_update()
             .set(config::value.eq(&conf.value))
             .get_result(&pool.conn())
@@ -180,6 +473,23 @@ async fn upsert_config(ctx: &Context, conf: Config) -> QueryResult<Config> {
     .unwrap()
 }
 
+async fn upsert_board_channel(ctx: &Context, bc: BoardChannel) -> QueryResult<BoardChannel> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::insert_into(signup_board_channels::table)
+            .values(&bc)
+            .on_conflict((
+                signup_board_channels::guild_id,
+                signup_board_channels::weekday,
+            ))
+            .do_update()
+            .set(signup_board_channels::channel_id.eq(bc.channel_id))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 // Delete
 async fn delete_user_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
     let pool = DBPool::load(ctx).await;
@@ -198,6 +508,78 @@ async fn delete_signup_roles_by_signup(ctx: &Context, id: i32) -> QueryResult<us
     .unwrap()
 }
 
+async fn delete_signup_role(ctx: &Context, signup_id: i32, role_id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(signup_roles::table.find((signup_id, role_id))).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_raid_role(ctx: &Context, raid_id: i32, role_id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(raid_roles::table.find((raid_id, role_id))).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+/// Removed bosses are also removed from the preferences of all sign-ups of
+/// the raid
+async fn update_raid_boss_mappings(
+    ctx: &Context,
+    raid_id: i32,
+    added: Vec<i32>,
+    removed: Vec<i32>,
+) -> QueryResult<()> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        let conn = pool.conn();
+        conn.transaction(|| {
+            diesel::delete(
+                signup_boss_preference_mappings::table
+                    .filter(signup_boss_preference_mappings::raid_boss_id.eq_any(&removed))
+                    .filter(
+                        signup_boss_preference_mappings::signup_id.eq_any(
+                            signups::table
+                                .filter(signups::raid_id.eq(raid_id))
+                                .select(signups::id),
+                        ),
+                    ),
+            )
+            .execute(&conn)?;
+            diesel::delete(
+                raid_boss_mappings::table
+                    .filter(raid_boss_mappings::raid_id.eq(raid_id))
+                    .filter(raid_boss_mappings::raid_boss_id.eq_any(&removed)),
+            )
+            .execute(&conn)?;
+            let mappings = added
+                .into_iter()
+                .map(|raid_boss_id| RaidBossMapping {
+                    raid_id,
+                    raid_boss_id,
+                })
+                .collect::<Vec<_>>();
+            diesel::insert_into(raid_boss_mappings::table)
+                .values(&mappings)
+                .execute(&conn)?;
+            Ok(())
+        })
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_attendance_by_signup(ctx: &Context, id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || diesel::delete(attendances::table.find(id)).execute(&pool.conn()))
+        .await
+        .unwrap()
+}
+
 async fn delete_signup_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || diesel::delete(signups::table.find(id)).execute(&pool.conn()))
@@ -234,6 +616,51 @@ async fn delete_raid_boss_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
     .unwrap()
 }
 
+async fn delete_raid_schedule_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(raid_schedules::table.find(id)).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_raid_template_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(raid_templates::table.find(id)).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_raid_category_by_id(ctx: &Context, id: i32) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(raid_categories::table.find(id)).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_config_by_name(ctx: &Context, guild_id: i64, name: String) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(config::table.find((guild_id, name))).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn delete_board_channel(ctx: &Context, guild_id: i64, weekday: i16) -> QueryResult<usize> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::delete(signup_board_channels::table.find((guild_id, weekday))).execute(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 // Select
 async fn select_user_by_id(ctx: &Context, id: i32) -> QueryResult<User> {
     let pool = DBPool::load(ctx).await;
@@ -253,15 +680,29 @@ async fn select_user_by_discord_id(ctx: &Context, discord_id: u64) -> QueryResul
     .unwrap()
 }
 
-async fn select_users_with_signup_by_date(
+async fn select_user_by_calendar_token(ctx: &Context, token: String) -> QueryResult<User> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        users::table
+            .filter(users::calendar_token.eq(token))
+            .first(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_users_with_signup_between(
     ctx: &Context,
-    date: NaiveDate,
+    guild_id: i64,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
 ) -> QueryResult<Vec<User>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         let join = users::table.inner_join(signups::table.inner_join(raids::table));
-        join.filter(raids::date.ge(date.and_hms(0, 0, 0)))
-            .filter(raids::date.le(date.and_hms(23, 59, 59)))
+        join.filter(raids::guild_id.eq(guild_id))
+            .filter(raids::date.ge(from))
+            .filter(raids::date.lt(to))
             .select(users::all_columns)
             .load(&pool.conn())
     })
@@ -283,6 +724,26 @@ async fn select_all_signups_by_user(ctx: &Context, user_id: i32) -> QueryResult<
     .unwrap()
 }
 
+async fn select_joined_upcoming_raids_by_user(
+    ctx: &Context,
+    user_id: i32,
+    since: NaiveDateTime,
+) -> QueryResult<Vec<Raid>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        let join = signups::table
+            .inner_join(users::table)
+            .inner_join(raids::table);
+        join.filter(users::id.eq(user_id))
+            .filter(raids::date.ge(since))
+            .select(raids::all_columns)
+            .order(raids::date)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 async fn select_joined_active_raids_by_user(
     ctx: &Context,
     user_id: i32,
@@ -371,13 +832,31 @@ async fn select_raid_by_id(ctx: &Context, id: i32) -> QueryResult<Raid> {
         .unwrap()
 }
 
+async fn select_raid_by_id_and_guild(
+    ctx: &Context,
+    id: i32,
+    guild_id: i64,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raids::table
+            .find(id)
+            .filter(raids::guild_id.eq(guild_id))
+            .first::<Raid>(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 async fn select_raids_by_state(
     ctx: &Context,
+    guild_id: i64,
     state: RaidState,
 ) -> QueryResult<Vec<Raid>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         raids::table
+            .filter(raids::guild_id.eq(guild_id))
             .filter(raids::state.eq(state))
             .load(&pool.conn())
     })
@@ -401,10 +880,11 @@ async fn select_raid_by_id_and_state(
     .unwrap()
 }
 
-async fn select_active_raids(ctx: &Context) -> QueryResult<Vec<Raid>> {
+async fn select_active_raids(ctx: &Context, guild_id: i64) -> QueryResult<Vec<Raid>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         raids::table
+            .filter(raids::guild_id.eq(guild_id))
             .filter(
                 raids::state
                     .eq(RaidState::Open)
@@ -417,6 +897,30 @@ async fn select_active_raids(ctx: &Context) -> QueryResult<Vec<Raid>> {
     .unwrap()
 }
 
+async fn select_upcoming_raids(
+    ctx: &Context,
+    guild_id: i64,
+    since: NaiveDateTime,
+) -> QueryResult<Vec<Raid>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raids::table
+            .filter(raids::guild_id.eq(guild_id))
+            .filter(raids::date.ge(since))
+            .filter(
+                raids::state
+                    .eq(RaidState::Open)
+                    .or(raids::state.eq(RaidState::Closed))
+                    .or(raids::state.eq(RaidState::Started))
+                    .or(raids::state.eq(RaidState::Cancelled)),
+            )
+            .order(raids::date)
+            .load::<Raid>(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 async fn select_raids_by_tier(ctx: &Context, id: i32) -> QueryResult<Vec<Raid>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
@@ -429,12 +933,18 @@ async fn select_raids_by_tier(ctx: &Context, id: i32) -> QueryResult<Vec<Raid>>
     .unwrap()
 }
 
-async fn select_raids_by_date(ctx: &Context, date: NaiveDate) -> QueryResult<Vec<Raid>> {
+async fn select_raids_between(
+    ctx: &Context,
+    guild_id: i64,
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+) -> QueryResult<Vec<Raid>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         raids::table
-            .filter(raids::date.ge(date.and_hms(0, 0, 0)))
-            .filter(raids::date.le(date.and_hms(23, 59, 59)))
+            .filter(raids::guild_id.eq(guild_id))
+            .filter(raids::date.ge(from))
+            .filter(raids::date.lt(to))
             .select(raids::all_columns)
             .load(&pool.conn())
     })
@@ -454,13 +964,27 @@ async fn select_signups_by_raid(ctx: &Context, id: i32) -> QueryResult<Vec<Signu
     .unwrap()
 }
 
-async fn select_signups_by_date(ctx: &Context, date: NaiveDate) -> QueryResult<Vec<Signup>> {
+async fn select_waitlisted_signups_by_raid(ctx: &Context, id: i32) -> QueryResult<Vec<Signup>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        let join = signups::table.inner_join(raids::table);
-        join.filter(raids::date.ge(date.and_hms(0, 0, 0)))
-            .filter(raids::date.le(date.and_hms(23, 59, 59)))
-            .select(signups::all_columns)
+        signups::table
+            .filter(signups::raid_id.eq(id))
+            .filter(signups::waitlisted.eq(true))
+            .order_by(signups::created_at)
+            .then_order_by(signups::id)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_signups_by_date(ctx: &Context, date: NaiveDate) -> QueryResult<Vec<Signup>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        let join = signups::table.inner_join(raids::table);
+        join.filter(raids::date.ge(date.and_hms(0, 0, 0)))
+            .filter(raids::date.le(date.and_hms(23, 59, 59)))
+            .select(signups::all_columns)
             .load(&pool.conn())
     })
     .await
@@ -500,11 +1024,15 @@ async fn select_signup_by_discord_user_and_raid(
     .unwrap()
 }
 
-async fn select_all_tiers(ctx: &Context) -> QueryResult<Vec<Tier>> {
+async fn select_all_tiers(ctx: &Context, guild_id: i64) -> QueryResult<Vec<Tier>> {
     let pool = DBPool::load(ctx).await;
-    task::spawn_blocking(move || tiers::table.load(&pool.conn()))
-        .await
-        .unwrap()
+    task::spawn_blocking(move || {
+        tiers::table
+            .filter(tiers::guild_id.eq(guild_id))
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
 }
 
 async fn select_tier_by_id(ctx: &Context, id: i32) -> QueryResult<Tier> {
@@ -514,10 +1042,11 @@ async fn select_tier_by_id(ctx: &Context, id: i32) -> QueryResult<Tier> {
         .unwrap()
 }
 
-async fn select_tier_by_name(ctx: &Context, name: String) -> QueryResult<Tier> {
+async fn select_tier_by_name(ctx: &Context, guild_id: i64, name: String) -> QueryResult<Tier> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         tiers::table
+            .filter(tiers::guild_id.eq(guild_id))
             .filter(tiers::name.eq(name))
             .first(&pool.conn())
     })
@@ -569,10 +1098,15 @@ async fn select_raid_roles_by_raid(
     .unwrap()
 }
 
-async fn select_roles_by_active(ctx: &Context, active: bool) -> QueryResult<Vec<Role>> {
+async fn select_roles_by_active(
+    ctx: &Context,
+    guild_id: i64,
+    active: bool,
+) -> QueryResult<Vec<Role>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         roles::table
+            .filter(roles::guild_id.eq(guild_id))
             .filter(roles::active.eq(active))
             .order_by(roles::priority.desc())
             .then_order_by(roles::title)
@@ -582,10 +1116,15 @@ async fn select_roles_by_active(ctx: &Context, active: bool) -> QueryResult<Vec<
     .unwrap()
 }
 
-async fn select_active_role_by_emoji(ctx: &Context, emoji_id: i64) -> QueryResult<Role> {
+async fn select_active_role_by_emoji(
+    ctx: &Context,
+    guild_id: i64,
+    emoji_id: i64,
+) -> QueryResult<Role> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         roles::table
+            .filter(roles::guild_id.eq(guild_id))
             .filter(roles::active.eq(true))
             .filter(roles::emoji.eq(emoji_id))
             .first(&pool.conn())
@@ -594,10 +1133,15 @@ async fn select_active_role_by_emoji(ctx: &Context, emoji_id: i64) -> QueryResul
     .unwrap()
 }
 
-async fn select_active_role_by_repr(ctx: &Context, repr: String) -> QueryResult<Role> {
+async fn select_active_role_by_repr(
+    ctx: &Context,
+    guild_id: i64,
+    repr: String,
+) -> QueryResult<Role> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         roles::table
+            .filter(roles::guild_id.eq(guild_id))
             .filter(roles::active.eq(true))
             .filter(roles::repr.eq(repr))
             .first(&pool.conn())
@@ -639,6 +1183,38 @@ async fn select_active_roles_by_raid(ctx: &Context, id: i32) -> QueryResult<Vec<
     .unwrap()
 }
 
+async fn select_roles_with_quota_by_raid(
+    ctx: &Context,
+    id: i32,
+) -> QueryResult<Vec<(Role, Option<i32>)>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_roles::table
+            .inner_join(roles::table)
+            .filter(raid_roles::raid_id.eq(id))
+            .select((roles::all_columns, raid_roles::quota))
+            .order_by(roles::priority.desc())
+            .then_order_by(roles::title)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_signup_roles_by_raid(ctx: &Context, id: i32) -> QueryResult<Vec<SignupRole>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        signup_roles::table
+            .inner_join(signups::table)
+            .filter(signups::raid_id.eq(id))
+            .filter(signups::waitlisted.eq(false))
+            .select(signup_roles::all_columns)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
 async fn select_roles_by_signup(ctx: &Context, id: i32) -> QueryResult<Vec<Role>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
@@ -655,24 +1231,74 @@ async fn select_roles_by_signup(ctx: &Context, id: i32) -> QueryResult<Vec<Role>
     .unwrap()
 }
 
-async fn select_config_by_name(ctx: &Context, name: String) -> QueryResult<Config> {
+async fn select_config_by_name(ctx: &Context, guild_id: i64, name: String) -> QueryResult<Config> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || config::table.find((guild_id, name)).first(&pool.conn()))
+        .await
+        .unwrap()
+}
+
+async fn select_guild_by_id(ctx: &Context, id: i64) -> QueryResult<Guild> {
     let pool = DBPool::load(ctx).await;
-    task::spawn_blocking(move || config::table.find(name).first(&pool.conn()))
+    task::spawn_blocking(move || guilds::table.find(id).first(&pool.conn()))
         .await
         .unwrap()
 }
 
-async fn select_all_raid_bosses(ctx: &Context) -> QueryResult<Vec<RaidBoss>> {
+async fn select_all_guilds(ctx: &Context) -> QueryResult<Vec<Guild>> {
     let pool = DBPool::load(ctx).await;
-    task::spawn_blocking(move || raid_bosses::table.load(&pool.conn()))
+    task::spawn_blocking(move || guilds::table.load(&pool.conn()))
         .await
         .unwrap()
 }
 
-async fn select_raid_boss_by_repr(ctx: &Context, repr: String) -> QueryResult<RaidBoss> {
+async fn select_board_channel(
+    ctx: &Context,
+    guild_id: i64,
+    weekday: i16,
+) -> QueryResult<BoardChannel> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        signup_board_channels::table
+            .find((guild_id, weekday))
+            .first(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_board_channels(ctx: &Context, guild_id: i64) -> QueryResult<Vec<BoardChannel>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        signup_board_channels::table
+            .filter(signup_board_channels::guild_id.eq(guild_id))
+            .order_by(signup_board_channels::weekday)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_all_raid_bosses(ctx: &Context, guild_id: i64) -> QueryResult<Vec<RaidBoss>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_bosses::table
+            .filter(raid_bosses::guild_id.eq(guild_id))
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_raid_boss_by_repr(
+    ctx: &Context,
+    guild_id: i64,
+    repr: String,
+) -> QueryResult<RaidBoss> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
         raid_bosses::table
+            .filter(raid_bosses::guild_id.eq(guild_id))
             .filter(raid_bosses::repr.eq(repr))
             .first(&pool.conn())
     })
@@ -697,206 +1323,761 @@ async fn select_raid_bosses_by_raid(
     .unwrap()
 }
 
-// Count
-async fn count_raids_by_state(ctx: &Context, state: RaidState) -> QueryResult<i64> {
+async fn select_preferred_raid_bosses_by_signup(
+    ctx: &Context,
+    id: i32,
+) -> QueryResult<Vec<RaidBoss>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        raids::table
-            .filter(raids::state.eq(state))
-            .count()
-            .get_result(&pool.conn())
+        signup_boss_preference_mappings::table
+            .inner_join(signups::table)
+            .inner_join(raid_bosses::table)
+            .filter(signups::id.eq(id))
+            .select(raid_bosses::all_columns)
+            .order_by(raid_bosses::wing)
+            .then_order_by(raid_bosses::position)
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn count_signups_by_raid(ctx: &Context, raid_id: i32) -> QueryResult<i64> {
+async fn select_raids_by_schedule_after(
+    ctx: &Context,
+    id: i32,
+    after: NaiveDateTime,
+) -> QueryResult<Vec<Raid>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        signups::table
-            .filter(signups::raid_id.eq(raid_id))
-            .count()
-            .get_result(&pool.conn())
+        raids::table
+            .filter(raids::schedule_id.eq(id))
+            .filter(raids::date.gt(after))
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn count_active_raids_by_date(ctx: &Context, date: NaiveDate) -> QueryResult<i64> {
+async fn select_all_raid_schedules(ctx: &Context, guild_id: i64) -> QueryResult<Vec<RaidSchedule>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        raids::table
-            .filter(raids::date.ge(date.and_hms(0, 0, 0)))
-            .filter(raids::date.le(date.and_hms(23, 59, 59)))
-            .filter(
-                raids::state
-                    .eq(RaidState::Open)
-                    .or(raids::state.eq(RaidState::Closed))
-                    .or(raids::state.eq(RaidState::Started)),
-            )
-            .count()
-            .get_result(&pool.conn())
+        raid_schedules::table
+            .filter(raid_schedules::guild_id.eq(guild_id))
+            .order_by(raid_schedules::weekday)
+            .then_order_by(raid_schedules::time)
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-// Update
-async fn update_raid_state(
+async fn select_active_raid_schedules(
     ctx: &Context,
-    id: i32,
-    state: RaidState,
-) -> QueryResult<Raid> {
+    guild_id: i64,
+) -> QueryResult<Vec<RaidSchedule>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::update(raids::table.find(id))
-            .set(raids::state.eq(state))
-            .get_result(&pool.conn())
+        raid_schedules::table
+            .filter(raid_schedules::guild_id.eq(guild_id))
+            .filter(raid_schedules::active.eq(true))
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn update_raid_tier(
+async fn select_raid_schedule_by_id(
     ctx: &Context,
+    guild_id: i64,
     id: i32,
-    tier_id: Option<i32>,
-) -> QueryResult<Raid> {
+) -> QueryResult<RaidSchedule> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::update(raids::table.find(id))
-            .set(raids::tier_id.eq(tier_id))
-            .get_result(&pool.conn())
+        raid_schedules::table
+            .find(id)
+            .filter(raid_schedules::guild_id.eq(guild_id))
+            .first(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn update_raid_board_message(
+async fn select_raid_schedule_roles_by_schedule(
     ctx: &Context,
     id: i32,
-    msg_id: Option<i64>,
-) -> QueryResult<Raid> {
+) -> QueryResult<Vec<RaidScheduleRole>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::update(raids::table.find(id))
-            .set(raids::board_message_id.eq(msg_id))
-            .get_result(&pool.conn())
+        raid_schedule_roles::table
+            .filter(raid_schedule_roles::schedule_id.eq(id))
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn update_role_active(ctx: &Context, id: i32, active: bool) -> QueryResult<Role> {
+async fn select_raid_schedule_bosses_by_schedule(
+    ctx: &Context,
+    id: i32,
+) -> QueryResult<Vec<RaidScheduleBoss>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::update(roles::table.find(id))
-            .set(roles::active.eq(active))
-            .get_result(&pool.conn())
+        raid_schedule_bosses::table
+            .filter(raid_schedule_bosses::schedule_id.eq(id))
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-async fn update_signup_comment(
+async fn select_raid_schedule_skips_by_schedule(
     ctx: &Context,
     id: i32,
-    comment: Option<String>,
-) -> QueryResult<Signup> {
+) -> QueryResult<Vec<RaidScheduleSkip>> {
     let pool = DBPool::load(ctx).await;
     task::spawn_blocking(move || {
-        diesel::update(signups::table.find(id))
-            .set(signups::comment.eq(comment))
-            .get_result(&pool.conn())
+        raid_schedule_skips::table
+            .filter(raid_schedule_skips::schedule_id.eq(id))
+            .load(&pool.conn())
     })
     .await
     .unwrap()
 }
 
-/* --- User --- */
-impl User {
-    pub async fn upsert(ctx: &Context, discord_id: u64, gw2_id: String) -> QueryResult<User> {
-        let user = NewUser {
-            discord_id: discord_id as i64,
-            gw2_id,
-        };
-        upsert_user(ctx, user).await
-    }
-
-    pub async fn delete(&self, ctx: &Context) -> QueryResult<usize> {
-        delete_user_by_id(ctx, self.id).await
-    }
+async fn select_all_raid_templates(ctx: &Context, guild_id: i64) -> QueryResult<Vec<RaidTemplate>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_templates::table
+            .filter(raid_templates::guild_id.eq(guild_id))
+            .order_by(raid_templates::name)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn by_discord_id(ctx: &Context, id: UserId) -> QueryResult<User> {
-        select_user_by_discord_id(ctx, *id.as_u64()).await
-    }
+async fn select_raid_template_by_name(
+    ctx: &Context,
+    guild_id: i64,
+    name: String,
+) -> QueryResult<RaidTemplate> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_templates::table
+            .filter(raid_templates::guild_id.eq(guild_id))
+            .filter(raid_templates::name.eq(name))
+            .first(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn joined_active_raids(&self, ctx: &Context) -> QueryResult<Vec<Raid>> {
-        select_joined_active_raids_by_user(ctx, self.id).await
-    }
+async fn select_all_raid_categories(
+    ctx: &Context,
+    guild_id: i64,
+) -> QueryResult<Vec<RaidCategory>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_categories::table
+            .filter(raid_categories::guild_id.eq(guild_id))
+            .order_by((raid_categories::sort_weight.desc(), raid_categories::name))
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn active_signups_with_raid(
-        &self,
-        ctx: &Context,
-    ) -> QueryResult<Vec<(Signup, Raid)>> {
-        select_active_signups_raids_by_user(ctx, self.id).await
-    }
+async fn select_raid_category_by_id(ctx: &Context, id: i32) -> QueryResult<RaidCategory> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || raid_categories::table.find(id).first(&pool.conn()))
+        .await
+        .unwrap()
+}
 
-    pub async fn active_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
-        select_active_signups_by_user(ctx, self.id).await
-    }
+async fn select_raid_category_by_name(
+    ctx: &Context,
+    guild_id: i64,
+    name: String,
+) -> QueryResult<RaidCategory> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_categories::table
+            .filter(raid_categories::guild_id.eq(guild_id))
+            .filter(raid_categories::name.eq(name))
+            .first(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn open_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
-        select_open_signups_by_user(ctx, self.id).await
-    }
+async fn select_roles_with_quota_by_template(
+    ctx: &Context,
+    id: i32,
+) -> QueryResult<Vec<(Role, Option<i32>)>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_template_roles::table
+            .inner_join(roles::table)
+            .filter(raid_template_roles::template_id.eq(id))
+            .select((roles::all_columns, raid_template_roles::quota))
+            .order_by(roles::priority.desc())
+            .then_order_by(roles::title)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn all_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
-        select_all_signups_by_user(ctx, self.id).await
-    }
+async fn select_raid_bosses_by_template(ctx: &Context, id: i32) -> QueryResult<Vec<RaidBoss>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raid_template_bosses::table
+            .inner_join(raid_bosses::table)
+            .filter(raid_template_bosses::template_id.eq(id))
+            .select(raid_bosses::all_columns)
+            .order_by(raid_bosses::wing)
+            .then_order_by(raid_bosses::position)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
 
-    pub async fn by_signed_up_and_date(ctx: &Context, date: NaiveDate) -> QueryResult<Vec<User>> {
-        select_users_with_signup_by_date(ctx, date).await
-    }
+async fn select_raids_to_remind(
+    ctx: &Context,
+    guild_id: i64,
+    until: NaiveDateTime,
+) -> QueryResult<Vec<Raid>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raids::table
+            .filter(raids::guild_id.eq(guild_id))
+            .filter(
+                raids::state
+                    .eq(RaidState::Open)
+                    .or(raids::state.eq(RaidState::Closed)),
+            )
+            .filter(raids::reminded.eq(false))
+            .filter(raids::date.le(until))
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
 }
 
-/* -- Raid -- */
-impl Raid {
-    pub async fn insert(
-        ctx: &Context,
+async fn select_attendances_by_raid(ctx: &Context, id: i32) -> QueryResult<Vec<Attendance>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        attendances::table
+            .inner_join(signups::table)
+            .filter(signups::raid_id.eq(id))
+            .select(attendances::all_columns)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_attendances_by_user(ctx: &Context, id: i32) -> QueryResult<Vec<Attendance>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        attendances::table
+            .inner_join(signups::table)
+            .filter(signups::user_id.eq(id))
+            .select(attendances::all_columns)
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn select_signouts_with_raid_by_user(
+    ctx: &Context,
+    id: i32,
+) -> QueryResult<Vec<(Signout, Raid)>> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        signouts::table
+            .inner_join(raids::table)
+            .filter(signouts::user_id.eq(id))
+            .load(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+// Count
+async fn count_raids_by_state(ctx: &Context, state: RaidState) -> QueryResult<i64> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raids::table
+            .filter(raids::state.eq(state))
+            .count()
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn count_signups_by_raid(
+    ctx: &Context,
+    raid_id: i32,
+    waitlisted: bool,
+) -> QueryResult<i64> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        signups::table
+            .filter(signups::raid_id.eq(raid_id))
+            .filter(signups::waitlisted.eq(waitlisted))
+            .count()
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn count_active_raids_by_date(ctx: &Context, date: NaiveDate) -> QueryResult<i64> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        raids::table
+            .filter(raids::date.ge(date.and_hms(0, 0, 0)))
+            .filter(raids::date.le(date.and_hms(23, 59, 59)))
+            .filter(
+                raids::state
+                    .eq(RaidState::Open)
+                    .or(raids::state.eq(RaidState::Closed))
+                    .or(raids::state.eq(RaidState::Started)),
+            )
+            .count()
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+// Update
+async fn update_raid_state(
+    ctx: &Context,
+    id: i32,
+    state: RaidState,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::state.eq(state))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_tier(
+    ctx: &Context,
+    id: i32,
+    tier_id: Option<i32>,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::tier_id.eq(tier_id))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_category(
+    ctx: &Context,
+    id: i32,
+    category_id: Option<i32>,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::category_id.eq(category_id))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_category_details(
+    ctx: &Context,
+    category: RaidCategory,
+) -> QueryResult<RaidCategory> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raid_categories::table.find(category.id))
+            .set(&category)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_board_message(
+    ctx: &Context,
+    id: i32,
+    channel_id: Option<i64>,
+    msg_id: Option<i64>,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set((
+                raids::board_channel_id.eq(channel_id),
+                raids::board_message_id.eq(msg_id),
+            ))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_title_and_date(
+    ctx: &Context,
+    id: i32,
+    title: String,
+    date: NaiveDateTime,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set((raids::title.eq(title), raids::date.eq(date)))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_details(ctx: &Context, id: i32, details: RaidDetails) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(&details)
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_cancel_reason(
+    ctx: &Context,
+    id: i32,
+    reason: Option<String>,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::cancel_reason.eq(reason))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_reminded(ctx: &Context, id: i32, reminded: bool) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::reminded.eq(reminded))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_user_reminders(ctx: &Context, id: i32, reminders: bool) -> QueryResult<User> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(users::table.find(id))
+            .set(users::reminders.eq(reminders))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_user_calendar_token(
+    ctx: &Context,
+    id: i32,
+    token: Option<String>,
+) -> QueryResult<User> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(users::table.find(id))
+            .set(users::calendar_token.eq(token))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_capacity(
+    ctx: &Context,
+    id: i32,
+    capacity: Option<i32>,
+) -> QueryResult<Raid> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raids::table.find(id))
+            .set(raids::capacity.eq(capacity))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_role_quota(
+    ctx: &Context,
+    raid_id: i32,
+    role_id: i32,
+    quota: Option<i32>,
+) -> QueryResult<RaidRole> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raid_roles::table.find((raid_id, role_id)))
+            .set(raid_roles::quota.eq(quota))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_raid_schedule_active(
+    ctx: &Context,
+    id: i32,
+    active: bool,
+) -> QueryResult<RaidSchedule> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(raid_schedules::table.find(id))
+            .set(raid_schedules::active.eq(active))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_role_active(ctx: &Context, id: i32, active: bool) -> QueryResult<Role> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(roles::table.find(id))
+            .set(roles::active.eq(active))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_signup_needs_role_update(
+    ctx: &Context,
+    id: i32,
+    needs_role_update: bool,
+) -> QueryResult<Signup> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(signups::table.find(id))
+            .set(signups::needs_role_update.eq(needs_role_update))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+async fn update_signup_comment(
+    ctx: &Context,
+    id: i32,
+    comment: Option<String>,
+) -> QueryResult<Signup> {
+    let pool = DBPool::load(ctx).await;
+    task::spawn_blocking(move || {
+        diesel::update(signups::table.find(id))
+            .set(signups::comment.eq(comment))
+            .get_result(&pool.conn())
+    })
+    .await
+    .unwrap()
+}
+
+/* --- User --- */
+impl User {
+    pub async fn upsert(ctx: &Context, discord_id: u64, gw2_id: String) -> QueryResult<User> {
+        let user = NewUser {
+            discord_id: discord_id as i64,
+            gw2_id,
+        };
+        upsert_user(ctx, user).await
+    }
+
+    pub async fn delete(&self, ctx: &Context) -> QueryResult<usize> {
+        delete_user_by_id(ctx, self.id).await
+    }
+
+    pub async fn set_reminders(&self, ctx: &Context, reminders: bool) -> QueryResult<User> {
+        update_user_reminders(ctx, self.id, reminders).await
+    }
+
+    pub async fn by_discord_id(ctx: &Context, id: UserId) -> QueryResult<User> {
+        select_user_by_discord_id(ctx, *id.as_u64()).await
+    }
+
+    pub async fn by_calendar_token(ctx: &Context, token: String) -> QueryResult<User> {
+        select_user_by_calendar_token(ctx, token).await
+    }
+
+    pub async fn set_calendar_token(
+        &self,
+        ctx: &Context,
+        token: Option<String>,
+    ) -> QueryResult<User> {
+        update_user_calendar_token(ctx, self.id, token).await
+    }
+
+    pub async fn joined_active_raids(&self, ctx: &Context) -> QueryResult<Vec<Raid>> {
+        select_joined_active_raids_by_user(ctx, self.id).await
+    }
+
+    /// All raids the user is signed up for taking place after `since`, in any state
+    pub async fn joined_upcoming_raids(
+        &self,
+        ctx: &Context,
+        since: NaiveDateTime,
+    ) -> QueryResult<Vec<Raid>> {
+        select_joined_upcoming_raids_by_user(ctx, self.id, since).await
+    }
+
+    pub async fn active_signups_with_raid(
+        &self,
+        ctx: &Context,
+    ) -> QueryResult<Vec<(Signup, Raid)>> {
+        select_active_signups_raids_by_user(ctx, self.id).await
+    }
+
+    pub async fn active_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
+        select_active_signups_by_user(ctx, self.id).await
+    }
+
+    pub async fn open_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
+        select_open_signups_by_user(ctx, self.id).await
+    }
+
+    pub async fn all_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
+        select_all_signups_by_user(ctx, self.id).await
+    }
+
+    pub async fn attendances(&self, ctx: &Context) -> QueryResult<Vec<Attendance>> {
+        select_attendances_by_user(ctx, self.id).await
+    }
+
+    pub async fn signouts_with_raid(&self, ctx: &Context) -> QueryResult<Vec<(Signout, Raid)>> {
+        select_signouts_with_raid_by_user(ctx, self.id).await
+    }
+
+    /// Users signed up for any raid of the guild taking place between `from` and `to` (exclusive)
+    pub async fn by_signed_up_between(
+        ctx: &Context,
+        guild_id: GuildId,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> QueryResult<Vec<User>> {
+        select_users_with_signup_between(ctx, guild_id.0 as i64, from, to).await
+    }
+}
+
+/* -- Raid -- */
+impl Raid {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert(
+        ctx: &Context,
+        guild_id: GuildId,
         title: String,
         date: NaiveDateTime,
         tier_id: Option<i32>,
+        capacity: Option<i32>,
+        category_id: Option<i32>,
+        open_at: Option<NaiveDateTime>,
+        details: RaidDetails,
+        roles: Vec<(i32, Option<i32>)>,
+        bosses: Vec<i32>,
     ) -> QueryResult<Raid> {
         let t = NewRaid {
             title,
             date,
             tier_id,
+            capacity,
+            schedule_id: None,
+            guild_id: guild_id.0 as i64,
+            category_id,
+            open_at,
         };
-        insert_raid(ctx, t).await
+        insert_raid(ctx, t, details, roles, bosses).await
     }
 
-    pub async fn by_state(ctx: &Context, state: RaidState) -> QueryResult<Vec<Raid>> {
-        select_raids_by_state(ctx, state).await
+    pub async fn by_state(
+        ctx: &Context,
+        guild_id: GuildId,
+        state: RaidState,
+    ) -> QueryResult<Vec<Raid>> {
+        select_raids_by_state(ctx, guild_id.0 as i64, state).await
     }
 
-    pub async fn all_active(ctx: &Context) -> QueryResult<Vec<Raid>> {
-        select_active_raids(ctx).await
+    pub async fn all_active(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Raid>> {
+        select_active_raids(ctx, guild_id.0 as i64).await
+    }
+
+    /// Active and cancelled raids of the guild taking place after `since`
+    pub async fn upcoming(
+        ctx: &Context,
+        guild_id: GuildId,
+        since: NaiveDateTime,
+    ) -> QueryResult<Vec<Raid>> {
+        select_upcoming_raids(ctx, guild_id.0 as i64, since).await
     }
 
     pub async fn amount_by_state(ctx: &Context, state: RaidState) -> QueryResult<i64> {
         count_raids_by_state(ctx, state).await
     }
 
-    pub async fn amount_active_by_day(ctx: &Context, date: NaiveDate) -> QueryResult<i64> {
-        count_active_raids_by_date(ctx, date).await
+    pub async fn amount_active_by_day(ctx: &Context, date: NaiveDate) -> QueryResult<i64> {
+        count_active_raids_by_date(ctx, date).await
+    }
+
+    /// Amount of sign-ups that are not on the waitlist
+    pub async fn get_signup_count(&self, ctx: &Context) -> QueryResult<i64> {
+        count_signups_by_raid(ctx, self.id, false).await
+    }
+
+    pub async fn get_waitlist_count(&self, ctx: &Context) -> QueryResult<i64> {
+        count_signups_by_raid(ctx, self.id, true).await
+    }
+
+    /// Loads all waitlisted sign-ups in the order they signed up
+    pub async fn get_waitlist(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
+        select_waitlisted_signups_by_raid(ctx, self.id).await
+    }
+
+    /// Moves as many sign-ups from the waitlist onto the raid as there are
+    /// free slots. Returns the promoted sign-ups
+    pub async fn promote_waitlist(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
+        update_signups_promote_waitlist(ctx, self.id).await
+    }
+
+    /// Whether new sign-ups have to go on the waitlist
+    pub async fn is_full(&self, ctx: &Context) -> QueryResult<bool> {
+        match self.capacity {
+            None => Ok(false),
+            Some(cap) => Ok(self.get_signup_count(ctx).await? >= cap as i64),
+        }
     }
 
-    pub async fn get_signup_count(&self, ctx: &Context) -> QueryResult<i64> {
-        count_signups_by_raid(ctx, self.id).await
+    pub async fn set_capacity(&self, ctx: &Context, capacity: Option<i32>) -> QueryResult<Raid> {
+        update_raid_capacity(ctx, self.id, capacity).await
     }
 
-    pub async fn by_id(ctx: &Context, id: i32) -> QueryResult<Raid> {
-        select_raid_by_id(ctx, id).await
+    /// Loads the raid only if it belongs to the guild
+    pub async fn by_id(ctx: &Context, guild_id: GuildId, id: i32) -> QueryResult<Raid> {
+        select_raid_by_id_and_guild(ctx, id, guild_id.0 as i64).await
     }
 
     pub async fn by_id_and_state(
@@ -907,14 +2088,63 @@ impl Raid {
         select_raid_by_id_and_state(ctx, id, state).await
     }
 
-    pub async fn by_date(ctx: &Context, date: NaiveDate) -> QueryResult<Vec<Raid>> {
-        select_raids_by_date(ctx, date).await
+    /// Raids of the guild taking place between `from` and `to` (exclusive)
+    pub async fn between(
+        ctx: &Context,
+        guild_id: GuildId,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> QueryResult<Vec<Raid>> {
+        select_raids_between(ctx, guild_id.0 as i64, from, to).await
     }
 
     pub async fn set_state(self, ctx: &Context, state: RaidState) -> QueryResult<Raid> {
         update_raid_state(ctx, self.id, state).await
     }
 
+    pub async fn get_attendances(&self, ctx: &Context) -> QueryResult<Vec<Attendance>> {
+        select_attendances_by_raid(ctx, self.id).await
+    }
+
+    /// Pre-fills the attendance of everyone not on the waitlist once the raid is finished
+    pub async fn prefill_attendances(&self, ctx: &Context) -> QueryResult<usize> {
+        insert_default_attendances_by_raid(ctx, self.id).await
+    }
+
+    pub async fn set_title_and_date(
+        &self,
+        ctx: &Context,
+        title: String,
+        date: NaiveDateTime,
+    ) -> QueryResult<Raid> {
+        update_raid_title_and_date(ctx, self.id, title, date).await
+    }
+
+    pub async fn set_cancel_reason(
+        &self,
+        ctx: &Context,
+        reason: Option<String>,
+    ) -> QueryResult<Raid> {
+        update_raid_cancel_reason(ctx, self.id, reason).await
+    }
+
+    /// Raids of the guild starting before `until` that have not been reminded about yet
+    pub async fn to_remind(
+        ctx: &Context,
+        guild_id: GuildId,
+        until: NaiveDateTime,
+    ) -> QueryResult<Vec<Raid>> {
+        select_raids_to_remind(ctx, guild_id.0 as i64, until).await
+    }
+
+    pub async fn set_reminded(&self, ctx: &Context, reminded: bool) -> QueryResult<Raid> {
+        update_raid_reminded(ctx, self.id, reminded).await
+    }
+
+    pub async fn set_details(&self, ctx: &Context, details: RaidDetails) -> QueryResult<Raid> {
+        update_raid_details(ctx, self.id, details).await
+    }
+
     pub async fn get_tier(&self, ctx: &Context) -> Option<QueryResult<Tier>> {
         match self.tier_id {
             None => None,
@@ -926,29 +2156,88 @@ impl Raid {
         update_raid_tier(ctx, self.id, tier_id).await
     }
 
+    pub async fn get_category(&self, ctx: &Context) -> Option<QueryResult<RaidCategory>> {
+        match self.category_id {
+            None => None,
+            Some(id) => Some(select_raid_category_by_id(ctx, id).await),
+        }
+    }
+
+    pub async fn set_category(&self, ctx: &Context, category_id: Option<i32>) -> QueryResult<Raid> {
+        update_raid_category(ctx, self.id, category_id).await
+    }
+
     pub async fn get_signups(&self, ctx: &Context) -> QueryResult<Vec<Signup>> {
         select_signups_by_raid(ctx, self.id).await
     }
 
-    pub async fn add_role(&self, ctx: &Context, role_id: i32) -> QueryResult<RaidRole> {
+    pub async fn add_role(
+        &self,
+        ctx: &Context,
+        role_id: i32,
+        quota: Option<i32>,
+    ) -> QueryResult<RaidRole> {
         let raid_role = NewRaidRole {
             raid_id: self.id,
             role_id,
+            quota,
         };
         insert_raid_role(ctx, raid_role).await
     }
 
-    pub async fn add_raid_boss(
+    pub async fn remove_role(&self, ctx: &Context, role_id: i32) -> QueryResult<usize> {
+        delete_raid_role(ctx, self.id, role_id).await
+    }
+
+    pub async fn set_role_quota(
         &self,
         ctx: &Context,
-        raid_boss_id: i32,
-    ) -> QueryResult<RaidBossMapping> {
-        let mapping = RaidBossMapping {
-            raid_id: self.id,
-            raid_boss_id,
-        };
-
-        insert_raid_boss_mapping(ctx, mapping).await
+        role_id: i32,
+        quota: Option<i32>,
+    ) -> QueryResult<RaidRole> {
+        update_raid_role_quota(ctx, self.id, role_id, quota).await
+    }
+
+    /// Loads all roles of the raid with their quota and the non waitlisted
+    /// sign-ups for them. A sign-up with several roles is a candidate for each
+    /// of them but only counts as fixed for a role if it selected nothing else
+    pub async fn role_slots(&self, ctx: &Context) -> QueryResult<Vec<RoleSlots>> {
+        let roles = select_roles_with_quota_by_raid(ctx, self.id).await?;
+        let signup_roles = select_signup_roles_by_raid(ctx, self.id).await?;
+        let mut roles_per_signup: HashMap<i32, usize> = HashMap::new();
+        for sr in &signup_roles {
+            *roles_per_signup.entry(sr.signup_id).or_insert(0) += 1;
+        }
+        Ok(roles
+            .into_iter()
+            .map(|(role, quota)| {
+                let selected = signup_roles
+                    .iter()
+                    .filter(|sr| sr.role_id == role.id)
+                    .collect::<Vec<_>>();
+                let fixed = selected
+                    .iter()
+                    .filter(|sr| roles_per_signup[&sr.signup_id] == 1)
+                    .count() as i64;
+                RoleSlots {
+                    role,
+                    quota,
+                    candidates: selected.len() as i64,
+                    fixed,
+                }
+            })
+            .collect())
+    }
+
+    /// Adds and removes bosses at once. Sign-ups lose their preference for
+    /// removed bosses
+    pub async fn update_raid_bosses(
+        &self,
+        ctx: &Context,
+        added: Vec<i32>,
+        removed: Vec<i32>,
+    ) -> QueryResult<()> {
+        update_raid_boss_mappings(ctx, self.id, added, removed).await
     }
 
     pub async fn get_raid_roles(&self, ctx: &Context) -> QueryResult<Vec<RaidRole>> {
@@ -967,23 +2256,37 @@ impl Raid {
         select_active_roles_by_raid(ctx, self.id).await
     }
 
-    pub async fn set_board_msg(&self, ctx: &Context, msg_id: Option<u64>) -> QueryResult<Raid> {
-        update_raid_board_message(ctx, self.id, msg_id.map(|id| id as i64)).await
+    pub async fn set_board_msg(
+        &self,
+        ctx: &Context,
+        msg: Option<(ChannelId, MessageId)>,
+    ) -> QueryResult<Raid> {
+        update_raid_board_message(
+            ctx,
+            self.id,
+            msg.map(|(c, _)| c.0 as i64),
+            msg.map(|(_, m)| m.0 as i64),
+        )
+        .await
+    }
+
+    pub fn board_message(&self) -> Option<(ChannelId, MessageId)> {
+        match (self.board_channel_id, self.board_message_id) {
+            (Some(c), Some(m)) => Some((ChannelId::from(c as u64), MessageId::from(m as u64))),
+            _ => None,
+        }
     }
 
-    pub fn board_message(&self) -> Option<MessageId> {
-        self.board_message_id.map(|id| MessageId::from(id as u64))
+    pub fn guild(&self) -> GuildId {
+        GuildId::from(self.guild_id())
     }
 }
 
 /* -- Signup -- */
 impl Signup {
+    /// The sign-up goes on the waitlist if the raid is full
     pub async fn insert(ctx: &Context, user: &User, raid: &Raid) -> QueryResult<Self> {
-        let new_signup = NewSignup {
-            user_id: user.id,
-            raid_id: raid.id,
-        };
-        insert_signup(ctx, new_signup).await
+        insert_signup(ctx, user.id, raid.id).await
     }
 
     pub async fn add_role(&self, ctx: &Context, role: &Role) -> QueryResult<SignupRole> {
@@ -994,6 +2297,14 @@ impl Signup {
         insert_signup_role(ctx, sr).await
     }
 
+    pub async fn set_preferred_bosses(
+        &self,
+        ctx: &Context,
+        raid_boss_ids: Vec<i32>,
+    ) -> QueryResult<Vec<SignupBossPreferenceMapping>> {
+        replace_signup_boss_preference_mappings(ctx, self.id, raid_boss_ids).await
+    }
+
     pub async fn update_comment(
         &self,
         ctx: &Context,
@@ -1018,6 +2329,38 @@ impl Signup {
         delete_signup_roles_by_signup(ctx, self.id).await
     }
 
+    pub async fn remove_role(&self, ctx: &Context, role_id: i32) -> QueryResult<usize> {
+        delete_signup_role(ctx, self.id, role_id).await
+    }
+
+    pub async fn set_attendance(
+        &self,
+        ctx: &Context,
+        status: AttendanceStatus,
+    ) -> QueryResult<Attendance> {
+        let att = NewAttendance {
+            signup_id: self.id,
+            status,
+        };
+        upsert_attendance(ctx, att).await
+    }
+
+    pub async fn clear_attendance(&self, ctx: &Context) -> QueryResult<usize> {
+        delete_attendance_by_signup(ctx, self.id).await
+    }
+
+    pub async fn set_needs_role_update(
+        &self,
+        ctx: &Context,
+        needs_role_update: bool,
+    ) -> QueryResult<Self> {
+        update_signup_needs_role_update(ctx, self.id, needs_role_update).await
+    }
+
+    pub async fn get_preferred_bosses(&self, ctx: &Context) -> QueryResult<Vec<RaidBoss>> {
+        select_preferred_raid_bosses_by_signup(ctx, self.id).await
+    }
+
     pub async fn by_user_and_raid(
         ctx: &Context,
         u: &User,
@@ -1041,6 +2384,16 @@ impl Signup {
     pub async fn remove(self, ctx: &Context) -> QueryResult<usize> {
         delete_signup_by_id(ctx, self.id).await
     }
+
+    /// Keeps track of the sign-out before the sign-up is removed
+    pub async fn sign_out(self, ctx: &Context) -> QueryResult<usize> {
+        let so = NewSignout {
+            user_id: self.user_id,
+            raid_id: self.raid_id,
+        };
+        insert_signout(ctx, so).await?;
+        self.remove(ctx).await
+    }
 }
 
 /* -- Role -- */
@@ -1048,6 +2401,7 @@ impl Signup {
 impl Role {
     pub async fn insert(
         ctx: &Context,
+        guild_id: GuildId,
         title: String,
         repr: String,
         emoji: u64,
@@ -1058,6 +2412,7 @@ impl Role {
             repr,
             emoji: emoji as i64,
             priority,
+            guild_id: guild_id.0 as i64,
         };
         insert_role(ctx, r).await
     }
@@ -1067,19 +2422,19 @@ impl Role {
         update_role_active(ctx, self.id, false).await
     }
 
-    /// Loads all active roles
-    pub async fn all_active(ctx: &Context) -> QueryResult<Vec<Role>> {
-        select_roles_by_active(ctx, true).await
+    /// Loads all active roles of the guild
+    pub async fn all_active(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Role>> {
+        select_roles_by_active(ctx, guild_id.0 as i64, true).await
     }
 
     /// Loads the current active role associated with provided emoji
-    pub async fn by_emoji(ctx: &Context, emoji: u64) -> QueryResult<Role> {
-        select_active_role_by_emoji(ctx, emoji as i64).await
+    pub async fn by_emoji(ctx: &Context, guild_id: GuildId, emoji: u64) -> QueryResult<Role> {
+        select_active_role_by_emoji(ctx, guild_id.0 as i64, emoji as i64).await
     }
 
     /// Loads the current active role with specified repr
-    pub async fn by_repr(ctx: &Context, repr: String) -> QueryResult<Role> {
-        select_active_role_by_repr(ctx, repr).await
+    pub async fn by_repr(ctx: &Context, guild_id: GuildId, repr: String) -> QueryResult<Role> {
+        select_active_role_by_repr(ctx, guild_id.0 as i64, repr).await
     }
 }
 
@@ -1096,17 +2451,20 @@ impl std::fmt::Display for Role {
 
 // --- Tier ---
 impl Tier {
-    pub async fn insert(ctx: &Context, name: String) -> QueryResult<Tier> {
-        let new_tier = NewTier { name };
+    pub async fn insert(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<Tier> {
+        let new_tier = NewTier {
+            name,
+            guild_id: guild_id.0 as i64,
+        };
         insert_tier(ctx, new_tier).await
     }
 
-    pub async fn all(ctx: &Context) -> QueryResult<Vec<Tier>> {
-        select_all_tiers(ctx).await
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Tier>> {
+        select_all_tiers(ctx, guild_id.0 as i64).await
     }
 
-    pub async fn by_name(ctx: &Context, name: String) -> QueryResult<Tier> {
-        select_tier_by_name(ctx, name).await
+    pub async fn by_name(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<Tier> {
+        select_tier_by_name(ctx, guild_id.0 as i64, name).await
     }
 
     pub async fn add_discord_role(
@@ -1151,18 +2509,150 @@ impl TierMapping {
 
 // --- Config ---
 impl Config {
-    pub async fn load(ctx: &Context, name: String) -> QueryResult<Config> {
-        select_config_by_name(ctx, name).await
+    pub fn new(guild_id: GuildId, name: String, value: String) -> Self {
+        Config {
+            name,
+            value,
+            guild_id: guild_id.0 as i64,
+        }
+    }
+
+    pub async fn load(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<Config> {
+        select_config_by_name(ctx, guild_id.0 as i64, name).await
     }
 
     pub async fn save(self, ctx: &Context) -> QueryResult<Config> {
         upsert_config(ctx, self).await
     }
+
+    pub async fn delete(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<usize> {
+        delete_config_by_name(ctx, guild_id.0 as i64, name).await
+    }
+}
+
+// --- Guild ---
+impl Guild {
+    pub fn guild(&self) -> GuildId {
+        GuildId::from(self.guild_id())
+    }
+
+    pub fn emoji_guild(&self) -> GuildId {
+        GuildId::from(self.emoji_guild_id())
+    }
+
+    pub async fn save(self, ctx: &Context) -> QueryResult<Guild> {
+        upsert_guild(ctx, self).await
+    }
+
+    pub async fn by_id(ctx: &Context, guild_id: GuildId) -> QueryResult<Guild> {
+        select_guild_by_id(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn all(ctx: &Context) -> QueryResult<Vec<Guild>> {
+        select_all_guilds(ctx).await
+    }
+
+    /// Hands everything created before multiple guilds were supported over
+    /// to the guild. Runs on startup before the connection pool exists
+    pub fn adopt_unassigned(conn: &PgConnection, guild_id: GuildId) -> QueryResult<usize> {
+        let id = guild_id.0 as i64;
+        conn.transaction(|| {
+            let mut adopted = 0;
+            adopted += diesel::update(raids::table.filter(raids::guild_id.eq(0)))
+                .set(raids::guild_id.eq(id))
+                .execute(conn)?;
+            adopted += diesel::update(roles::table.filter(roles::guild_id.eq(0)))
+                .set(roles::guild_id.eq(id))
+                .execute(conn)?;
+            adopted += diesel::update(raid_bosses::table.filter(raid_bosses::guild_id.eq(0)))
+                .set(raid_bosses::guild_id.eq(id))
+                .execute(conn)?;
+            adopted += diesel::update(tiers::table.filter(tiers::guild_id.eq(0)))
+                .set(tiers::guild_id.eq(id))
+                .execute(conn)?;
+            adopted += diesel::update(raid_schedules::table.filter(raid_schedules::guild_id.eq(0)))
+                .set(raid_schedules::guild_id.eq(id))
+                .execute(conn)?;
+            adopted += diesel::update(raid_templates::table.filter(raid_templates::guild_id.eq(0)))
+                .set(raid_templates::guild_id.eq(id))
+                .execute(conn)?;
+            adopted +=
+                diesel::update(raid_categories::table.filter(raid_categories::guild_id.eq(0)))
+                    .set(raid_categories::guild_id.eq(id))
+                    .execute(conn)?;
+            adopted += diesel::update(config::table.filter(config::guild_id.eq(0)))
+                .set(config::guild_id.eq(id))
+                .execute(conn)?;
+            Ok(adopted)
+        })
+    }
+
+    /// Same as `save` for the guild configured on startup
+    pub fn upsert_with(conn: &PgConnection, guild: &Guild) -> QueryResult<Guild> {
+        diesel::insert_into(guilds::table)
+            .values(guild)
+            .on_conflict(guilds::id)
+            .do_update()
+            .set(guild)
+            .get_result(conn)
+    }
+}
+
+// --- BoardChannel ---
+impl BoardChannel {
+    pub fn new(guild_id: GuildId, weekday: Weekday, channel_id: ChannelId) -> Self {
+        BoardChannel {
+            guild_id: guild_id.0 as i64,
+            weekday: weekday.num_days_from_monday() as i16,
+            channel_id: channel_id.0 as i64,
+        }
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // stored as days from monday
+        (0..self.weekday).fold(Weekday::Mon, |d, _| d.succ())
+    }
+
+    pub fn channel(&self) -> ChannelId {
+        ChannelId::from(self.channel_id())
+    }
+
+    pub async fn save(self, ctx: &Context) -> QueryResult<BoardChannel> {
+        upsert_board_channel(ctx, self).await
+    }
+
+    pub async fn by_weekday(
+        ctx: &Context,
+        guild_id: GuildId,
+        weekday: Weekday,
+    ) -> QueryResult<BoardChannel> {
+        select_board_channel(
+            ctx,
+            guild_id.0 as i64,
+            weekday.num_days_from_monday() as i16,
+        )
+        .await
+    }
+
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<BoardChannel>> {
+        select_board_channels(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn delete(ctx: &Context, guild_id: GuildId, weekday: Weekday) -> QueryResult<usize> {
+        delete_board_channel(
+            ctx,
+            guild_id.0 as i64,
+            weekday.num_days_from_monday() as i16,
+        )
+        .await
+    }
 }
 
 impl RaidBoss {
+    #[allow(clippy::too_many_arguments)]
     pub async fn insert(
         ctx: &Context,
+        guild_id: GuildId,
         name: String,
         repr: String,
         wing: i32,
@@ -1177,17 +2667,18 @@ impl RaidBoss {
             position,
             emoji: emoji.0 as i64,
             url: url.map(|u| u.to_string()),
+            guild_id: guild_id.0 as i64,
         };
 
         insert_raid_boss(ctx, tb).await
     }
 
-    pub async fn all(ctx: &Context) -> QueryResult<Vec<Self>> {
-        select_all_raid_bosses(ctx).await
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Self>> {
+        select_all_raid_bosses(ctx, guild_id.0 as i64).await
     }
 
-    pub async fn by_repr(ctx: &Context, repr: String) -> QueryResult<Self> {
-        select_raid_boss_by_repr(ctx, repr).await
+    pub async fn by_repr(ctx: &Context, guild_id: GuildId, repr: String) -> QueryResult<Self> {
+        select_raid_boss_by_repr(ctx, guild_id.0 as i64, repr).await
     }
 
     pub async fn delete(&self, ctx: &Context) -> QueryResult<usize> {
@@ -1217,3 +2708,290 @@ impl std::fmt::Display for RaidBoss {
         }
     }
 }
+
+// --- RaidSchedule ---
+impl RaidSchedule {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert(
+        ctx: &Context,
+        guild_id: GuildId,
+        title: String,
+        weekday: Weekday,
+        time: NaiveTime,
+        tier_id: Option<i32>,
+        capacity: Option<i32>,
+        lookahead: i16,
+        category_id: Option<i32>,
+    ) -> QueryResult<Self> {
+        let rs = NewRaidSchedule {
+            title,
+            weekday: weekday.num_days_from_monday() as i16,
+            time,
+            tier_id,
+            capacity,
+            lookahead,
+            guild_id: guild_id.0 as i64,
+            category_id,
+        };
+        insert_raid_schedule(ctx, rs).await
+    }
+
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Self>> {
+        select_all_raid_schedules(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn all_active(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Self>> {
+        select_active_raid_schedules(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn by_id(ctx: &Context, guild_id: GuildId, id: i32) -> QueryResult<Self> {
+        select_raid_schedule_by_id(ctx, guild_id.0 as i64, id).await
+    }
+
+    pub fn weekday(&self) -> Weekday {
+        // Constraint in db guarantees a valid range
+        (0..self.weekday).fold(Weekday::Mon, |d, _| d.succ())
+    }
+
+    /// The next `lookahead` dates in UTC the schedule takes place after `after`.
+    /// Weekday and time are in the guild time zone
+    pub fn upcoming(&self, tz: &Tz, after: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let local = timezone::to_local(tz, after);
+        let days_ahead =
+            (self.weekday as i64 - local.weekday().num_days_from_monday() as i64).rem_euclid(7);
+        let mut next = local.date() + Duration::days(days_ahead);
+        if timezone::to_utc_earliest(tz, next.and_time(self.time)) <= after {
+            next += Duration::weeks(1);
+        }
+        (0..self.lookahead as i64)
+            .map(|w| timezone::to_utc_earliest(tz, (next + Duration::weeks(w)).and_time(self.time)))
+            .collect()
+    }
+
+    pub async fn add_role(
+        &self,
+        ctx: &Context,
+        role_id: i32,
+        quota: Option<i32>,
+    ) -> QueryResult<RaidScheduleRole> {
+        let rsr = RaidScheduleRole {
+            schedule_id: self.id,
+            role_id,
+            quota,
+        };
+        insert_raid_schedule_role(ctx, rsr).await
+    }
+
+    pub async fn add_raid_boss(
+        &self,
+        ctx: &Context,
+        raid_boss_id: i32,
+    ) -> QueryResult<RaidScheduleBoss> {
+        let rsb = RaidScheduleBoss {
+            schedule_id: self.id,
+            raid_boss_id,
+        };
+        insert_raid_schedule_boss(ctx, rsb).await
+    }
+
+    pub async fn get_roles(&self, ctx: &Context) -> QueryResult<Vec<RaidScheduleRole>> {
+        select_raid_schedule_roles_by_schedule(ctx, self.id).await
+    }
+
+    pub async fn get_raid_bosses(&self, ctx: &Context) -> QueryResult<Vec<RaidScheduleBoss>> {
+        select_raid_schedule_bosses_by_schedule(ctx, self.id).await
+    }
+
+    pub async fn skip(&self, ctx: &Context, day: NaiveDate) -> QueryResult<RaidScheduleSkip> {
+        let rss = RaidScheduleSkip {
+            schedule_id: self.id,
+            day,
+        };
+        insert_raid_schedule_skip(ctx, rss).await
+    }
+
+    pub async fn get_skips(&self, ctx: &Context) -> QueryResult<Vec<NaiveDate>> {
+        Ok(select_raid_schedule_skips_by_schedule(ctx, self.id)
+            .await?
+            .into_iter()
+            .map(|s| s.day)
+            .collect())
+    }
+
+    /// Raids already created by this schedule taking place after `after`
+    pub async fn get_raids_after(
+        &self,
+        ctx: &Context,
+        after: NaiveDateTime,
+    ) -> QueryResult<Vec<Raid>> {
+        select_raids_by_schedule_after(ctx, self.id, after).await
+    }
+
+    pub async fn set_active(&self, ctx: &Context, active: bool) -> QueryResult<Self> {
+        update_raid_schedule_active(ctx, self.id, active).await
+    }
+
+    pub async fn get_category(&self, ctx: &Context) -> Option<QueryResult<RaidCategory>> {
+        match self.category_id {
+            None => None,
+            Some(id) => Some(select_raid_category_by_id(ctx, id).await),
+        }
+    }
+
+    /// Creates the raid for the given date with all roles and bosses of the schedule
+    pub async fn create_raid(&self, ctx: &Context, date: NaiveDateTime) -> QueryResult<Raid> {
+        let t = NewRaid {
+            title: self.title.clone(),
+            date,
+            tier_id: self.tier_id,
+            capacity: self.capacity,
+            schedule_id: Some(self.id),
+            guild_id: self.guild_id,
+            category_id: self.category_id,
+            open_at: None,
+        };
+        let roles = self
+            .get_roles(ctx)
+            .await?
+            .into_iter()
+            .map(|r| (r.role_id, r.quota))
+            .collect();
+        let bosses = self
+            .get_raid_bosses(ctx)
+            .await?
+            .into_iter()
+            .map(|b| b.raid_boss_id)
+            .collect();
+        insert_raid(ctx, t, RaidDetails::default(), roles, bosses).await
+    }
+
+    pub async fn delete(self, ctx: &Context) -> QueryResult<usize> {
+        delete_raid_schedule_by_id(ctx, self.id).await
+    }
+}
+
+// --- RaidCategory ---
+impl RaidCategory {
+    pub async fn insert(
+        ctx: &Context,
+        guild_id: GuildId,
+        name: String,
+        sort_weight: i32,
+        color: Option<i32>,
+        emoji: Option<i64>,
+    ) -> QueryResult<Self> {
+        let rc = NewRaidCategory {
+            guild_id: guild_id.0 as i64,
+            name,
+            sort_weight,
+            color,
+            emoji,
+        };
+        insert_raid_category(ctx, rc).await
+    }
+
+    /// Ordered the way raids are listed
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Self>> {
+        select_all_raid_categories(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn by_name(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<Self> {
+        select_raid_category_by_name(ctx, guild_id.0 as i64, name).await
+    }
+
+    pub async fn save(self, ctx: &Context) -> QueryResult<Self> {
+        update_raid_category_details(ctx, self).await
+    }
+
+    /// Raids of the category are left without one
+    pub async fn delete(self, ctx: &Context) -> QueryResult<usize> {
+        delete_raid_category_by_id(ctx, self.id).await
+    }
+}
+
+// --- RaidTemplate ---
+impl RaidTemplate {
+    #[allow(clippy::too_many_arguments)]
+    pub async fn insert(
+        ctx: &Context,
+        guild_id: GuildId,
+        name: String,
+        title: String,
+        description: Option<String>,
+        tier_id: Option<i32>,
+        capacity: Option<i32>,
+        category_id: Option<i32>,
+    ) -> QueryResult<Self> {
+        let rt = NewRaidTemplate {
+            name,
+            title,
+            description,
+            tier_id,
+            capacity,
+            guild_id: guild_id.0 as i64,
+            category_id,
+        };
+        insert_raid_template(ctx, rt).await
+    }
+
+    pub async fn all(ctx: &Context, guild_id: GuildId) -> QueryResult<Vec<Self>> {
+        select_all_raid_templates(ctx, guild_id.0 as i64).await
+    }
+
+    pub async fn by_name(ctx: &Context, guild_id: GuildId, name: String) -> QueryResult<Self> {
+        select_raid_template_by_name(ctx, guild_id.0 as i64, name).await
+    }
+
+    pub async fn add_role(
+        &self,
+        ctx: &Context,
+        role_id: i32,
+        quota: Option<i32>,
+    ) -> QueryResult<RaidTemplateRole> {
+        let rtr = RaidTemplateRole {
+            template_id: self.id,
+            role_id,
+            quota,
+        };
+        insert_raid_template_role(ctx, rtr).await
+    }
+
+    pub async fn add_raid_boss(
+        &self,
+        ctx: &Context,
+        raid_boss_id: i32,
+    ) -> QueryResult<RaidTemplateBoss> {
+        let rtb = RaidTemplateBoss {
+            template_id: self.id,
+            raid_boss_id,
+        };
+        insert_raid_template_boss(ctx, rtb).await
+    }
+
+    /// All roles of the template together with their quota
+    pub async fn get_roles(&self, ctx: &Context) -> QueryResult<Vec<(Role, Option<i32>)>> {
+        select_roles_with_quota_by_template(ctx, self.id).await
+    }
+
+    pub async fn get_raid_bosses(&self, ctx: &Context) -> QueryResult<Vec<RaidBoss>> {
+        select_raid_bosses_by_template(ctx, self.id).await
+    }
+
+    pub async fn get_tier(&self, ctx: &Context) -> Option<QueryResult<Tier>> {
+        match self.tier_id {
+            None => None,
+            Some(id) => Some(select_tier_by_id(ctx, id).await),
+        }
+    }
+
+    pub async fn get_category(&self, ctx: &Context) -> Option<QueryResult<RaidCategory>> {
+        match self.category_id {
+            None => None,
+            Some(id) => Some(select_raid_category_by_id(ctx, id).await),
+        }
+    }
+
+    pub async fn delete(self, ctx: &Context) -> QueryResult<usize> {
+        delete_raid_template_by_id(ctx, self.id).await
+    }
+}
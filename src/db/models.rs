@@ -1,19 +1,27 @@
 use crate::db::schema::{
-    config, roles, signup_roles, signups, tier_mappings, tiers, raid_boss_mappings,
-    raid_bosses, raid_roles, raids, users,
+    config, guilds, roles, signup_board_channels, signup_boss_preference_mappings, signup_roles,
+    signups, tier_mappings, tiers,
+    raid_boss_mappings, raid_bosses, raid_categories, raid_roles, raid_schedule_bosses, raid_schedule_roles,
+    raid_schedule_skips, raid_schedules, raid_template_bosses, raid_template_roles,
+    raid_templates, raids, users,
 };
 use diesel_derive_enum::DbEnum;
 use serde::Serialize;
 use std::{fmt, str};
 
-use chrono::naive::NaiveDateTime;
+use chrono::naive::{NaiveDate, NaiveDateTime, NaiveTime};
 
-#[derive(Identifiable, Queryable, PartialEq, Debug, Serialize)]
+#[derive(Identifiable, Queryable, Hash, PartialEq, Eq, Debug, Serialize)]
 #[table_name = "users"]
 pub struct User {
     pub id: i32,
     pub discord_id: i64,
     pub gw2_id: String,
+    /// Whether the user wants to be reminded about raids by DM
+    pub reminders: bool,
+    /// Secret used in the url of the users calendar feed
+    #[serde(skip)]
+    pub calendar_token: Option<String>,
 }
 
 impl User {
@@ -38,6 +46,10 @@ pub struct Signup {
     pub user_id: i32,
     pub raid_id: i32,
     pub comment: Option<String>,
+    pub waitlisted: bool,
+    pub created_at: NaiveDateTime,
+    /// Set when selected roles were removed from the raid
+    pub needs_role_update: bool,
 }
 
 #[derive(Insertable, Debug)]
@@ -45,6 +57,7 @@ pub struct Signup {
 pub struct NewSignup {
     pub user_id: i32,
     pub raid_id: i32,
+    pub waitlisted: bool,
 }
 
 #[derive(Debug, DbEnum, PartialEq, PartialOrd, Clone, Serialize)]
@@ -55,6 +68,7 @@ pub enum RaidState {
     Closed,
     Started,
     Finished,
+    Cancelled,
 }
 
 impl fmt::Display for RaidState {
@@ -65,6 +79,7 @@ impl fmt::Display for RaidState {
             RaidState::Closed => write!(f, "closed"),
             RaidState::Started => write!(f, "started"),
             RaidState::Finished => write!(f, "finished"),
+            RaidState::Cancelled => write!(f, "cancelled"),
         }
     }
 }
@@ -79,6 +94,7 @@ impl str::FromStr for RaidState {
             "closed" => Ok(RaidState::Closed),
             "started" => Ok(RaidState::Started),
             "finished" => Ok(RaidState::Finished),
+            "cancelled" => Ok(RaidState::Cancelled),
             e => Err(format!("unknown raid state: {}", e)),
         }
     }
@@ -86,6 +102,7 @@ impl str::FromStr for RaidState {
 
 #[derive(Identifiable, Queryable, Associations, PartialEq, Debug, Serialize, Clone)]
 #[belongs_to(Tier)]
+#[belongs_to(RaidCategory, foreign_key = "category_id")]
 #[table_name = "raids"]
 pub struct Raid {
     pub id: i32,
@@ -94,6 +111,73 @@ pub struct Raid {
     pub state: RaidState,
     pub tier_id: Option<i32>,
     pub board_message_id: Option<i64>,
+    pub capacity: Option<i32>,
+    pub schedule_id: Option<i32>,
+    /// When the raid is opened automatically
+    pub open_at: Option<NaiveDateTime>,
+    pub reminded: bool,
+    pub cancel_reason: Option<String>,
+    pub description: Option<String>,
+    /// Discord id of the commander leading the raid
+    pub leader_id: Option<i64>,
+    pub voice_channel_id: Option<i64>,
+    /// In minutes
+    pub duration: i32,
+    pub guild_id: i64,
+    /// Channel of the board message
+    pub board_channel_id: Option<i64>,
+    pub category_id: Option<i32>,
+}
+
+impl Raid {
+    pub fn guild_id(&self) -> u64 {
+        self.guild_id as u64
+    }
+
+    pub fn leader_id(&self) -> Option<u64> {
+        self.leader_id.map(|l| l as u64)
+    }
+
+    pub fn voice_channel_id(&self) -> Option<u64> {
+        self.voice_channel_id.map(|v| v as u64)
+    }
+
+    /// The expected end of the raid
+    pub fn end(&self) -> NaiveDateTime {
+        self.date + chrono::Duration::minutes(self.duration as i64)
+    }
+
+    pub fn details(&self) -> RaidDetails {
+        RaidDetails {
+            description: self.description.clone(),
+            leader_id: self.leader_id,
+            voice_channel_id: self.voice_channel_id,
+            duration: self.duration,
+        }
+    }
+}
+
+/// Everything about a raid that is purely informational
+#[derive(AsChangeset, Clone, PartialEq, Eq, Debug)]
+#[table_name = "raids"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct RaidDetails {
+    pub description: Option<String>,
+    pub leader_id: Option<i64>,
+    pub voice_channel_id: Option<i64>,
+    pub duration: i32,
+}
+
+impl Default for RaidDetails {
+    fn default() -> Self {
+        RaidDetails {
+            description: None,
+            leader_id: None,
+            voice_channel_id: None,
+            // same as the column default
+            duration: 120,
+        }
+    }
 }
 
 #[derive(Insertable, Debug)]
@@ -102,9 +186,14 @@ pub(super) struct NewRaid {
     pub title: String,
     pub date: NaiveDateTime,
     pub tier_id: Option<i32>,
+    pub capacity: Option<i32>,
+    pub schedule_id: Option<i32>,
+    pub guild_id: i64,
+    pub category_id: Option<i32>,
+    pub open_at: Option<NaiveDateTime>,
 }
 
-#[derive(Identifiable, Queryable, Associations, Hash, PartialEq, Eq, Debug, Serialize)]
+#[derive(Identifiable, Queryable, Associations, Hash, PartialEq, Eq, Clone, Debug, Serialize)]
 #[table_name = "roles"]
 pub struct Role {
     pub id: i32,
@@ -113,6 +202,7 @@ pub struct Role {
     pub emoji: i64,
     pub active: bool,
     pub priority: i16,
+    pub guild_id: i64,
 }
 
 #[derive(Insertable, Debug)]
@@ -122,6 +212,7 @@ pub(super) struct NewRole {
     pub repr: String,
     pub emoji: i64,
     pub priority: Option<i16>,
+    pub guild_id: i64,
 }
 
 #[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
@@ -149,6 +240,7 @@ pub(super) struct NewSignupRole {
 pub struct RaidRole {
     pub raid_id: i32,
     pub role_id: i32,
+    pub quota: Option<i32>,
 }
 
 #[derive(Insertable, Debug)]
@@ -156,6 +248,30 @@ pub struct RaidRole {
 pub(super) struct NewRaidRole {
     pub raid_id: i32,
     pub role_id: i32,
+    pub quota: Option<i32>,
+}
+
+/// A role of a raid together with its quota and how many sign-ups selected it
+#[derive(Debug)]
+pub struct RoleSlots {
+    pub role: Role,
+    pub quota: Option<i32>,
+    /// Sign-ups that selected the role, possibly among others
+    pub candidates: i64,
+    /// Sign-ups that selected only this role
+    pub fixed: i64,
+}
+
+impl RoleSlots {
+    /// Whether the quota is reached by sign-ups that can not switch to another
+    /// role. Flexible sign-ups are left out so the role is not reported as full
+    /// while they might still play something else
+    pub fn is_full(&self) -> bool {
+        match self.quota {
+            Some(q) => self.fixed >= q as i64,
+            None => false,
+        }
+    }
 }
 
 #[derive(Identifiable, Queryable, PartialEq, Debug)]
@@ -163,12 +279,14 @@ pub(super) struct NewRaidRole {
 pub struct Tier {
     pub id: i32,
     pub name: String,
+    pub guild_id: i64,
 }
 
 #[derive(Insertable, Debug)]
 #[table_name = "tiers"]
 pub(super) struct NewTier {
     pub name: String,
+    pub guild_id: i64,
 }
 
 #[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
@@ -192,9 +310,55 @@ pub(super) struct NewTierMapping {
 pub struct Config {
     pub name: String,
     pub value: String,
+    pub guild_id: i64,
+}
+
+/// Channel the board messages of raids on that weekday are posted in
+#[derive(Queryable, Insertable, Debug)]
+#[table_name = "signup_board_channels"]
+pub struct BoardChannel {
+    pub guild_id: i64,
+    /// Days from monday
+    pub weekday: i16,
+    pub channel_id: i64,
 }
 
-#[derive(Identifiable, Queryable, Associations, Hash, PartialEq, Eq, Debug, Serialize)]
+impl BoardChannel {
+    pub fn channel_id(&self) -> u64 {
+        self.channel_id as u64
+    }
+}
+
+/// A guild served by the bot. Guilds without an entry only get `/setup`
+#[derive(Identifiable, Queryable, Insertable, AsChangeset, PartialEq, Eq, Clone, Debug)]
+#[table_name = "guilds"]
+pub struct Guild {
+    pub id: i64,
+    pub admin_role_id: i64,
+    pub squadmaker_role_id: i64,
+    /// Guild the role and boss emojis are uploaded to
+    pub emoji_guild_id: i64,
+}
+
+impl Guild {
+    pub fn guild_id(&self) -> u64 {
+        self.id as u64
+    }
+
+    pub fn admin_role_id(&self) -> u64 {
+        self.admin_role_id as u64
+    }
+
+    pub fn squadmaker_role_id(&self) -> u64 {
+        self.squadmaker_role_id as u64
+    }
+
+    pub fn emoji_guild_id(&self) -> u64 {
+        self.emoji_guild_id as u64
+    }
+}
+
+#[derive(Identifiable, Queryable, Associations, Hash, PartialEq, Eq, Clone, Debug, Serialize)]
 #[table_name = "raid_bosses"]
 pub struct RaidBoss {
     pub id: i32,
@@ -204,6 +368,7 @@ pub struct RaidBoss {
     pub position: i32,
     pub emoji: i64,
     pub url: Option<String>,
+    pub guild_id: i64,
 }
 
 #[derive(Insertable, Associations, Debug)]
@@ -215,6 +380,7 @@ pub struct NewRaidBoss {
     pub position: i32,
     pub emoji: i64,
     pub url: Option<String>,
+    pub guild_id: i64,
 }
 
 #[derive(Insertable, Queryable, Associations, Debug, Hash, PartialEq, Eq)]
@@ -223,3 +389,199 @@ pub struct RaidBossMapping {
     pub raid_id: i32,
     pub raid_boss_id: i32,
 }
+
+#[derive(Insertable, Queryable, Associations, Debug, Hash, PartialEq, Eq)]
+#[table_name = "signup_boss_preference_mappings"]
+pub struct SignupBossPreferenceMapping {
+    pub signup_id: i32,
+    pub raid_boss_id: i32,
+}
+
+#[derive(Identifiable, Queryable, Associations, PartialEq, Debug, Clone)]
+#[belongs_to(Tier)]
+#[belongs_to(RaidCategory, foreign_key = "category_id")]
+#[table_name = "raid_schedules"]
+pub struct RaidSchedule {
+    pub id: i32,
+    pub title: String,
+    /// Days from monday
+    pub weekday: i16,
+    pub time: NaiveTime,
+    pub tier_id: Option<i32>,
+    pub capacity: Option<i32>,
+    /// How many upcoming raids are created ahead of time
+    pub lookahead: i16,
+    pub active: bool,
+    pub guild_id: i64,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "raid_schedules"]
+pub(super) struct NewRaidSchedule {
+    pub title: String,
+    pub weekday: i16,
+    pub time: NaiveTime,
+    pub tier_id: Option<i32>,
+    pub capacity: Option<i32>,
+    pub lookahead: i16,
+    pub guild_id: i64,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Insertable, Queryable, Associations, Debug)]
+#[belongs_to(RaidSchedule, foreign_key = "schedule_id")]
+#[table_name = "raid_schedule_roles"]
+pub struct RaidScheduleRole {
+    pub schedule_id: i32,
+    pub role_id: i32,
+    pub quota: Option<i32>,
+}
+
+#[derive(Insertable, Queryable, Associations, Debug)]
+#[belongs_to(RaidSchedule, foreign_key = "schedule_id")]
+#[table_name = "raid_schedule_bosses"]
+pub struct RaidScheduleBoss {
+    pub schedule_id: i32,
+    pub raid_boss_id: i32,
+}
+
+#[derive(Insertable, Queryable, Associations, Debug)]
+#[belongs_to(RaidSchedule, foreign_key = "schedule_id")]
+#[table_name = "raid_schedule_skips"]
+pub struct RaidScheduleSkip {
+    pub schedule_id: i32,
+    pub day: NaiveDate,
+}
+
+/// Groups raids on the board and decides their order
+#[derive(Identifiable, Queryable, AsChangeset, PartialEq, Eq, Debug, Clone)]
+#[table_name = "raid_categories"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct RaidCategory {
+    pub id: i32,
+    pub guild_id: i64,
+    pub name: String,
+    /// Categories with a higher weight are listed first
+    pub sort_weight: i32,
+    /// 0xRRGGBB
+    pub color: Option<i32>,
+    pub emoji: Option<i64>,
+}
+
+impl RaidCategory {
+    pub fn emoji(&self) -> Option<u64> {
+        self.emoji.map(|e| e as u64)
+    }
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "raid_categories"]
+pub(super) struct NewRaidCategory {
+    pub guild_id: i64,
+    pub name: String,
+    pub sort_weight: i32,
+    pub color: Option<i32>,
+    pub emoji: Option<i64>,
+}
+
+#[derive(Identifiable, Queryable, Associations, PartialEq, Debug, Clone)]
+#[belongs_to(Tier)]
+#[belongs_to(RaidCategory, foreign_key = "category_id")]
+#[table_name = "raid_templates"]
+pub struct RaidTemplate {
+    pub id: i32,
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub tier_id: Option<i32>,
+    pub capacity: Option<i32>,
+    pub guild_id: i64,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "raid_templates"]
+pub(super) struct NewRaidTemplate {
+    pub name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub tier_id: Option<i32>,
+    pub capacity: Option<i32>,
+    pub guild_id: i64,
+    pub category_id: Option<i32>,
+}
+
+#[derive(Insertable, Queryable, Associations, Debug)]
+#[belongs_to(RaidTemplate, foreign_key = "template_id")]
+#[table_name = "raid_template_roles"]
+pub struct RaidTemplateRole {
+    pub template_id: i32,
+    pub role_id: i32,
+    pub quota: Option<i32>,
+}
+
+#[derive(Insertable, Queryable, Associations, Debug)]
+#[belongs_to(RaidTemplate, foreign_key = "template_id")]
+#[table_name = "raid_template_bosses"]
+pub struct RaidTemplateBoss {
+    pub template_id: i32,
+    pub raid_boss_id: i32,
+}
+
+#[derive(Debug, DbEnum, PartialEq, Eq, Hash, Clone, Copy, Serialize)]
+#[DieselType = "Attendance_status"]
+pub enum AttendanceStatus {
+    Attended,
+    NoShow,
+    Late,
+    Substitute,
+}
+
+impl fmt::Display for AttendanceStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttendanceStatus::Attended => write!(f, "attended"),
+            AttendanceStatus::NoShow => write!(f, "no-show"),
+            AttendanceStatus::Late => write!(f, "late"),
+            AttendanceStatus::Substitute => write!(f, "substitute"),
+        }
+    }
+}
+
+/// Whether a sign-up actually took part in the raid
+#[derive(Identifiable, Queryable, Associations, PartialEq, Debug, Serialize)]
+#[belongs_to(Signup)]
+#[table_name = "attendances"]
+#[primary_key(signup_id)]
+pub struct Attendance {
+    pub signup_id: i32,
+    pub status: AttendanceStatus,
+    pub recorded_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "attendances"]
+pub(super) struct NewAttendance {
+    pub signup_id: i32,
+    pub status: AttendanceStatus,
+}
+
+/// Kept when a sign-up is removed by the user
+#[derive(Identifiable, Queryable, Associations, PartialEq, Debug)]
+#[belongs_to(User)]
+#[belongs_to(Raid)]
+#[table_name = "signouts"]
+pub struct Signout {
+    pub id: i32,
+    pub user_id: i32,
+    pub raid_id: i32,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[table_name = "signouts"]
+pub(super) struct NewSignout {
+    pub user_id: i32,
+    pub raid_id: i32,
+}
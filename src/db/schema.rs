@@ -7,7 +7,7 @@ table! {
     /// Representation of the `config` table.
     ///
     /// (Automatically generated by Diesel.)
-    config (name) {
+    config (guild_id, name) {
         /// The `name` column of the `config` table.
         ///
         /// Its SQL type is `Text`.
@@ -20,6 +20,12 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         value -> Text,
+        /// The `guild_id` column of the `config` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
     }
 }
 
@@ -67,6 +73,12 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         priority -> Int2,
+        /// The `guild_id` column of the `roles` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
     }
 }
 
@@ -77,13 +89,19 @@ table! {
     /// Representation of the `signup_board_channels` table.
     ///
     /// (Automatically generated by Diesel.)
-    signup_board_channels (day) {
-        /// The `day` column of the `signup_board_channels` table.
+    signup_board_channels (guild_id, weekday) {
+        /// The `guild_id` column of the `signup_board_channels` table.
         ///
-        /// Its SQL type is `Date`.
+        /// Its SQL type is `Int8`.
         ///
         /// (Automatically generated by Diesel.)
-        day -> Date,
+        guild_id -> Int8,
+        /// The `weekday` column of the `signup_board_channels` table.
+        ///
+        /// Its SQL type is `Int2`.
+        ///
+        /// (Automatically generated by Diesel.)
+        weekday -> Int2,
         /// The `channel_id` column of the `signup_board_channels` table.
         ///
         /// Its SQL type is `Int8`.
@@ -171,6 +189,24 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         comment -> Nullable<Text>,
+        /// The `waitlisted` column of the `signups` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        waitlisted -> Bool,
+        /// The `created_at` column of the `signups` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+        /// The `needs_role_update` column of the `signups` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        needs_role_update -> Bool,
     }
 }
 
@@ -217,6 +253,12 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         name -> Text,
+        /// The `guild_id` column of the `tiers` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
     }
 }
 
@@ -293,6 +335,12 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         url -> Nullable<Text>,
+        /// The `guild_id` column of the `raid_bosses` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
     }
 }
 
@@ -316,6 +364,12 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         role_id -> Int4,
+        /// The `quota` column of the `raid_roles` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        quota -> Nullable<Int4>,
     }
 }
 
@@ -363,6 +417,78 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         board_message_id -> Nullable<Int8>,
+        /// The `capacity` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        capacity -> Nullable<Int4>,
+        /// The `schedule_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        schedule_id -> Nullable<Int4>,
+        /// The `open_at` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Timestamp>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        open_at -> Nullable<Timestamp>,
+        /// The `reminded` column of the `raids` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reminded -> Bool,
+        /// The `cancel_reason` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        cancel_reason -> Nullable<Text>,
+        /// The `description` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        description -> Nullable<Text>,
+        /// The `leader_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        leader_id -> Nullable<Int8>,
+        /// The `voice_channel_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        voice_channel_id -> Nullable<Int8>,
+        /// The `duration` column of the `raids` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        duration -> Int4,
+        /// The `guild_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
+        /// The `board_channel_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        board_channel_id -> Nullable<Int8>,
+        /// The `category_id` column of the `raids` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        category_id -> Nullable<Int4>,
     }
 }
 
@@ -392,6 +518,421 @@ table! {
         ///
         /// (Automatically generated by Diesel.)
         gw2_id -> Text,
+        /// The `reminders` column of the `users` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        reminders -> Bool,
+        /// The `calendar_token` column of the `users` table.
+        ///
+        /// Its SQL type is `Nullable<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        calendar_token -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_schedules` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_schedules (id) {
+        /// The `id` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `title` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        title -> Text,
+        /// The `weekday` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Int2`.
+        ///
+        /// (Automatically generated by Diesel.)
+        weekday -> Int2,
+        /// The `time` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Time`.
+        ///
+        /// (Automatically generated by Diesel.)
+        time -> Time,
+        /// The `tier_id` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tier_id -> Nullable<Int4>,
+        /// The `capacity` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        capacity -> Nullable<Int4>,
+        /// The `lookahead` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Int2`.
+        ///
+        /// (Automatically generated by Diesel.)
+        lookahead -> Int2,
+        /// The `active` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Bool`.
+        ///
+        /// (Automatically generated by Diesel.)
+        active -> Bool,
+        /// The `guild_id` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
+        /// The `category_id` column of the `raid_schedules` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        category_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_schedule_roles` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_schedule_roles (schedule_id, role_id) {
+        /// The `schedule_id` column of the `raid_schedule_roles` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        schedule_id -> Int4,
+        /// The `role_id` column of the `raid_schedule_roles` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        role_id -> Int4,
+        /// The `quota` column of the `raid_schedule_roles` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        quota -> Nullable<Int4>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_schedule_bosses` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_schedule_bosses (schedule_id, raid_boss_id) {
+        /// The `schedule_id` column of the `raid_schedule_bosses` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        schedule_id -> Int4,
+        /// The `raid_boss_id` column of the `raid_schedule_bosses` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        raid_boss_id -> Int4,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_schedule_skips` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_schedule_skips (schedule_id, day) {
+        /// The `schedule_id` column of the `raid_schedule_skips` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        schedule_id -> Int4,
+        /// The `day` column of the `raid_schedule_skips` table.
+        ///
+        /// Its SQL type is `Date`.
+        ///
+        /// (Automatically generated by Diesel.)
+        day -> Date,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_templates` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_templates (id) {
+        /// The `id` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `name` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Text,
+        /// The `title` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        title -> Text,
+        /// The `description` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Nullable<Text>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        description -> Nullable<Text>,
+        /// The `tier_id` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        tier_id -> Nullable<Int4>,
+        /// The `capacity` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        capacity -> Nullable<Int4>,
+        /// The `guild_id` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
+        /// The `category_id` column of the `raid_templates` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        category_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_template_roles` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_template_roles (template_id, role_id) {
+        /// The `template_id` column of the `raid_template_roles` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        template_id -> Int4,
+        /// The `role_id` column of the `raid_template_roles` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        role_id -> Int4,
+        /// The `quota` column of the `raid_template_roles` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        quota -> Nullable<Int4>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_template_bosses` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_template_bosses (template_id, raid_boss_id) {
+        /// The `template_id` column of the `raid_template_bosses` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        template_id -> Int4,
+        /// The `raid_boss_id` column of the `raid_template_bosses` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        raid_boss_id -> Int4,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `attendances` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    attendances (signup_id) {
+        /// The `signup_id` column of the `attendances` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        signup_id -> Int4,
+        /// The `status` column of the `attendances` table.
+        ///
+        /// Its SQL type is `Attendance_status`.
+        ///
+        /// (Automatically generated by Diesel.)
+        status -> Attendance_status,
+        /// The `recorded_at` column of the `attendances` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        recorded_at -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `signouts` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    signouts (id) {
+        /// The `id` column of the `signouts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `user_id` column of the `signouts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        user_id -> Int4,
+        /// The `raid_id` column of the `signouts` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        raid_id -> Int4,
+        /// The `created_at` column of the `signouts` table.
+        ///
+        /// Its SQL type is `Timestamp`.
+        ///
+        /// (Automatically generated by Diesel.)
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `guilds` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    guilds (id) {
+        /// The `id` column of the `guilds` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int8,
+        /// The `admin_role_id` column of the `guilds` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        admin_role_id -> Int8,
+        /// The `squadmaker_role_id` column of the `guilds` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        squadmaker_role_id -> Int8,
+        /// The `emoji_guild_id` column of the `guilds` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        emoji_guild_id -> Int8,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::db::*;
+
+    /// Representation of the `raid_categories` table.
+    ///
+    /// (Automatically generated by Diesel.)
+    raid_categories (id) {
+        /// The `id` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        id -> Int4,
+        /// The `guild_id` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Int8`.
+        ///
+        /// (Automatically generated by Diesel.)
+        guild_id -> Int8,
+        /// The `name` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Text`.
+        ///
+        /// (Automatically generated by Diesel.)
+        name -> Text,
+        /// The `sort_weight` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Int4`.
+        ///
+        /// (Automatically generated by Diesel.)
+        sort_weight -> Int4,
+        /// The `color` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Nullable<Int4>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        color -> Nullable<Int4>,
+        /// The `emoji` column of the `raid_categories` table.
+        ///
+        /// Its SQL type is `Nullable<Int8>`.
+        ///
+        /// (Automatically generated by Diesel.)
+        emoji -> Nullable<Int8>,
     }
 }
 
@@ -407,6 +948,24 @@ joinable!(raid_boss_mappings -> raids (raid_id));
 joinable!(raid_roles -> roles (role_id));
 joinable!(raid_roles -> raids (raid_id));
 joinable!(raids -> tiers (tier_id));
+joinable!(raid_schedules -> tiers (tier_id));
+joinable!(raid_schedule_roles -> raid_schedules (schedule_id));
+joinable!(raid_schedule_roles -> roles (role_id));
+joinable!(raid_schedule_bosses -> raid_schedules (schedule_id));
+joinable!(raid_schedule_bosses -> raid_bosses (raid_boss_id));
+joinable!(raid_schedule_skips -> raid_schedules (schedule_id));
+joinable!(raids -> raid_schedules (schedule_id));
+joinable!(raid_templates -> tiers (tier_id));
+joinable!(raid_template_roles -> raid_templates (template_id));
+joinable!(raid_template_roles -> roles (role_id));
+joinable!(raid_template_bosses -> raid_templates (template_id));
+joinable!(raid_template_bosses -> raid_bosses (raid_boss_id));
+joinable!(attendances -> signups (signup_id));
+joinable!(signouts -> users (user_id));
+joinable!(signouts -> raids (raid_id));
+joinable!(raids -> raid_categories (category_id));
+joinable!(raid_schedules -> raid_categories (category_id));
+joinable!(raid_templates -> raid_categories (category_id));
 
 allow_tables_to_appear_in_same_query!(
     config,
@@ -422,4 +981,15 @@ allow_tables_to_appear_in_same_query!(
     raid_roles,
     raids,
     users,
+    raid_schedules,
+    raid_schedule_roles,
+    raid_schedule_bosses,
+    raid_schedule_skips,
+    raid_templates,
+    raid_template_roles,
+    raid_template_bosses,
+    attendances,
+    signouts,
+    guilds,
+    raid_categories,
 );
@@ -0,0 +1,146 @@
+// Picks up changes of raids and sign-ups made outside of the bot, like SQL
+// maintenance or a second instance. The database triggers also report the
+// changes the bot makes itself. The board merges them with its own notifications
+use crate::signup_board;
+use anyhow::{anyhow, bail, Context as ErrContext, Result};
+use native_tls::{Certificate, TlsConnector};
+use postgres_native_tls::MakeTlsConnector;
+use serenity::{
+    client::Context,
+    futures::future,
+    model::id::{ChannelId, GuildId, MessageId},
+};
+use std::{env, fs, time::Duration};
+use tokio::sync::mpsc;
+use tokio_postgres::AsyncMessage;
+use tracing::{error, info, warn};
+use url::Url;
+
+/// Has to match the channel used by the database triggers
+const CHANNEL: &str = "raid_changes";
+const RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Payload format: <guild_id>:<raid_id>. Deleted raids with a board message
+/// add :<board_channel_id>:<board_message_id>
+fn parse_payload(payload: &str) -> Option<(GuildId, i32, Option<(ChannelId, MessageId)>)> {
+    let mut parts = payload.split(':');
+    let guild_id = GuildId::from(parts.next()?.parse::<u64>().ok()?);
+    let raid_id = parts.next()?.parse().ok()?;
+    let board_msg = match (parts.next(), parts.next()) {
+        (Some(chan), Some(msg)) => Some((
+            ChannelId::from(chan.parse::<u64>().ok()?),
+            MessageId::from(msg.parse::<u64>().ok()?),
+        )),
+        (None, None) => None,
+        _ => return None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((guild_id, raid_id, board_msg))
+}
+
+/// Follows the sslmode and sslrootcert of the database url like libpq used by
+/// diesel does. Only verify-ca and verify-full check the certificate, the
+/// latter also the host name. A root certificate turns require into verify-ca.
+/// tokio-postgres supports neither these modes nor sslrootcert, so the returned
+/// url uses require for them and leaves out the certificate
+fn tls_config(database_url: &str) -> Result<(String, MakeTlsConnector)> {
+    let mut url = Url::parse(database_url).context("DATABASE_URL is not a valid url")?;
+
+    let mut sslmode = String::from("prefer");
+    let mut root_cert = None;
+    let mut params = Vec::new();
+    for (key, value) in url.query_pairs().into_owned() {
+        match key.as_str() {
+            "sslmode" => sslmode = value,
+            "sslrootcert" => root_cert = Some(value),
+            _ => params.push((key, value)),
+        }
+    }
+
+    let mut builder = TlsConnector::builder();
+    let sslmode = match (sslmode.as_str(), &root_cert) {
+        ("disable", _) => "disable",
+        ("allow", _) | ("prefer", _) => {
+            builder.danger_accept_invalid_certs(true);
+            "prefer"
+        }
+        ("require", None) => {
+            builder.danger_accept_invalid_certs(true);
+            "require"
+        }
+        ("require", Some(_)) | ("verify-ca", _) => {
+            builder.danger_accept_invalid_hostnames(true);
+            "require"
+        }
+        ("verify-full", _) => "require",
+        (other, _) => bail!("Unknown sslmode: {}", other),
+    };
+    if let Some(path) = root_cert {
+        let pem =
+            fs::read(&path).with_context(|| format!("Failed to read root certificate {}", path))?;
+        builder.add_root_certificate(Certificate::from_pem(&pem)?);
+    }
+
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(&params)
+        .append_pair("sslmode", sslmode);
+    Ok((url.to_string(), MakeTlsConnector::new(builder.build()?)))
+}
+
+async fn listen(ctx: &Context, database_url: &str, tls: MakeTlsConnector) -> Result<()> {
+    let (client, mut connection) = tokio_postgres::connect(database_url, tls).await?;
+
+    // the connection only makes progress while it is polled
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Some(msg) = future::poll_fn(|cx| connection.poll_message(cx)).await {
+            if tx.send(msg).is_err() {
+                break;
+            }
+        }
+    });
+
+    client.batch_execute(&format!("LISTEN {}", CHANNEL)).await?;
+    info!("Listening for database changes");
+
+    while let Some(msg) = rx.recv().await {
+        if let AsyncMessage::Notification(n) = msg? {
+            match parse_payload(n.payload()) {
+                Some((guild_id, raid_id, board_msg)) => {
+                    // the raid is gone, so the board can not remove its message anymore
+                    if let Some((chan, msg)) = board_msg {
+                        if let Err(e) = chan.delete_message(ctx, msg).await {
+                            warn!("Failed to remove board message of raid {}: {}", raid_id, e);
+                        }
+                    }
+                    signup_board::notify_change(ctx, guild_id, &[raid_id]).await
+                }
+                None => warn!("Unexpected database notification: {}", n.payload()),
+            }
+        }
+    }
+
+    Err(anyhow!("Database connection closed"))
+}
+
+pub async fn db_listener_task(ctx: Context) {
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let (database_url, tls) = match tls_config(&database_url) {
+        Ok(conf) => conf,
+        Err(e) => {
+            // retrying would not help. The periodic board refresh still works
+            error!("Database changes are not picked up: {:?}", e);
+            return;
+        }
+    };
+    loop {
+        if let Err(e) = listen(&ctx, &database_url, tls.clone()).await {
+            error!("Listening for database changes failed: {:?}", e);
+        }
+        // changes missed in the meantime are caught by the periodic board refresh
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
@@ -1,12 +1,16 @@
-use crate::db;
-use chrono::{Duration, NaiveDateTime};
+use crate::{db, timezone};
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
 use serenity::{
     builder::{CreateEmbed, CreateEmbedAuthor},
-    model::{id::EmojiId, misc::Mention},
+    model::{
+        id::{ChannelId, EmojiId, UserId},
+        misc::Mention,
+    },
 };
 
 const EMBED_AUTHOR_ICON_URL: &str = "https://cdn.discordapp.com/app-icons/951478616095604786/c688ced5faebc2fc23320fc62be291b9.png?size=128";
-const EMBED_AUTHOR_NAME: &str = "Raid Helper Bot";
+pub(crate) const EMBED_AUTHOR_NAME: &str = "Raid Helper Bot";
 const EMBED_THUMBNAIL: &str =
     "https://cdn.discordapp.com/app-icons/951478616095604786/c688ced5faebc2fc23320fc62be291b9.png?size=256";
 const EMBED_STYLE_COLOR: (u8, u8, u8) = (99, 51, 45);
@@ -44,7 +48,7 @@ fn discord_timestamp(dt: &NaiveDateTime) -> String {
 const GOOGLE_CALENDAR_TIME_FMT: &str = "%Y%m%dT%H%M%SZ";
 pub(crate) fn google_calendar_link(raid: &db::Raid) -> String {
     let begin = raid.date.format(GOOGLE_CALENDAR_TIME_FMT);
-    let end = (raid.date + Duration::hours(2)).format(GOOGLE_CALENDAR_TIME_FMT);
+    let end = raid.end().format(GOOGLE_CALENDAR_TIME_FMT);
     format!(
         "https://calendar.google.com/calendar/event?action=TEMPLATE&dates={}/{}&text={}",
         begin,
@@ -53,15 +57,17 @@ pub(crate) fn google_calendar_link(raid: &db::Raid) -> String {
     )
 }
 
-const RAID_TIME_FMT: &str = "%H:%M (UTC)";
+const RAID_TIME_FMT: &str = "%H:%M";
 
-pub(crate) fn field_raid_date(raid: &db::Raid) -> (String, String, bool) {
+/// The time next to the timestamp is the one of the guild time zone
+pub(crate) fn field_raid_date(raid: &db::Raid, tz: &Tz) -> (String, String, bool) {
     (
         "**Date**".to_string(),
         format!(
-            "{} | [{}]({})",
+            "{} | [{} ({})]({})",
             discord_timestamp(&raid.date),
-            raid.date.format(RAID_TIME_FMT),
+            timezone::to_local(tz, raid.date).format(RAID_TIME_FMT),
+            tz.name(),
             google_calendar_link(raid),
         ),
         false,
@@ -105,6 +111,32 @@ pub fn embed_add_roles(e: &mut CreateEmbed, roles: &[db::Role], inline: bool, re
     }
 }
 
+/// Formats a duration in minutes. Example: 2h 30m
+pub(crate) fn duration_fmt(minutes: i32) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{}m", m),
+        (h, 0) => format!("{}h", h),
+        (h, m) => format!("{}h {}m", h, m),
+    }
+}
+
+pub fn embed_add_raid_details(e: &mut CreateEmbed, details: &db::RaidDetails) {
+    if let Some(d) = &details.description {
+        e.field("Description", d, false);
+    }
+    if let Some(l) = details.leader_id {
+        e.field("Leader", Mention::from(UserId::from(l as u64)), true);
+    }
+    if let Some(v) = details.voice_channel_id {
+        e.field(
+            "Voice Channel",
+            Mention::from(ChannelId::from(v as u64)),
+            true,
+        );
+    }
+    e.field("Duration", duration_fmt(details.duration), true);
+}
+
 fn internal_register_embed(e: &mut CreateEmbed) {
     e.description(
         "To register with the bot simply use the register slash command: `/register` in any channel \
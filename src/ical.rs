@@ -0,0 +1,237 @@
+use crate::db::{self, RaidState};
+use chrono::{Duration, NaiveDateTime, Utc};
+
+const PRODID: &str = "-//Raid Helper Bot//Raids//EN";
+/// Together with the raid id this makes up the UID of an event. It must never
+/// change or calendars will start to duplicate events on re-import
+const UID_DOMAIN: &str = "raid-helper-bot";
+const TIME_FMT: &str = "%Y%m%dT%H%M%SZ";
+/// Content lines should not be longer than 75 octets, excluding the line break
+const MAX_LINE_OCTETS: usize = 75;
+/// Raids that started this many hours ago are still exported
+const PAST_HOURS: i64 = 12;
+
+/// Raids taking place after this are of interest for calendars
+pub fn export_since() -> NaiveDateTime {
+    Utc::now().naive_utc() - Duration::hours(PAST_HOURS)
+}
+
+/// Escapes a TEXT value as defined in RFC 5545 3.3.11
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            ';' => out.push_str("\\;"),
+            ',' => out.push_str("\\,"),
+            '\n' => out.push_str("\\n"),
+            '\r' => (),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Adds a content line, folding it as described in RFC 5545 3.1
+fn push_line(out: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // the leading space of the continuation counts as well
+            octets = 1;
+        }
+        out.push(c);
+        octets += c.len_utf8();
+    }
+    out.push_str("\r\n");
+}
+
+fn push_event(out: &mut String, raid: &db::Raid, stamp: &NaiveDateTime) {
+    push_line(out, "BEGIN:VEVENT");
+    push_line(out, &format!("UID:raid-{}@{}", raid.id, UID_DOMAIN));
+    push_line(out, &format!("DTSTAMP:{}", stamp.format(TIME_FMT)));
+    push_line(out, &format!("DTSTART:{}", raid.date.format(TIME_FMT)));
+    push_line(out, &format!("DTEND:{}", raid.end().format(TIME_FMT)));
+    push_line(out, &format!("SUMMARY:{}", escape(&raid.title)));
+
+    let mut description = raid.description.clone().unwrap_or_default();
+    if let Some(reason) = &raid.cancel_reason {
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&format!("Cancelled: {}", reason));
+    }
+    if !description.is_empty() {
+        push_line(out, &format!("DESCRIPTION:{}", escape(&description)));
+    }
+
+    let status = match raid.state {
+        RaidState::Cancelled => "CANCELLED",
+        RaidState::Created => "TENTATIVE",
+        _ => "CONFIRMED",
+    };
+    push_line(out, &format!("STATUS:{}", status));
+    push_line(out, "END:VEVENT");
+}
+
+/// Creates an iCalendar (.ics) file containing one event per raid
+pub fn calendar(name: &str, raids: &[db::Raid]) -> String {
+    let stamp = Utc::now().naive_utc();
+
+    let mut out = String::new();
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, &format!("PRODID:{}", PRODID));
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, "METHOD:PUBLISH");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape(name)));
+    for raid in raids {
+        push_event(&mut out, raid, &stamp);
+    }
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn raid(id: i32, state: RaidState) -> db::Raid {
+        db::Raid {
+            id,
+            title: String::from("Raid"),
+            date: NaiveDate::from_ymd(2022, 6, 17).and_hms(18, 30, 0),
+            state,
+            tier_id: None,
+            board_message_id: None,
+            capacity: None,
+            schedule_id: None,
+            open_at: None,
+            reminded: false,
+            cancel_reason: None,
+            description: None,
+            leader_id: None,
+            voice_channel_id: None,
+            duration: 120,
+            guild_id: 1,
+            board_channel_id: None,
+            category_id: None,
+        }
+    }
+
+    fn event(raid: &db::Raid) -> String {
+        let mut out = String::new();
+        push_event(&mut out, raid, &Utc::now().naive_utc());
+        out
+    }
+
+    fn property<'a>(out: &'a str, name: &str) -> Option<&'a str> {
+        out.split("\r\n").find_map(|l| l.strip_prefix(name))
+    }
+
+    // a single content line split into lines that are short enough, all but
+    // the first starting with a space
+    fn assert_folded(out: &str) {
+        let lines: Vec<&str> = out.strip_suffix("\r\n").unwrap().split("\r\n").collect();
+        assert!(lines.iter().all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert!(lines.iter().skip(1).all(|l| l.starts_with(' ')));
+    }
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(escape("a,b;c\\d"), "a\\,b\\;c\\\\d");
+        assert_eq!(escape("first\r\nsecond\nthird"), "first\\nsecond\\nthird");
+        assert_eq!(escape("Ünïcödé stays"), "Ünïcödé stays");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let mut out = String::new();
+        push_line(&mut out, "SUMMARY:Raid");
+        assert_eq!(out, "SUMMARY:Raid\r\n");
+
+        let mut out = String::new();
+        let line = "X".repeat(MAX_LINE_OCTETS);
+        push_line(&mut out, &line);
+        assert_eq!(out, format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn long_lines_are_folded() {
+        let line = format!("SUMMARY:{}", "X".repeat(200));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+
+        assert_folded(&out);
+        assert_eq!(out.matches("\r\n").count(), 3);
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn folding_keeps_characters_whole() {
+        // two and four octet characters, some of them would cross the limit
+        let line = format!("SUMMARY:{}", "Ä🐉".repeat(40));
+        let mut out = String::new();
+        push_line(&mut out, &line);
+
+        assert_folded(&out);
+        // lines are only broken where the next character does not fit anymore
+        let lines: Vec<&str> = out.strip_suffix("\r\n").unwrap().split("\r\n").collect();
+        for pair in lines.windows(2) {
+            let next = pair[1].chars().nth(1).unwrap();
+            assert!(pair[0].len() + next.len_utf8() > MAX_LINE_OCTETS);
+        }
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", line));
+    }
+
+    #[test]
+    fn uid_only_depends_on_the_raid_id() {
+        let mut changed = raid(7, RaidState::Cancelled);
+        changed.title = String::from("Something else");
+        changed.date = NaiveDate::from_ymd(2022, 7, 1).and_hms(20, 0, 0);
+
+        let uid = "raid-7@raid-helper-bot";
+        assert_eq!(
+            property(&event(&raid(7, RaidState::Open)), "UID:"),
+            Some(uid)
+        );
+        assert_eq!(property(&event(&changed), "UID:"), Some(uid));
+        assert_ne!(
+            property(&event(&raid(8, RaidState::Open)), "UID:"),
+            Some(uid)
+        );
+    }
+
+    #[test]
+    fn status_follows_the_raid_state() {
+        for (state, status) in [
+            (RaidState::Created, "TENTATIVE"),
+            (RaidState::Open, "CONFIRMED"),
+            (RaidState::Closed, "CONFIRMED"),
+            (RaidState::Started, "CONFIRMED"),
+            (RaidState::Finished, "CONFIRMED"),
+            (RaidState::Cancelled, "CANCELLED"),
+        ] {
+            assert_eq!(property(&event(&raid(1, state)), "STATUS:"), Some(status));
+        }
+    }
+
+    #[test]
+    fn event_uses_utc_times_and_escaped_text() {
+        let mut r = raid(1, RaidState::Cancelled);
+        r.title = String::from("Beginner, Wing 1");
+        r.cancel_reason = Some(String::from("No commander; sorry"));
+        let out = event(&r);
+
+        assert!(out.split("\r\n").all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert_eq!(property(&out, "DTSTART:"), Some("20220617T183000Z"));
+        assert_eq!(property(&out, "DTEND:"), Some("20220617T203000Z"));
+        assert_eq!(property(&out, "SUMMARY:"), Some("Beginner\\, Wing 1"));
+        assert_eq!(
+            property(&out, "DESCRIPTION:"),
+            Some("Cancelled: No commander\\; sorry")
+        );
+    }
+}
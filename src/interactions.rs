@@ -32,6 +32,7 @@ impl std::error::Error for GlobalInteractionParseError {}
 #[non_exhaustive]
 pub enum GlobalInteraction {
     Overview(OverviewMessageInteraction),
+    Raid(RaidMessageInteraction),
 }
 
 impl std::str::FromStr for GlobalInteraction {
@@ -41,6 +42,9 @@ impl std::str::FromStr for GlobalInteraction {
         if let Ok(bgi) = s.parse::<OverviewMessageInteraction>() {
             return Ok(Self::Overview(bgi));
         }
+        if let Ok(rmi) = s.parse::<RaidMessageInteraction>() {
+            return Ok(Self::Raid(rmi));
+        }
         Err(GlobalInteractionParseError {})
     }
 }
@@ -49,6 +53,7 @@ impl std::fmt::Display for GlobalInteraction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Overview(bgi) => write!(f, "{}", bgi),
+            Self::Raid(rmi) => write!(f, "{}", rmi),
         }
     }
 }
@@ -84,6 +89,43 @@ impl std::fmt::Display for OverviewMessageInteraction {
     }
 }
 
+/// Interactions on the board message of a single raid
+#[derive(Debug, Clone)]
+pub enum RaidMessageInteraction {
+    ManageSignup(i32),
+}
+
+impl std::str::FromStr for RaidMessageInteraction {
+    type Err = GlobalInteractionParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split('_').collect();
+        if parts.len() != 3 {
+            return Err(GlobalInteractionParseError {});
+        }
+        if !(*parts.get(0).unwrap()).eq("raid") {
+            return Err(GlobalInteractionParseError {});
+        }
+        let id = parts
+            .get(2)
+            .unwrap()
+            .parse::<i32>()
+            .map_err(|_| GlobalInteractionParseError {})?;
+        match *parts.get(1).unwrap() {
+            "managesignup" => Ok(Self::ManageSignup(id)),
+            _ => Err(GlobalInteractionParseError {}),
+        }
+    }
+}
+
+impl std::fmt::Display for RaidMessageInteraction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ManageSignup(id) => write!(f, "raid_managesignup_{}", id),
+        }
+    }
+}
+
 pub fn overview_action_row() -> CreateActionRow {
     let mut ar = CreateActionRow::default();
 
@@ -97,6 +139,19 @@ pub fn overview_action_row() -> CreateActionRow {
     ar
 }
 
+pub fn raid_action_row(raid_id: i32) -> CreateActionRow {
+    let mut ar = CreateActionRow::default();
+
+    let mut b = CreateButton::default();
+    b.style(ButtonStyle::Primary);
+    b.custom_id(RaidMessageInteraction::ManageSignup(raid_id));
+    b.label(COMPONENT_MANAGE_SIGNUPS);
+    b.emoji(ReactionType::from(MEMO_EMOJI));
+    ar.add_button(b);
+
+    ar
+}
+
 async fn button_general_interaction(
     ctx: &Context,
     mci: Arc<MessageComponentInteraction>,
@@ -105,7 +160,22 @@ async fn button_general_interaction(
     log_discord(ctx, mci.clone().as_ref(), |trace| async move {
         match ovi {
             OverviewMessageInteraction::ManageSignups => {
-                manage_sign_up::interaction(ctx, mci, trace).await
+                manage_sign_up::interaction(ctx, mci, None, trace).await
+            }
+        }
+    })
+    .await
+}
+
+async fn button_raid_interaction(
+    ctx: &Context,
+    mci: Arc<MessageComponentInteraction>,
+    rmi: &RaidMessageInteraction,
+) {
+    log_discord(ctx, mci.clone().as_ref(), |trace| async move {
+        match rmi {
+            RaidMessageInteraction::ManageSignup(id) => {
+                manage_sign_up::interaction(ctx, mci, Some(*id), trace).await
             }
         }
     })
@@ -119,6 +189,7 @@ pub async fn button_interaction(ctx: &Context, mci: MessageComponentInteraction)
     if let Ok(bi) = mci.data.custom_id.parse::<GlobalInteraction>() {
         match &bi {
             GlobalInteraction::Overview(bgi) => button_general_interaction(ctx, mci, bgi).await,
+            GlobalInteraction::Raid(rmi) => button_raid_interaction(ctx, mci, rmi).await,
         }
     };
 }
@@ -1,5 +1,6 @@
 use std::{
     cmp::Reverse,
+    collections::HashMap,
     fmt::{Display, Error as FmtError},
     str::FromStr,
     sync::Arc,
@@ -22,17 +23,18 @@ use serenity::{
     },
 };
 use serenity_tools::{
-    builder::CreateEmbedExt,
-    collectors::{PagedSelectorConfig, PagedSelectorError, UpdatAbleMessage},
+    builder::{CreateActionRowExt, CreateEmbedExt},
+    collectors::{MessageCollectorExt, PagedSelectorConfig, PagedSelectorError, UpdatAbleMessage},
     components::Button,
     interactions::MessageComponentInteractionExt,
 };
 
 use crate::{
-    data, db,
+    db,
     embeds::{self, CrossroadsEmbeds},
     logging::{self, LogTrace, ReplyHelper},
-    signup_board::title_sort_value,
+    reliability::{Reliability, ReliabilityConfig},
+    signup_board, timezone, waitlist,
 };
 
 enum Buttons {
@@ -50,7 +52,7 @@ impl Display for Buttons {
             Self::Join => write!(f, "Sign Up"),
             Self::Leave => write!(f, "Sign Out"),
             Self::EditRoles => write!(f, "Edit Roles"),
-            Self::EditPreferences => write!(f, "Edit Boss Preferences (soon TM)"),
+            Self::EditPreferences => write!(f, "Edit Boss Preferences"),
             Self::AddComment => write!(f, "Add/Edit a Comment"),
             Self::BackToSelection => write!(f, "Back to Selection"),
         }
@@ -94,7 +96,7 @@ impl Buttons {
             Self::Join => b.style(ButtonStyle::Success),
             Self::Leave => b.style(ButtonStyle::Danger),
             Self::EditRoles => b.style(ButtonStyle::Primary),
-            Self::EditPreferences => b.style(ButtonStyle::Primary).disabled(true),
+            Self::EditPreferences => b.style(ButtonStyle::Primary),
             Self::AddComment => b.style(ButtonStyle::Primary),
             Self::BackToSelection => b
                 .style(ButtonStyle::Secondary)
@@ -108,6 +110,7 @@ impl Buttons {
 pub(crate) async fn interaction(
     ctx: &Context,
     mut mci: Arc<MessageComponentInteraction>,
+    mut preselected: Option<i32>,
     trace: LogTrace,
 ) -> Result<()> {
     trace.step("Preparing interaction");
@@ -115,15 +118,14 @@ pub(crate) async fn interaction(
     mci.create_quick_info(ctx, "Loading...", true).await?;
     let mut msg = mci.get_interaction_response(ctx).await?;
 
-    let guild_id = {
-        ctx.data
-            .read()
-            .await
-            .get::<data::ConfigValuesData>()
-            .unwrap()
-            .clone()
-            .main_guild_id
-    };
+    // the board the interaction came from decides the guild
+    let guild_id = mci
+        .guild_id
+        .context("Sign-ups are only possible from a signup board")?;
+
+    // raids are listed by the day in the guild time zone
+    let tz = timezone::load(ctx, guild_id).await?;
+    let local_day = |r: &db::Raid| timezone::to_local(&tz, r.date).date();
 
     loop {
         trace.step("Refreshing information");
@@ -141,7 +143,7 @@ pub(crate) async fn interaction(
             }
             Err(e) => bail!(e),
         };
-        let raids_all = db::Raid::all_active(ctx).await?;
+        let raids_all = db::Raid::all_active(ctx, guild_id).await?;
         let mut raids: Vec<db::Raid> = Vec::with_capacity(raids_all.len());
 
         for raid in raids_all {
@@ -190,19 +192,37 @@ pub(crate) async fn interaction(
         }
 
         // Sort raids -> splitted raids will also be sorted
-        raids.sort_by_key(|b| Reverse(title_sort_value(b)));
+        let weights: HashMap<i32, i32> = db::RaidCategory::all(ctx, guild_id)
+            .await?
+            .into_iter()
+            .map(|c| (c.id, c.sort_weight))
+            .collect();
+        // raids without a category are placed like a category with weight 0
+        raids.sort_by_key(|b| {
+            Reverse(
+                b.category_id
+                    .and_then(|c| weights.get(&c).copied())
+                    .unwrap_or(0),
+            )
+        });
         raids.sort_by_key(|t| t.date);
 
         let signups = db_user.active_signups(ctx).await?;
         let mut joined: Vec<&db::Raid> = Vec::with_capacity(raids.len());
         let mut not_joined: Vec<&db::Raid> = Vec::with_capacity(raids.len());
 
+        // raids where a new sign-up would end up on the waitlist
+        let mut full: Vec<i32> = Vec::new();
+
         for t in &raids {
             if signups.iter().map(|s| s.raid_id).contains(&t.id) {
                 joined.push(t);
             } else {
                 // only show raids still open for not yet joined
                 if t.state == db::RaidState::Open {
+                    if t.is_full(ctx).await? {
+                        full.push(t.id);
+                    }
                     not_joined.push(t);
                 }
             }
@@ -214,14 +234,25 @@ pub(crate) async fn interaction(
         emb.description("**Feel free to dismiss this message once your are done**");
 
         let mut joined_str = String::new();
-        for (d, v) in joined.iter().group_by(|t| t.date.date()).into_iter() {
+        for (d, v) in joined.iter().group_by(|t| local_day(t)).into_iter() {
             joined_str.push_str(&format!("```\n{}\n\n", d.format("%A, %v")));
             for t in v {
-                if t.state == db::RaidState::Open {
-                    joined_str.push_str(&format!("> {}\n", &t.title));
-                } else {
-                    joined_str.push_str(&format!("> {} 🔒\n", &t.title));
+                let waitlisted = signups.iter().any(|s| s.raid_id == t.id && s.waitlisted);
+                let needs_role_update = signups
+                    .iter()
+                    .any(|s| s.raid_id == t.id && s.needs_role_update);
+                let mut line = format!("> {}", &t.title);
+                if t.state != db::RaidState::Open {
+                    line.push_str(" 🔒");
+                }
+                if waitlisted {
+                    line.push_str(" ⏳");
                 }
+                if needs_role_update {
+                    line.push_str(" ⚠️");
+                }
+                line.push('\n');
+                joined_str.push_str(&line);
             }
             joined_str.push_str("```");
         }
@@ -236,10 +267,14 @@ pub(crate) async fn interaction(
             .collect();
 
         let mut not_joined_str = String::new();
-        for (d, v) in not_joined.iter().group_by(|t| t.date.date()).into_iter() {
+        for (d, v) in not_joined.iter().group_by(|t| local_day(t)).into_iter() {
             not_joined_str.push_str(&format!("```\n{}\n\n", d.format("%A, %v")));
             for t in v {
-                not_joined_str.push_str(&format!("> {}\n", &t.title));
+                if full.contains(&t.id) {
+                    not_joined_str.push_str(&format!("> {} ⏳\n", &t.title));
+                } else {
+                    not_joined_str.push_str(&format!("> {}\n", &t.title));
+                }
             }
             not_joined_str.push_str("```");
         }
@@ -250,66 +285,91 @@ pub(crate) async fn interaction(
         emb.field("🤔 How to",
             "```To sign up, sign out or to edit your sign-up simply select the raid from the select menu below\n\n\
             📝 => Sign out or edit your existing sign-up\n\
-            🟢 => Sign up for this raid\n```",
+            🟢 => Sign up for this raid\n\
+            ⏳ => Raid is full. New sign-ups go onto the waitlist\n\
+            ⚠️ => Roles of the raid changed. Please select your roles again\n```",
             false);
 
-        let mut select_menu = CreateSelectMenu::default();
-        select_menu.custom_id("_user_raid_select");
-        select_menu.placeholder("Select a raid to continue");
-        select_menu.options(|opts| {
-            for t in &joined {
-                opts.create_option(|o| {
-                    o.label(format!("| {} {}", t.date.date().format("%A"), t.title));
-                    o.emoji(ReactionType::from('📝'));
-                    o.value(t.id);
-                    o
-                });
+        let selected_id = match preselected.take() {
+            // coming from the board message of a single raid
+            Some(id) => {
+                if !joined.iter().chain(not_joined.iter()).any(|t| t.id == id) {
+                    mci.edit_quick_info(
+                        ctx,
+                        "You can not sign up for this raid or change your sign-up anymore",
+                    )
+                    .await?;
+                    return Ok(());
+                }
+                id
             }
-            for t in &not_joined {
-                opts.create_option(|o| {
-                    o.label(format!("| {} {}", t.date.date().format("%A"), t.title));
-                    o.emoji(ReactionType::from('🟢'));
-                    o.value(t.id);
-                    o
+            None => {
+                let mut select_menu = CreateSelectMenu::default();
+                select_menu.custom_id("_user_raid_select");
+                select_menu.placeholder("Select a raid to continue");
+                select_menu.options(|opts| {
+                    for t in &joined {
+                        opts.create_option(|o| {
+                            o.label(format!("| {} {}", local_day(t).format("%A"), t.title));
+                            o.emoji(ReactionType::from('📝'));
+                            o.value(t.id);
+                            o
+                        });
+                    }
+                    for t in &not_joined {
+                        opts.create_option(|o| {
+                            if full.contains(&t.id) {
+                                o.label(format!(
+                                    "| {} {} (waitlist)",
+                                    local_day(t).format("%A"),
+                                    t.title
+                                ));
+                            } else {
+                                o.label(format!("| {} {}", local_day(t).format("%A"), t.title));
+                            }
+                            o.emoji(ReactionType::from('🟢'));
+                            o.value(t.id);
+                            o
+                        });
+                    }
+                    opts
                 });
-            }
-            opts
-        });
 
-        mci.edit_original_interaction_response(ctx, |r| {
-            r.add_embed(emb);
-            r.components(|c| c.create_action_row(|ar| ar.add_select_menu(select_menu)))
-        })
-        .await?;
+                mci.edit_original_interaction_response(ctx, |r| {
+                    r.add_embed(emb);
+                    r.components(|c| c.create_action_row(|ar| ar.add_select_menu(select_menu)))
+                })
+                .await?;
 
-        mci = msg
-            .await_component_interaction(ctx)
-            .timeout(Duration::from_secs(60 * 3))
-            .await
-            .context(logging::InfoError::TimedOut)
-            .map_err_reply(|what| mci.edit_quick_info(ctx, what))
-            .await?;
+                mci = msg
+                    .await_component_interaction(ctx)
+                    .timeout(Duration::from_secs(60 * 3))
+                    .await
+                    .context(logging::InfoError::TimedOut)
+                    .map_err_reply(|what| mci.edit_quick_info(ctx, what))
+                    .await?;
 
-        mci.create_interaction_response(ctx, |r| {
-            r.kind(InteractionResponseType::UpdateMessage);
-            r.interaction_response_data(|d| {
-                d.add_embed(CreateEmbed::info_box("Loading ..."));
-                d.components(|c| c)
-            })
-        })
-        .await?;
+                mci.create_interaction_response(ctx, |r| {
+                    r.kind(InteractionResponseType::UpdateMessage);
+                    r.interaction_response_data(|d| {
+                        d.add_embed(CreateEmbed::info_box("Loading ..."));
+                        d.components(|c| c)
+                    })
+                })
+                .await?;
 
-        let selected_id = mci
-            .data
-            .values
-            .get(0)
-            .context("Unexpected missing value on raid select menu. Aborted")
-            .map_err_reply(|what| mci.edit_quick_error(ctx, what))
-            .await?
-            .parse::<i32>()
-            .context("Unexpected value found on interaction. Aborted")
-            .map_err_reply(|what| mci.edit_quick_error(ctx, what))
-            .await?;
+                mci.data
+                    .values
+                    .get(0)
+                    .context("Unexpected missing value on raid select menu. Aborted")
+                    .map_err_reply(|what| mci.edit_quick_error(ctx, what))
+                    .await?
+                    .parse::<i32>()
+                    .context("Unexpected value found on interaction. Aborted")
+                    .map_err_reply(|what| mci.edit_quick_error(ctx, what))
+                    .await?
+            }
+        };
 
         let selected = raids
             .iter()
@@ -340,6 +400,8 @@ pub(crate) async fn interaction(
         } else {
         }
 
+        signup_board::notify_change(ctx, guild_id, &[selected.id]).await;
+
         mci.edit_quick_info(ctx, "Loading ...").await?;
     }
 }
@@ -355,7 +417,9 @@ async fn edit(
     trace: LogTrace,
 ) -> Result<Arc<MessageComponentInteraction>> {
     trace.step("Signup edit");
-    let bosses = raid.all_raid_bosses(ctx).await?;
+    let mut bosses = raid.all_raid_bosses(ctx).await?;
+    bosses.sort_by_key(|b| b.position);
+    bosses.sort_by_key(|b| b.wing);
     let roles = raid.all_roles(ctx).await?;
 
     // Current selected roles by user
@@ -366,16 +430,46 @@ async fn edit(
         .map(|r| r.id)
         .collect();
 
-    // TODO load already selected preferred bosses once we support it
+    // Current preferred bosses by user
+    let mut curr_bosses: Vec<_> = signup
+        .get_preferred_bosses(ctx)
+        .await?
+        .into_iter()
+        .map(|b| b.id)
+        .collect();
 
+    let tz = timezone::load(ctx, raid.guild()).await?;
     let mut base_emb = CreateEmbed::xdefault();
     base_emb.title(&raid.title);
-    let (a, b, c) = embeds::field_raid_date(raid);
+    let (a, b, c) = embeds::field_raid_date(raid, &tz);
     base_emb.field(a, b, c);
-    base_emb.description("✅ You are signed up\n**Feel free to dismiss this message**");
+    if signup.waitlisted {
+        let position = raid
+            .get_waitlist(ctx)
+            .await?
+            .iter()
+            .position(|s| s.id == signup.id)
+            .map(|p| p + 1)
+            .unwrap_or_default();
+        base_emb.description(format!(
+            "⏳ You are on the waitlist (position {})\n\
+            You will be notified by DM once a slot opens up\n\
+            **Feel free to dismiss this message**",
+            position
+        ));
+    } else {
+        base_emb.description("✅ You are signed up\n**Feel free to dismiss this message**");
+    }
 
     loop {
         let mut emb = base_emb.clone();
+        if signup.needs_role_update {
+            emb.field(
+                "⚠️ Roles changed",
+                "Some of your selected roles were removed from the raid. Please select your roles again",
+                false,
+            );
+        }
         if let Some(comment) = &signup.comment {
             emb.field("Comment", &comment, false);
         }
@@ -386,12 +480,19 @@ async fn edit(
                     Some(l) => format!("[{}]({})", b.name, l),
                     None => b.name.to_string(),
                 };
-                // TODO highlight already preferred bosses
-                format!(
-                    "{} | {}",
-                    Mention::from(EmojiId::from(b.emoji as u64)),
-                    boss_link
-                )
+                if curr_bosses.contains(&b.id) {
+                    format!(
+                        "{} | __**{}**__",
+                        Mention::from(EmojiId::from(b.emoji as u64)),
+                        boss_link
+                    )
+                } else {
+                    format!(
+                        "{} | {}",
+                        Mention::from(EmojiId::from(b.emoji as u64)),
+                        boss_link
+                    )
+                }
             },
             "Boss Pool",
             false,
@@ -425,7 +526,10 @@ async fn edit(
             r.components(|c| {
                 c.create_action_row(|ar| {
                     ar.add_button(Buttons::EditRoles.button());
-                    ar.add_button(Buttons::EditPreferences.button());
+                    let mut pref_button = Buttons::EditPreferences.button();
+                    // Nothing to prefer without a boss pool
+                    pref_button.disabled(bosses.is_empty());
+                    ar.add_button(pref_button);
                     ar.add_button(Buttons::AddComment.button())
                 });
                 c.create_action_row(|ar| {
@@ -451,12 +555,20 @@ async fn edit(
 
                 match Buttons::from_str(&mci.data.custom_id)? {
                     Buttons::Leave => {
-                        signup
-                            .remove(ctx)
-                            .await
-                            .context("Something went wrong while removing your signup =(")
+                        let waitlisted = signup.waitlisted;
+                        // Only taken slots matter for the reliability
+                        let res = if waitlisted {
+                            signup.remove(ctx).await
+                        } else {
+                            signup.sign_out(ctx).await
+                        };
+                        res.context("Something went wrong while removing your signup =(")
                             .map_err_reply(|what| mci.edit_quick_error(ctx, what))
                             .await?;
+                        if !waitlisted {
+                            trace.step("Promoting from waitlist");
+                            waitlist::promote(ctx, raid).await;
+                        }
                         return Ok(mci);
                     },
                     Buttons::EditRoles => {
@@ -504,9 +616,54 @@ async fn edit(
                             .into_iter()
                             .map(|r| r.id)
                             .collect();
+
+                        if signup.needs_role_update {
+                            signup = signup.set_needs_role_update(ctx, false).await?;
+                        }
                     }
                     Buttons::EditPreferences => {
-                        todo!()
+                        let pre_sel: Vec<&db::RaidBoss> = bosses
+                            .iter()
+                            .filter(|b| curr_bosses.contains(&b.id))
+                            .collect();
+
+                        trace.step("Edit boss preferences");
+                        let mut selector = UpdatAbleMessage::ComponentInteraction(&mci, msg);
+                        let mut selector_conf = PagedSelectorConfig::default();
+                        let mut sel_emb = base_emb.clone();
+                        sel_emb.description("Select your preferred bosses");
+                        selector_conf
+                            .base_embed(sel_emb)
+                            .items_per_row(4)
+                            .rows_per_page(3)
+                            .pre_selected(&pre_sel);
+
+                        let selected = match selector
+                            .paged_selector(ctx, selector_conf, &bosses, |b| {
+                                (
+                                    ReactionType::from(EmojiId::from(b.emoji as u64)),
+                                    b.name.to_string(),
+                                )
+                            })
+                            .await {
+                                Ok(s) => s,
+                                Err(PagedSelectorError::Aborted) => return Ok(mci),
+                                Err(PagedSelectorError::TimedOut) => {
+                                    let err = anyhow!(logging::InfoError::TimedOut);
+                                    mci.edit_quick_info(ctx, err.to_string()).await?;
+                                    return Err(err);
+                                },
+                                Err(e) => return Err(e.into()),
+                        };
+
+                        signup
+                            .set_preferred_bosses(ctx, selected.iter().map(|b| b.id).collect())
+                            .await?;
+
+                        curr_bosses = selected
+                            .into_iter()
+                            .map(|b| b.id)
+                            .collect();
                     }
                     Buttons::AddComment => {
                         trace.step("Add comment");
@@ -593,13 +750,44 @@ async fn edit(
 // The returned interaction will also be responded to already
 async fn join(
     ctx: &Context,
-    mci: Arc<MessageComponentInteraction>,
+    mut mci: Arc<MessageComponentInteraction>,
     msg: &mut Message,
     db_user: &db::User,
     raid: &db::Raid,
     trace: LogTrace,
 ) -> Result<Arc<MessageComponentInteraction>> {
     trace.step("New Signup");
+    trace.step("Checking reliability");
+    let conf = ReliabilityConfig::load(ctx, raid.guild()).await?;
+    let rel = Reliability::load(ctx, db_user, &conf).await?;
+    if let Some(until) = conf.blocked_until(&rel, chrono::Utc::now().naive_utc()) {
+        let mut emb = CreateEmbed::xdefault();
+        emb.title("Sign-up blocked");
+        emb.description(format!(
+            "Due to recent no-shows or late sign-outs your reliability is at **{}**\n\
+            You can sign up again <t:{}:R>",
+            rel,
+            until.timestamp()
+        ));
+        mci.edit_original_interaction_response(ctx, |r| {
+            r.add_embed(emb);
+            r.components(|c| {
+                c.create_action_row(|ar| ar.add_button(Buttons::BackToSelection.button()))
+            })
+        })
+        .await?;
+
+        mci = msg
+            .await_component_interaction(ctx)
+            .timeout(Duration::from_secs(60 * 5))
+            .await
+            .context(logging::InfoError::TimedOut)
+            .map_err_reply(|what| mci.edit_quick_info(ctx, what))
+            .await?;
+        mci.defer(ctx).await?;
+        return Ok(mci);
+    }
+
     let roles = raid.all_roles(ctx).await?;
     let mut selector = UpdatAbleMessage::ComponentInteraction(&mci, msg);
     let mut selector_conf = PagedSelectorConfig::default();
@@ -638,6 +826,45 @@ async fn join(
     .into_iter()
     .collect::<Vec<_>>();
 
+    trace.step("Checking role quotas");
+    let full_roles = raid
+        .role_slots(ctx)
+        .await?
+        .into_iter()
+        .filter(|rs| rs.is_full() && selected.iter().any(|r| r.id == rs.role.id))
+        .map(|rs| rs.role.title)
+        .collect::<Vec<_>>();
+
+    if !full_roles.is_empty() {
+        let mut emb = CreateEmbed::xdefault();
+        emb.title("Role(s) already filled");
+        emb.description(format!(
+            "The raid already has enough sign-ups that only play: **{}**\n\
+            You can still join, but you might not be needed in that role",
+            full_roles.join(", ")
+        ));
+        mci.edit_original_interaction_response(ctx, |r| {
+            r.add_embed(emb);
+            r.components(|c| c.create_action_row(|a| a.confirm_button().abort_button()))
+        })
+        .await?;
+
+        mci = msg
+            .await_confirm_abort_interaction(ctx)
+            .timeout(Duration::from_secs(60))
+            .await
+            .context(logging::InfoError::TimedOut)
+            .map_err_reply(|what| mci.edit_quick_info(ctx, what))
+            .await?;
+        mci.defer(ctx).await?;
+
+        match mci.parse_button()? {
+            Button::Confirm => (),
+            Button::Abort => return Ok(mci),
+            _ => bail!("Unexpected interaction"),
+        }
+    }
+
     let signup = db::Signup::insert(ctx, db_user, raid)
         .await
         .context("Failed to create signup")
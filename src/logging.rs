@@ -53,6 +53,8 @@ impl std::error::Error for InfoError {}
 
 #[derive(Debug)]
 pub struct LogInfo {
+    /// Guild whose log channel is used
+    guild_id: Option<GuildId>,
     /// The user that initiated
     user: Option<User>,
     /// The kind
@@ -64,12 +66,18 @@ pub struct LogInfo {
 impl LogInfo {
     pub fn automatic<W: ToString>(what: W) -> Self {
         Self {
+            guild_id: None,
             user: None,
             kind: "Automatic",
             what: what.to_string(),
         }
     }
 
+    pub fn with_guild(mut self, guild_id: GuildId) -> Self {
+        self.guild_id = Some(guild_id);
+        self
+    }
+
     pub fn add_user(&mut self, user: User) {
         self.user = Some(user);
     }
@@ -78,6 +86,7 @@ impl LogInfo {
 impl From<&Message> for LogInfo {
     fn from(msg: &Message) -> Self {
         LogInfo {
+            guild_id: msg.guild_id,
             user: Some(msg.author.clone()),
             kind: "Message",
             what: msg.content.clone(),
@@ -132,6 +141,7 @@ fn fmt_app_command_data(data: &ApplicationCommandInteractionData) -> String {
 impl From<&ApplicationCommandInteraction> for LogInfo {
     fn from(aci: &ApplicationCommandInteraction) -> Self {
         LogInfo {
+            guild_id: aci.guild_id,
             user: Some(aci.user.clone()),
             kind: "Application Command",
             what: fmt_app_command_data(&aci.data),
@@ -142,6 +152,7 @@ impl From<&ApplicationCommandInteraction> for LogInfo {
 impl From<&MessageComponentInteraction> for LogInfo {
     fn from(mci: &MessageComponentInteraction) -> Self {
         LogInfo {
+            guild_id: mci.guild_id,
             user: Some(mci.user.clone()),
             kind: "Message Interaction",
             what: mci.data.custom_id.to_string(),
@@ -183,17 +194,25 @@ fn log_basic_embed(info: LogInfo) -> CreateEmbed {
     emb
 }
 
+/// The log channel of the guild, if one is set up
+pub async fn log_channel(ctx: &SerenityContext, guild_id: GuildId) -> Option<ChannelId> {
+    ctx.data
+        .read()
+        .await
+        .get::<LogConfigData>()
+        .unwrap()
+        .clone()
+        .read()
+        .await
+        .log
+        .get(&guild_id)
+        .copied()
+}
+
 async fn log_to_channel(ctx: &SerenityContext, info: LogInfo, trace: LogTrace, res: Result<()>) {
-    let log_channel_info = {
-        ctx.data
-            .read()
-            .await
-            .get::<LogConfigData>()
-            .unwrap()
-            .clone()
-            .read()
-            .await
-            .log
+    let log_channel_info = match info.guild_id {
+        Some(guild_id) => log_channel(ctx, guild_id).await,
+        None => None,
     };
 
     if let Some(chan) = log_channel_info {
@@ -3,19 +3,30 @@ extern crate diesel;
 extern crate dotenv;
 extern crate serenity;
 
+mod calendar_server;
+mod compose;
 mod data;
+mod date_input;
 mod db;
+mod db_listener;
 mod embeds;
+mod ical;
 mod interactions;
 mod logging;
+mod reliability;
+mod reminders;
 mod signup_board;
 mod slash_commands;
 mod status;
 mod tasks;
+mod timezone;
+mod transitions;
+mod waitlist;
 
 use anyhow::bail;
 use data::*;
 use logging::{log_discord, LogInfo};
+use signup_board::BoardChanges;
 //use crate::logging;
 //use crate::{
 //    data::*, db, interactions, logging::*, signup_board::*, slash_commands, status, tasks,
@@ -29,8 +40,8 @@ use serenity::{
     model::prelude::*,
     prelude::*,
 };
-use signup_board::SignupBoard;
 use std::{
+    collections::HashMap,
     env,
     str::FromStr,
     sync::{
@@ -48,6 +59,11 @@ embed_migrations!("migrations/");
 
 struct Handler {
     signup_board_loop_running: AtomicBool,
+    raid_schedule_loop_running: AtomicBool,
+    raid_state_loop_running: AtomicBool,
+    reminder_loop_running: AtomicBool,
+    calendar_server_running: AtomicBool,
+    db_listener_running: AtomicBool,
 }
 
 #[async_trait]
@@ -56,84 +72,51 @@ impl EventHandler for Handler {
         info!("Connected as {}", ready.user.name);
         info!("Refreshing config values");
 
-        let log_channel = db::Config::load(&ctx, String::from(INFO_LOG_NAME))
-            .await
-            .ok();
-
-        let data_read = ctx.data.read().await;
-        let mut log_write = data_read.get::<LogConfigData>().unwrap().write().await;
-
-        match log_channel {
-            None => info!("Log channel not found in db. skipped"),
-            Some(info) => match ChannelId::from_str(&info.value) {
-                Err(e) => error!("Failed to parse log channel id: {}", e),
-                Ok(id) => log_write.log = Some(id),
-            },
-        }
-
-        // Register slash commands for main guild
-        let main_guild_id = {
-            ctx.data
-                .read()
-                .await
-                .get::<ConfigValuesData>()
-                .unwrap()
-                .clone()
-                .main_guild_id
+        let guilds = match db::Guild::all(&ctx).await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                error!("Failed to load guilds: {}", e);
+                Vec::new()
+            }
         };
 
-        info!("Setting up slash commands");
-        match main_guild_id
-            .set_application_commands(&ctx, |cmds| {
-                cmds.set_application_commands(slash_commands::AppCommands::create_default())
-            })
-            .await
-        {
-            Ok(cmds) => {
-                //info!("Setting slash commands permissions for: {:#?}", cmds);
-                // Register slash commands for main guild
-                let confs = {
-                    ctx.data
-                        .read()
-                        .await
-                        .get::<ConfigValuesData>()
-                        .unwrap()
-                        .clone()
-                };
-
-                let perms = cmds
-                    .iter()
-                    .map(|c| {
-                        slash_commands::AppCommands::from_str(&c.name)
-                            .map(|ac| ac.permission(c, &confs))
-                    })
-                    .collect::<Result<Vec<_>, _>>();
-                match perms {
-                    Err(e) => error!("Failed to figure out permissions for slash commands: {}", e),
-                    Ok(perms) => {
-                        if let Err(e) = main_guild_id
-                            .set_application_commands_permissions(&ctx, |p| {
-                                p.set_application_commands(perms)
-                            })
-                            .await
-                        {
-                            error!("Failed to set permissions for slash commands {:?}", e);
-                        }
+        let mut log_channels = HashMap::new();
+        for guild in &guilds {
+            let log_channel = db::Config::load(&ctx, guild.guild(), String::from(INFO_LOG_NAME))
+                .await
+                .ok();
+
+            match log_channel {
+                None => info!("Log channel not found in db for {}. skipped", guild.id),
+                Some(info) => match ChannelId::from_str(&info.value) {
+                    Err(e) => error!("Failed to parse log channel id: {}", e),
+                    Ok(id) => {
+                        log_channels.insert(guild.guild(), id);
                     }
-                }
+                },
             }
-            Err(e) => error!("Failed to create application commands: {:?}", e),
         }
 
-        // attempt to load SignupBoardData from db
-        data_read
-            .get::<SignupBoardData>()
+        ctx.data
+            .read()
+            .await
+            .get::<LogConfigData>()
             .unwrap()
             .write()
             .await
-            .load_from_db(&ctx)
-            .await
-            .unwrap();
+            .log = log_channels;
+
+        // Guilds that are not set up yet only get the command to do so
+        info!("Setting up slash commands");
+        for guild_id in ready.guilds.iter().map(|g| g.id()) {
+            let res = match guilds.iter().find(|g| g.guild() == guild_id) {
+                Some(guild) => slash_commands::register_commands(&ctx, guild).await,
+                None => slash_commands::register_setup_command(&ctx, guild_id).await,
+            };
+            if let Err(e) = res {
+                error!("Failed to set up slash commands for {}: {:?}", guild_id, e);
+            }
+        }
 
         info!("Setting presence");
         status::update_status(&ctx).await;
@@ -145,6 +128,61 @@ impl EventHandler for Handler {
             self.signup_board_loop_running.swap(true, Ordering::Relaxed);
         }
         info!("Starting signup board loop");
+
+        if !self.raid_schedule_loop_running.load(Ordering::Relaxed) {
+            let ctx = ctx.clone();
+            tokio::task::spawn(tasks::raid_schedule_task(ctx));
+            self.raid_schedule_loop_running.swap(true, Ordering::Relaxed);
+        }
+        info!("Starting raid schedule loop");
+
+        if !self.raid_state_loop_running.load(Ordering::Relaxed) {
+            let ctx = ctx.clone();
+            tokio::task::spawn(tasks::raid_state_task(ctx));
+            self.raid_state_loop_running.swap(true, Ordering::Relaxed);
+        }
+        info!("Starting raid state loop");
+
+        if !self.reminder_loop_running.load(Ordering::Relaxed) {
+            let ctx = ctx.clone();
+            tokio::task::spawn(tasks::reminder_task(ctx));
+            self.reminder_loop_running.swap(true, Ordering::Relaxed);
+        }
+        info!("Starting reminder loop");
+
+        if !self.db_listener_running.load(Ordering::Relaxed) {
+            let ctx = ctx.clone();
+            tokio::task::spawn(db_listener::db_listener_task(ctx));
+            self.db_listener_running.swap(true, Ordering::Relaxed);
+        }
+        info!("Starting database listener");
+
+        let calendar_port = ctx
+            .data
+            .read()
+            .await
+            .get::<ConfigValuesData>()
+            .unwrap()
+            .calendar_port;
+        if let Some(port) = calendar_port {
+            if !self.calendar_server_running.load(Ordering::Relaxed) {
+                let ctx = ctx.clone();
+                tokio::task::spawn(calendar_server::calendar_server_task(ctx, port));
+                self.calendar_server_running.swap(true, Ordering::Relaxed);
+            }
+            info!("Starting calendar server");
+        }
+    }
+
+    async fn guild_create(&self, ctx: Context, guild: Guild, is_new: bool) {
+        if !is_new {
+            return;
+        }
+
+        info!("Joined {}. Setting up slash commands", guild.name);
+        if let Err(e) = slash_commands::register_setup_command(&ctx, guild.id).await {
+            error!("Failed to set up slash commands for {}: {:?}", guild.id, e);
+        }
     }
 
     async fn resume(&self, _: Context, _: ResumedEvent) {
@@ -169,39 +207,65 @@ impl EventHandler for Handler {
         user: User,
         _member_data_if_available: Option<Member>,
     ) {
-        // Check if in correct guild
-        let main_guild_id = {
-            ctx.data
-                .read()
-                .await
-                .get::<ConfigValuesData>()
-                .unwrap()
-                .clone()
-                .main_guild_id
+        // Check if in a guild that is set up
+        let guilds = match db::Guild::all(&ctx).await {
+            Ok(guilds) => guilds,
+            Err(e) => {
+                error!("Failed to load guilds: {}", e);
+                return;
+            }
         };
 
-        if guild_id != main_guild_id {
+        if !guilds.iter().any(|g| g.guild() == guild_id) {
             return;
         }
 
         let ctx = &ctx;
         let user_id = user.id;
-        let mut log_info = LogInfo::automatic("User left server");
+        let mut log_info = LogInfo::automatic("User left server").with_guild(guild_id);
         log_info.add_user(user);
 
         log_discord(ctx, log_info, |trace| async move {
             trace.step("Loading user database info");
-            match db::User::by_discord_id(ctx, user_id).await {
-                Ok(db_user) => {
-                    trace.step("Deleting user from db");
-                    db_user.delete(ctx).await?;
-                }
+            let db_user = match db::User::by_discord_id(ctx, user_id).await {
+                Ok(db_user) => db_user,
                 Err(NotFound) => {
                     trace.step("User not found in database");
                     return Err(logging::InfoError::NotRegistered.into());
                 }
                 Err(e) => bail!(e),
             };
+
+            // Users are shared by all guilds. Only delete them once they left all of them
+            trace.step("Checking other guilds");
+            let mut other_member = false;
+            for other in guilds.iter().filter(|g| g.guild() != guild_id) {
+                if other.guild().member(ctx, user_id).await.is_ok() {
+                    other_member = true;
+                    break;
+                }
+            }
+
+            // sign-ups of this guild are gone either way
+            let signups = db_user
+                .active_signups_with_raid(ctx)
+                .await?
+                .into_iter()
+                .filter(|(_, raid)| raid.guild() == guild_id)
+                .collect::<Vec<_>>();
+            let raid_ids = signups.iter().map(|(_, raid)| raid.id).collect::<Vec<_>>();
+
+            if other_member {
+                trace.step("Removing sign-ups of the guild");
+                for (signup, _) in signups {
+                    signup.remove(ctx).await?;
+                }
+            } else {
+                trace.step("Deleting user from db");
+                db_user.delete(ctx).await?;
+            }
+
+            signup_board::notify_change(ctx, guild_id, &raid_ids).await;
             Ok(())
         })
         .await;
@@ -220,12 +284,53 @@ async fn main() {
 
     tracing::subscriber::set_global_default(subscriber).expect("Failed to start the logger");
 
+    // Optional. Sets up a guild without using /setup
+    let main_guild_id = env::var("MAIN_GUILD_ID")
+        .ok()
+        .map(|id| GuildId::from(id.parse::<u64>().expect("Failed to parse manager guild id")));
+
     // Run migrations on the database
     {
         let database_url = env::var("DATABASE_URL").expect("DATABASE_URL not set");
         let conn = PgConnection::establish(&database_url)
             .unwrap_or_else(|_| panic!("Error connecting to {}", database_url));
         embedded_migrations::run(&conn).expect("Failed to run migrations");
+
+        if let Some(main_guild_id) = main_guild_id {
+            let admin_role_id = RoleId::from(
+                env::var("ADMIN_ROLE_ID")
+                    .expect("ADMIN_ROLE_ID not set")
+                    .parse::<u64>()
+                    .expect("Failed to parse admin role id"),
+            );
+
+            let squadmaker_role_id = RoleId::from(
+                env::var("SQUADMAKER_ROLE_ID")
+                    .expect("SQUADMAKER_ROLE_ID not set")
+                    .parse::<u64>()
+                    .expect("Failed to parse squadmaker role id"),
+            );
+
+            let emoji_guild_id = env::var("EMOJI_GUILD_ID")
+                .ok()
+                .map(|id| GuildId::from(id.parse::<u64>().expect("Failed to parse emoji guild id")))
+                .unwrap_or(main_guild_id);
+
+            let guild = db::Guild {
+                id: main_guild_id.0 as i64,
+                admin_role_id: admin_role_id.0 as i64,
+                squadmaker_role_id: squadmaker_role_id.0 as i64,
+                emoji_guild_id: emoji_guild_id.0 as i64,
+            };
+            db::Guild::upsert_with(&conn, &guild).expect("Failed to save main guild");
+
+            // Everything from before multiple guilds were supported belongs to it
+            let adopted =
+                db::Guild::adopt_unassigned(&conn, main_guild_id).expect("Failed to adopt data");
+            if adopted > 0 {
+                info!("Assigned {} entries to the main guild", adopted);
+            }
+        }
     }
 
     let token = env::var("DISCORD_TOKEN").expect("discord token not set");
@@ -234,38 +339,24 @@ async fn main() {
         .parse::<u64>()
         .expect("Failed to parse application id");
 
-    let main_guild_id = GuildId::from(
-        env::var("MAIN_GUILD_ID")
-            .expect("MAIN_GUILD_ID not set")
-            .parse::<u64>()
-            .expect("Failed to parse manager guild id"),
-    );
-
-    let emoji_guild_id = GuildId::from(
-        env::var("EMOJI_GUILD_ID")
-            .expect("EMOJI_GUILD_ID not set")
-            .parse::<u64>()
-            .expect("Failed to parse emoji guild id"),
-    );
-
-    let admin_role_id = RoleId::from(
-        env::var("ADMIN_ROLE_ID")
-            .expect("ADMIN_ROLE_ID not set")
-            .parse::<u64>()
-            .expect("Failed to parse admin role id"),
-    );
-
-    let squadmaker_role_id = RoleId::from(
-        env::var("SQUADMAKER_ROLE_ID")
-            .expect("SQUADMAKER_ROLE_ID not set")
-            .parse::<u64>()
-            .expect("Failed to parse squadmaker role id"),
-    );
+    let calendar_port = env::var("CALENDAR_PORT")
+        .ok()
+        .map(|p| p.parse::<u16>().expect("Failed to parse calendar port"));
+
+    // The server is only bound locally. Usually a reverse proxy makes it reachable
+    let calendar_url = calendar_port.map(|port| {
+        env::var("CALENDAR_URL").unwrap_or_else(|_| format!("http://127.0.0.1:{}", port))
+    });
 
     let mut client = Client::builder(token)
         .application_id(app_id)
         .event_handler(Handler {
             signup_board_loop_running: AtomicBool::new(false),
+            raid_schedule_loop_running: AtomicBool::new(false),
+            raid_state_loop_running: AtomicBool::new(false),
+            reminder_loop_running: AtomicBool::new(false),
+            calendar_server_running: AtomicBool::new(false),
+            db_listener_running: AtomicBool::new(false),
         })
         .intents(GatewayIntents::non_privileged() | GatewayIntents::GUILD_MEMBERS)
         .await
@@ -274,17 +365,16 @@ async fn main() {
     {
         let mut data = client.data.write().await;
         data.insert::<ConfigValuesData>(Arc::new(ConfigValues {
-            main_guild_id,
-            admin_role_id,
-            squadmaker_role_id,
-            emoji_guild_id,
+            calendar_port,
+            calendar_url,
         }));
-        data.insert::<LogConfigData>(Arc::new(RwLock::new(LogConfig { log: None })));
-        data.insert::<DBPoolData>(Arc::new(db::DBPool::new()));
-        data.insert::<SignupBoardData>(Arc::new(RwLock::new(SignupBoard {
-            overview_channel_id: None,
-            overview_message_id: None,
+        data.insert::<LogConfigData>(Arc::new(RwLock::new(LogConfig {
+            log: HashMap::new(),
         })));
+        data.insert::<DBPoolData>(Arc::new(db::DBPool::new()));
+        // Boards are loaded on first use
+        data.insert::<SignupBoardData>(Arc::new(RwLock::new(HashMap::new())));
+        data.insert::<BoardChangesData>(Arc::new(BoardChanges::default()));
     }
 
     let shard_manager = client.shard_manager.clone();
@@ -0,0 +1,263 @@
+// how reliable a user is, based on attendances and late sign-outs
+use std::fmt;
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use serenity::{client::Context, model::id::GuildId};
+
+use crate::{
+    db::{self, AttendanceStatus},
+    transitions::load_config_i64,
+};
+
+pub static MIN_SCORE_NAME: &str = "reliability_min_score";
+pub static COOLDOWN_NAME: &str = "reliability_cooldown_days";
+pub static LATE_SIGNOUT_NAME: &str = "reliability_late_signout_hours";
+
+const DEFAULT_COOLDOWN_DAYS: i64 = 7;
+const DEFAULT_LATE_SIGNOUT_HOURS: i64 = 24;
+
+#[derive(Debug)]
+pub struct ReliabilityConfig {
+    /// Users below this score in percent can not sign up during the cooldown
+    pub min_score: Option<i64>,
+    /// Days after the last incident a user stays blocked
+    pub cooldown_days: i64,
+    /// Sign-outs less than this many hours before the raid count as incident
+    pub late_signout_hours: i64,
+}
+
+impl ReliabilityConfig {
+    pub async fn load(ctx: &Context, guild_id: GuildId) -> Result<Self> {
+        Ok(ReliabilityConfig {
+            min_score: load_config_i64(ctx, guild_id, MIN_SCORE_NAME).await?,
+            cooldown_days: load_config_i64(ctx, guild_id, COOLDOWN_NAME)
+                .await?
+                .unwrap_or(DEFAULT_COOLDOWN_DAYS),
+            late_signout_hours: load_config_i64(ctx, guild_id, LATE_SIGNOUT_NAME)
+                .await?
+                .unwrap_or(DEFAULT_LATE_SIGNOUT_HOURS),
+        })
+    }
+
+    /// Until when the user is not allowed to sign up, if blocked at all
+    pub fn blocked_until(&self, rel: &Reliability, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        let min = self.min_score?;
+        if rel.score()? as i64 >= min {
+            return None;
+        }
+        let until = rel.last_incident? + Duration::days(self.cooldown_days);
+        if until > now {
+            Some(until)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Reliability {
+    pub attended: usize,
+    pub late: usize,
+    pub no_shows: usize,
+    pub late_signouts: usize,
+    pub last_incident: Option<NaiveDateTime>,
+}
+
+impl Reliability {
+    fn incident(&mut self, at: NaiveDateTime) {
+        self.last_incident = self.last_incident.max(Some(at));
+    }
+
+    pub fn compute(
+        attendances: &[db::Attendance],
+        signouts: &[(db::Signout, db::Raid)],
+        conf: &ReliabilityConfig,
+    ) -> Self {
+        let mut rel = Reliability::default();
+
+        for a in attendances {
+            match a.status {
+                AttendanceStatus::Attended | AttendanceStatus::Substitute => rel.attended += 1,
+                AttendanceStatus::Late => {
+                    rel.late += 1;
+                    rel.incident(a.recorded_at);
+                }
+                AttendanceStatus::NoShow => {
+                    rel.no_shows += 1;
+                    rel.incident(a.recorded_at);
+                }
+            }
+        }
+
+        let late = Duration::hours(conf.late_signout_hours);
+        for (so, raid) in signouts {
+            if so.created_at > raid.date - late {
+                rel.late_signouts += 1;
+                rel.incident(so.created_at);
+            }
+        }
+
+        rel
+    }
+
+    pub async fn load(ctx: &Context, user: &db::User, conf: &ReliabilityConfig) -> Result<Self> {
+        let attendances = user.attendances(ctx).await?;
+        let signouts = user.signouts_with_raid(ctx).await?;
+        Ok(Self::compute(&attendances, &signouts, conf))
+    }
+
+    /// Score in percent. Being late counts half. None without any history
+    pub fn score(&self) -> Option<u8> {
+        let total = self.attended + self.late + self.no_shows + self.late_signouts;
+        if total == 0 {
+            return None;
+        }
+        let good = self.attended as f64 + self.late as f64 / 2.0;
+        Some((good * 100.0 / total as f64).round() as u8)
+    }
+}
+
+impl fmt::Display for Reliability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.score() {
+            Some(s) => write!(f, "{}%", s),
+            None => write!(f, "new"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2022, 6, day).and_hms(hour, 0, 0)
+    }
+
+    fn conf(min_score: Option<i64>) -> ReliabilityConfig {
+        ReliabilityConfig {
+            min_score,
+            cooldown_days: 7,
+            late_signout_hours: 24,
+        }
+    }
+
+    fn attendance(status: AttendanceStatus, recorded_at: NaiveDateTime) -> db::Attendance {
+        db::Attendance {
+            signup_id: 1,
+            status,
+            recorded_at,
+        }
+    }
+
+    fn signout(created_at: NaiveDateTime, raid_date: NaiveDateTime) -> (db::Signout, db::Raid) {
+        let signout = db::Signout {
+            id: 1,
+            user_id: 1,
+            raid_id: 1,
+            created_at,
+        };
+        let raid = db::Raid {
+            id: 1,
+            title: String::from("Raid"),
+            date: raid_date,
+            state: db::RaidState::Finished,
+            tier_id: None,
+            board_message_id: None,
+            capacity: None,
+            schedule_id: None,
+            open_at: None,
+            reminded: true,
+            cancel_reason: None,
+            description: None,
+            leader_id: None,
+            voice_channel_id: None,
+            duration: 120,
+            guild_id: 1,
+            board_channel_id: None,
+            category_id: None,
+        };
+        (signout, raid)
+    }
+
+    #[test]
+    fn no_history_has_no_score() {
+        let rel = Reliability::compute(&[], &[], &conf(Some(100)));
+        assert_eq!(rel.score(), None);
+        assert_eq!(rel.last_incident, None);
+        assert_eq!(rel.to_string(), "new");
+        assert_eq!(conf(Some(100)).blocked_until(&rel, at(1, 0)), None);
+    }
+
+    #[test]
+    fn being_late_counts_half() {
+        let attendances = [
+            attendance(AttendanceStatus::Attended, at(1, 20)),
+            attendance(AttendanceStatus::Late, at(8, 20)),
+        ];
+        let rel = Reliability::compute(&attendances, &[], &conf(None));
+        assert_eq!((rel.attended, rel.late), (1, 1));
+        assert_eq!(rel.score(), Some(75));
+        assert_eq!(rel.to_string(), "75%");
+    }
+
+    #[test]
+    fn substitutes_attended() {
+        let attendances = [
+            attendance(AttendanceStatus::Substitute, at(1, 20)),
+            attendance(AttendanceStatus::NoShow, at(8, 20)),
+        ];
+        let rel = Reliability::compute(&attendances, &[], &conf(None));
+        assert_eq!((rel.attended, rel.no_shows), (1, 1));
+        assert_eq!(rel.score(), Some(50));
+    }
+
+    #[test]
+    fn only_late_sign_outs_are_incidents() {
+        let raid = at(10, 20);
+        let signouts = [
+            signout(at(8, 20), raid),
+            signout(at(9, 20), raid),
+            signout(at(10, 18), raid),
+        ];
+        let attendances = [attendance(AttendanceStatus::Attended, at(1, 20))];
+        let rel = Reliability::compute(&attendances, &signouts, &conf(None));
+
+        // exactly the configured hours ahead is still in time
+        assert_eq!(rel.late_signouts, 1);
+        assert_eq!(rel.no_shows, 0);
+        assert_eq!(rel.last_incident, Some(at(10, 18)));
+        assert_eq!(rel.score(), Some(50));
+    }
+
+    #[test]
+    fn last_incident_is_the_most_recent_one() {
+        let attendances = [
+            attendance(AttendanceStatus::Late, at(12, 20)),
+            attendance(AttendanceStatus::NoShow, at(3, 20)),
+            attendance(AttendanceStatus::Attended, at(20, 20)),
+        ];
+        let signouts = [signout(at(8, 12), at(8, 20))];
+        let rel = Reliability::compute(&attendances, &signouts, &conf(None));
+        assert_eq!(rel.last_incident, Some(at(12, 20)));
+    }
+
+    #[test]
+    fn blocked_during_cooldown_after_last_incident() {
+        let attendances = [
+            attendance(AttendanceStatus::NoShow, at(3, 20)),
+            attendance(AttendanceStatus::NoShow, at(10, 20)),
+            attendance(AttendanceStatus::Attended, at(1, 20)),
+        ];
+        let rel = Reliability::compute(&attendances, &[], &conf(None));
+        assert_eq!(rel.score(), Some(33));
+
+        let blocking = conf(Some(50));
+        assert_eq!(blocking.blocked_until(&rel, at(11, 0)), Some(at(17, 20)));
+        assert_eq!(blocking.blocked_until(&rel, at(17, 20)), None);
+        assert_eq!(conf(Some(33)).blocked_until(&rel, at(11, 0)), None);
+        assert_eq!(conf(None).blocked_until(&rel, at(11, 0)), None);
+    }
+}
@@ -0,0 +1,118 @@
+// direct messages to signed up users shortly before a raid starts
+use anyhow::{anyhow, Result};
+use chrono::Duration;
+use chrono_tz::Tz;
+use serenity::{
+    builder::CreateEmbed,
+    client::Context,
+    model::id::{EmojiId, GuildId, UserId},
+    model::misc::Mention,
+};
+
+use crate::{
+    db,
+    embeds::{self, CrossroadsEmbeds},
+    logging::LogTrace,
+    timezone,
+    transitions::load_config_i64,
+};
+
+pub static REMINDER_NAME: &str = "reminder_minutes";
+
+fn reminder_embed(
+    raid: &db::Raid,
+    tz: &Tz,
+    roles: &[db::Role],
+    bosses: &[db::RaidBoss],
+) -> CreateEmbed {
+    let mut emb = CreateEmbed::xdefault();
+    emb.title(format!("Reminder: {}", raid.title));
+    emb.description(format!("Starts <t:{}:R>", raid.date.timestamp()));
+    emb.fields(vec![embeds::field_raid_date(raid, tz)]);
+    if !roles.is_empty() {
+        emb.field(
+            "**Your Roles**",
+            roles
+                .iter()
+                .map(|r| {
+                    format!(
+                        "{} {}",
+                        Mention::from(EmojiId::from(r.emoji as u64)),
+                        r.title
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            false,
+        );
+    }
+    if !bosses.is_empty() {
+        emb.field(
+            "**Bosses**",
+            bosses
+                .iter()
+                .map(|b| Mention::from(EmojiId::from(b.emoji as u64)).to_string())
+                .collect::<Vec<_>>()
+                .join(" "),
+            false,
+        );
+    }
+    emb
+}
+
+/// Sends a reminder to everyone signed up for a raid starting within the
+/// configured amount of minutes. Users that could not be reached are
+/// reported together as a single error
+pub async fn send_reminders(ctx: &Context, guild_id: GuildId, trace: LogTrace) -> Result<()> {
+    trace.step("Loading configuration");
+    let minutes = match load_config_i64(ctx, guild_id, REMINDER_NAME).await? {
+        Some(m) => m,
+        None => return Ok(()),
+    };
+
+    trace.step("Loading raids");
+    let now = chrono::Utc::now().naive_utc();
+    let raids = db::Raid::to_remind(ctx, guild_id, now + Duration::minutes(minutes)).await?;
+    let tz = timezone::load(ctx, guild_id).await?;
+
+    let mut failed: Vec<String> = Vec::new();
+    for raid in raids {
+        // Marked up front so an error further down does not remind everyone
+        // twice. Raids that already started are not worth a reminder anymore
+        raid.set_reminded(ctx, true).await?;
+        if raid.date <= now {
+            continue;
+        }
+
+        trace.step("Sending reminders");
+        let bosses = raid.all_raid_bosses(ctx).await?;
+        for signup in raid.get_signups(ctx).await? {
+            if signup.waitlisted {
+                continue;
+            }
+            let user = signup.get_user(ctx).await?;
+            if !user.reminders {
+                continue;
+            }
+            let roles = signup.get_roles(ctx).await?;
+            let emb = reminder_embed(&raid, &tz, &roles, &bosses);
+
+            let sent = match UserId::from(user.discord_id()).to_user(ctx).await {
+                Ok(u) => u.dm(ctx, |m| m.set_embed(emb)).await.is_ok(),
+                Err(_) => false,
+            };
+            if !sent {
+                failed.push(format!("{} ({})", user.gw2_id, raid.title));
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(anyhow!(
+            "Failed to send reminders to:\n{}",
+            failed.join("\n")
+        ));
+    }
+
+    Ok(())
+}
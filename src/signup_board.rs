@@ -1,25 +1,103 @@
-use crate::embeds::CrossroadsEmbeds;
-use crate::{data, data::SignupBoardData, db, interactions, logging::LogTrace};
-use anyhow::Result;
-use chrono::NaiveDate;
+use crate::embeds::{CrossroadsEmbeds, EMBED_AUTHOR_NAME};
+use crate::{
+    data::{BoardChangesData, SignupBoardData},
+    db, interactions,
+    logging::LogTrace,
+    timezone,
+};
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, NaiveDate};
 use itertools::Itertools;
-use serenity::builder::CreateEmbed;
+use serenity::builder::{CreateEmbed, EditMessage};
 use serenity::{model::prelude::*, prelude::*};
 use serenity_tools::builder::CreateEmbedExt;
-use std::{mem, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+use tokio::sync::Notify;
+use tracing::error;
 
 const OVERVIEW_CHANNEL_ID: &str = "overview_channel_id";
 const OVERVIEW_MESSAGE_ID: &str = "overview_message_id";
+/// What happens to the board message of a raid once it is finished or cancelled
+pub const BOARD_FINISHED_NAME: &str = "board_finished";
+pub const BOARD_FINISHED_DELETE: &str = "delete";
+pub const BOARD_FINISHED_ARCHIVE: &str = "archive";
 const CROSS_EMOJI: char = '❌';
 const RUNNING_EMOJI: char = '🏃';
 const GREEN_CIRCLE_EMOJI: char = '🟢';
 const CONSTRUCTION_SITE_EMOJI: char = '🚧';
 const LOCK_EMOJI: char = '🔒';
-
-// Hold on to often used values
+const NO_ENTRY_EMOJI: char = '🚫';
+const DESCRIPTION_MAX_CHARS: usize = 200;
+const OVERVIEW_EXTRA_MESSAGE_IDS: &str = "overview_extra_message_ids";
+/// More raids than fit into this many messages are left out
+const OVERVIEW_MAX_MESSAGES: usize = 5;
+// Discord limits
+const MESSAGE_MAX_EMBEDS: usize = 10;
+/// For all embeds of a message together
+const MESSAGE_MAX_CHARS: usize = 6000;
+const EMBED_MAX_FIELDS: usize = 25;
+const FIELD_NAME_MAX_CHARS: usize = 256;
+const FIELD_VALUE_MAX_CHARS: usize = 1024;
+const FOOTER_TEXT: &str = "Last update";
+/// Field values can not be empty
+const EMPTY_FIELD_VALUE: &str = "\u{200b}";
+
+// Hold on to often used values. There is one board per guild
 pub struct SignupBoard {
+    pub guild_id: GuildId,
     pub overview_channel_id: Option<ChannelId>,
     pub overview_message_id: Option<MessageId>,
+    /// Messages the overview continues in if it does not fit into one
+    pub overview_extra_message_ids: Vec<MessageId>,
+}
+
+/// Raids that changed since the board was last updated. Changes coming in
+/// quickly after each other are shown with a single update
+#[derive(Default)]
+pub struct BoardChanges {
+    pending: Mutex<HashMap<GuildId, HashSet<i32>>>,
+    notify: Notify,
+}
+
+impl BoardChanges {
+    pub async fn get(ctx: &Context) -> Arc<BoardChanges> {
+        ctx.data
+            .read()
+            .await
+            .get::<BoardChangesData>()
+            .unwrap()
+            .clone()
+    }
+
+    /// Waits for the next change. A change made while nobody was waiting
+    /// returns right away
+    pub async fn changed(&self) {
+        self.notify.notified().await
+    }
+
+    /// Removes and returns all changed raids by guild
+    pub async fn take(&self) -> HashMap<GuildId, HashSet<i32>> {
+        mem::take(&mut *self.pending.lock().await)
+    }
+}
+
+/// Marks raids of a guild as changed. The board is updated a few seconds later.
+/// Without raids only the overview is updated
+pub async fn notify_change(ctx: &Context, guild_id: GuildId, raid_ids: &[i32]) {
+    let changes = BoardChanges::get(ctx).await;
+    changes
+        .pending
+        .lock()
+        .await
+        .entry(guild_id)
+        .or_default()
+        .extend(raid_ids);
+    changes.notify.notify_one();
 }
 
 #[derive(Debug)]
@@ -43,58 +121,440 @@ impl std::fmt::Display for SignupBoardError {
 
 impl std::error::Error for SignupBoardError {}
 
-// loads the main guild id, that is always set
-async fn load_guild_id(ctx: &Context) -> Result<GuildId> {
-    // Load guild id provided on startup
-    let guild_id = ctx
-        .data
-        .read()
-        .await
-        .get::<data::ConfigValuesData>()
-        .unwrap()
-        .main_guild_id;
+/// Shortens the text to at most max chars. Cuts at a line break if possible
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let short = text.chars().take(max - 1).collect::<String>();
+    match short.rfind('\n') {
+        Some(pos) => format!("{}…", &short[..pos]),
+        None => format!("{}…", short),
+    }
+}
 
-    Ok(guild_id)
+// One embed of the overview. Kept as plain text until it is sent so the
+// discord limits can be checked
+struct OverviewEmbed {
+    title: String,
+    description: Option<String>,
+    fields: Vec<(String, String)>,
+    footer: bool,
+    /// Amount of raids listed
+    raids: usize,
 }
 
-pub(crate) fn title_sort_value(t: &db::Raid) -> u64 {
-    if t.title.contains("Beginner") {
-        return 10;
+impl OverviewEmbed {
+    fn new(title: String) -> Self {
+        OverviewEmbed {
+            title,
+            description: None,
+            fields: Vec::new(),
+            footer: false,
+            raids: 0,
+        }
+    }
+
+    fn header() -> Self {
+        let mut emb = OverviewEmbed::new("Sign up for a raid".to_string());
+        emb.fields.push((
+            "How to".to_string(),
+            "\
+Before you can sign up you have to be __registered__. \
+To do so simply use the `/register` command in any channel you have write permissions in.\n\n\
+To **sign up**, **sign out** or to **edit** your sign-up click the button at the end of the board"
+                .to_string(),
+        ));
+        emb.fields.push((
+            "Legend".to_string(),
+            format!(
+                "{} => {}\n{} => {}\n{} => {}",
+                GREEN_CIRCLE_EMOJI,
+                "You can join this raid or edit/remove your sign-up",
+                LOCK_EMOJI,
+                "The raid is locked. Most likely squadmaking is in progress",
+                RUNNING_EMOJI,
+                "The raid is currently ongoing"
+            ),
+        ));
+        emb.footer = true;
+        emb
     }
-    if t.title.contains("Intermediate") {
-        return 8;
+
+    // Shown in place of the raids that did not fit
+    fn hidden(count: usize) -> Self {
+        let mut emb = OverviewEmbed::new("More raids".to_string());
+        emb.description = Some(format!(
+            "{} more raid(s) do not fit on the board. They are listed again once earlier raids are done",
+            count
+        ));
+        emb
     }
-    if t.title.contains("Practice") {
-        return 6;
+
+    /// Characters counted towards the limit of a message
+    fn len(&self) -> usize {
+        let mut len = EMBED_AUTHOR_NAME.chars().count() + self.title.chars().count();
+        if let Some(desc) = &self.description {
+            len += desc.chars().count();
+        }
+        for (name, value) in &self.fields {
+            len += name.chars().count() + value.chars().count();
+        }
+        if self.footer {
+            len += FOOTER_TEXT.chars().count();
+        }
+        len
+    }
+
+    fn fits(&self, fields: &[(String, String)]) -> bool {
+        let len: usize = fields
+            .iter()
+            .map(|(name, value)| name.chars().count() + value.chars().count())
+            .sum();
+        self.fields.len() + fields.len() <= EMBED_MAX_FIELDS
+            && self.len() + len <= MESSAGE_MAX_CHARS
+    }
+
+    fn build<'a>(&self, e: &'a mut CreateEmbed) -> &'a mut CreateEmbed {
+        e.title(&self.title);
+        if let Some(desc) = &self.description {
+            e.description(desc);
+        }
+        for (name, value) in &self.fields {
+            e.field(name, value, false);
+        }
+        if self.footer {
+            e.footer(|f| f.text(FOOTER_TEXT));
+            e.timestamp(&chrono::Utc::now());
+        }
+        e
+    }
+}
+
+fn page_len(page: &[OverviewEmbed]) -> usize {
+    page.iter().map(|e| e.len()).sum()
+}
+
+/// Splits the overview into messages within the discord limits. Raids that do
+/// not fit into the allowed amount of messages are left out
+fn paginate(embeds: Vec<OverviewEmbed>) -> Vec<Vec<OverviewEmbed>> {
+    let mut pages: Vec<Vec<OverviewEmbed>> = vec![Vec::new()];
+    let mut hidden = 0;
+    for emb in embeds {
+        let page = pages.last().unwrap();
+        let full =
+            page.len() == MESSAGE_MAX_EMBEDS || page_len(page) + emb.len() > MESSAGE_MAX_CHARS;
+        if full && !page.is_empty() {
+            if pages.len() == OVERVIEW_MAX_MESSAGES {
+                hidden += emb.raids;
+                continue;
+            }
+            pages.push(Vec::new());
+        }
+        pages.last_mut().unwrap().push(emb);
+    }
+
+    if hidden > 0 {
+        // make room for the note on the last message
+        let page = pages.last_mut().unwrap();
+        while page.len() == MESSAGE_MAX_EMBEDS
+            || page_len(page) + OverviewEmbed::hidden(hidden).len() > MESSAGE_MAX_CHARS
+        {
+            match page.pop() {
+                Some(emb) => hidden += emb.raids,
+                None => break,
+            }
+        }
+        page.push(OverviewEmbed::hidden(hidden));
+    }
+
+    pages
+}
+
+/// Starts the raids of a category on the overview
+fn section_heading(category: Option<&db::RaidCategory>) -> (String, String) {
+    let name = match category {
+        Some(c) => match c.emoji() {
+            Some(e) => format!("{} __{}__", Mention::from(EmojiId::from(e)), c.name),
+            None => format!("__{}__", c.name),
+        },
+        None => "__Other raids__".to_string(),
+    };
+    (
+        truncate(&name, FIELD_NAME_MAX_CHARS),
+        EMPTY_FIELD_VALUE.to_string(),
+    )
+}
+
+/// Raids without a category are placed like a category with weight 0
+fn sort_weight(category: Option<&db::RaidCategory>) -> i32 {
+    category.map_or(0, |c| c.sort_weight)
+}
+
+fn state_emoji(state: &db::RaidState) -> char {
+    match state {
+        db::RaidState::Created => CONSTRUCTION_SITE_EMOJI,
+        db::RaidState::Open => GREEN_CIRCLE_EMOJI,
+        db::RaidState::Closed => LOCK_EMOJI,
+        db::RaidState::Started => RUNNING_EMOJI,
+        db::RaidState::Finished => CROSS_EMOJI,
+        db::RaidState::Cancelled => NO_ENTRY_EMOJI,
+    }
+}
+
+struct TierInfo {
+    _tier: db::Tier,
+    discord: Vec<RoleId>,
+}
+
+// Everything shown about a raid on the board
+struct RaidInfo {
+    raid: db::Raid,
+    signup_count: i64,
+    waitlist_count: i64,
+    tier_info: Option<TierInfo>,
+    category: Option<db::RaidCategory>,
+    bosses: Vec<db::RaidBoss>,
+    slots: Vec<db::RoleSlots>,
+}
+
+impl RaidInfo {
+    async fn load(ctx: &Context, raid: db::Raid) -> Result<RaidInfo> {
+        let signup_count = raid.get_signup_count(ctx).await?;
+        let waitlist_count = raid.get_waitlist_count(ctx).await?;
+        let tier = raid.get_tier(ctx).await.transpose()?;
+        let tier_info = if let Some(_tier) = tier {
+            let discord = _tier
+                .get_discord_roles(ctx)
+                .await?
+                .into_iter()
+                .map(|t| RoleId::from(t.discord_role_id as u64))
+                .collect::<Vec<_>>();
+
+            Some(TierInfo { _tier, discord })
+        } else {
+            None
+        };
+        let category = raid.get_category(ctx).await.transpose()?;
+        let mut bosses = raid.all_raid_bosses(ctx).await?;
+        bosses.sort_by_key(|b| b.position);
+        bosses.sort_by_key(|b| b.wing);
+        let slots = raid.role_slots(ctx).await?;
+
+        Ok(RaidInfo {
+            raid,
+            signup_count,
+            waitlist_count,
+            tier_info,
+            category,
+            bosses,
+            slots,
+        })
+    }
+
+    fn details(&self) -> String {
+        let mut details = format!(
+            "`     Time    `   <t:{}:t> - <t:{}:t>",
+            self.raid.date.timestamp(),
+            self.raid.end().timestamp()
+        );
+        if let Some(leader) = self.raid.leader_id() {
+            details.push_str(&format!(
+                "\n`    Leader   `   {}",
+                Mention::from(UserId::from(leader))
+            ));
+        }
+        if let Some(voice) = self.raid.voice_channel_id() {
+            details.push_str(&format!(
+                "\n`    Voice    `   {}",
+                Mention::from(ChannelId::from(voice))
+            ));
+        }
+        if let Some(tier) = &self.tier_info {
+            details.push_str(&format!(
+                "\n`Tier required`   {}",
+                tier.discord.iter().map(|d| Mention::from(*d)).join(" ")
+            ));
+        } else {
+            details.push_str("\n`Tier required`   None");
+        }
+        match self.raid.capacity {
+            Some(cap) => details.push_str(&format!(
+                "\n`Sign-up count`   {}/{}",
+                self.signup_count, cap
+            )),
+            None => details.push_str(&format!("\n`Sign-up count`   {}", self.signup_count)),
+        }
+        if self.waitlist_count > 0 {
+            details.push_str(&format!(" (+{} waiting)", self.waitlist_count));
+        }
+        // only roles with a quota are of interest here. Sign-ups with several
+        // roles are candidates for each of them, so the numbers can add up to
+        // more than the sign-up count
+        let slots = self
+            .slots
+            .iter()
+            .filter_map(|rs| {
+                rs.quota.map(|q| {
+                    format!(
+                        "{} {}/{}",
+                        Mention::from(EmojiId::from(rs.role.emoji as u64)),
+                        rs.candidates,
+                        q
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        if !slots.is_empty() {
+            details.push_str(&format!("\n` Candidates  `   {}", slots.join(" ")));
+        }
+        match self.bosses.len() {
+            0 => (),
+            1 => details.push_str("\n`     Boss    `   "),
+            _ => details.push_str("\n`  Boss Pool  `   "),
+        }
+        let boss_emojis = self
+            .bosses
+            .iter()
+            .map(|b| Mention::from(EmojiId::from(b.emoji as u64)).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        details.push_str(&boss_emojis);
+        if let Some(desc) = &self.raid.description {
+            // keep the field well below the embed field limit
+            if desc.chars().count() > DESCRIPTION_MAX_CHARS {
+                let short = desc.chars().take(DESCRIPTION_MAX_CHARS).collect::<String>();
+                details.push_str(&format!("\n{}…", short));
+            } else {
+                details.push_str(&format!("\n{}", desc));
+            }
+        }
+        details
+    }
+
+    /// The board message of a single raid
+    fn embed(&self) -> CreateEmbed {
+        let mut emb = CreateEmbed::xdefault();
+        let emoji = self
+            .category
+            .as_ref()
+            .and_then(|c| c.emoji())
+            .map(|e| format!("{} ", Mention::from(EmojiId::from(e))))
+            .unwrap_or_default();
+        emb.title(format!(
+            "{}    {}{}",
+            state_emoji(&self.raid.state),
+            emoji,
+            self.raid.title
+        ));
+        if let Some(color) = self.category.as_ref().and_then(|c| c.color) {
+            emb.colour(color as u32);
+        }
+        emb.description(self.details());
+        if !self.slots.is_empty() {
+            let roster = self
+                .slots
+                .iter()
+                .map(|rs| {
+                    let emoji = Mention::from(EmojiId::from(rs.role.emoji as u64));
+                    match rs.quota {
+                        Some(q) => format!("{} {}/{}", emoji, rs.candidates, q),
+                        None => format!("{} {}", emoji, rs.candidates),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            emb.field(
+                "Candidates per role",
+                truncate(&roster, FIELD_VALUE_MAX_CHARS),
+                false,
+            );
+        }
+        let footer = match &self.category {
+            Some(c) => format!("Raid {} | {} | Last update", self.raid.id, c.name),
+            None => format!("Raid {} | Last update", self.raid.id),
+        };
+        emb.footer(|f| f.text(footer));
+        emb.timestamp(&chrono::Utc::now());
+        emb
     }
-    0
 }
 
 impl SignupBoard {
-    // get a lock on the SignupBoardConfig
-    pub async fn get(ctx: &Context) -> Arc<RwLock<SignupBoard>> {
-        ctx.data
+    // get a lock on the SignupBoard of the guild. Loads it from the db on first use
+    pub async fn get(ctx: &Context, guild_id: GuildId) -> Result<Arc<RwLock<SignupBoard>>> {
+        let boards = ctx
+            .data
             .read()
             .await
             .get::<SignupBoardData>()
             .unwrap()
-            .clone()
+            .clone();
+
+        if let Some(board) = boards.read().await.get(&guild_id) {
+            return Ok(board.clone());
+        }
+
+        let mut board = SignupBoard {
+            guild_id,
+            overview_channel_id: None,
+            overview_message_id: None,
+            overview_extra_message_ids: Vec::new(),
+        };
+        board.load_from_db(ctx).await?;
+
+        let board = boards
+            .write()
+            .await
+            .entry(guild_id)
+            .or_insert_with(|| Arc::new(RwLock::new(board)))
+            .clone();
+        Ok(board)
     }
 
     pub async fn load_from_db(&mut self, ctx: &Context) -> Result<()> {
+        let guild_id = self.guild_id;
         let new_board = SignupBoard {
-            overview_channel_id: match db::Config::load(ctx, OVERVIEW_CHANNEL_ID.to_string()).await
+            guild_id,
+            overview_channel_id: match db::Config::load(
+                ctx,
+                guild_id,
+                OVERVIEW_CHANNEL_ID.to_string(),
+            )
+            .await
             {
                 Ok(conf) => Some(conf.value.parse::<ChannelId>()?),
                 Err(diesel::NotFound) => None,
                 Err(e) => return Err(e.into()),
             },
-            overview_message_id: match db::Config::load(ctx, OVERVIEW_MESSAGE_ID.to_string()).await
+            overview_message_id: match db::Config::load(
+                ctx,
+                guild_id,
+                OVERVIEW_MESSAGE_ID.to_string(),
+            )
+            .await
             {
                 Ok(conf) => Some(conf.value.parse::<u64>()?.into()),
                 Err(diesel::NotFound) => None,
                 Err(e) => return Err(e.into()),
             },
+            overview_extra_message_ids: match db::Config::load(
+                ctx,
+                guild_id,
+                OVERVIEW_EXTRA_MESSAGE_IDS.to_string(),
+            )
+            .await
+            {
+                // comma separated. Empty if all of the overview fits into one message
+                Ok(conf) => conf
+                    .value
+                    .split(',')
+                    .filter(|id| !id.is_empty())
+                    .map(|id| id.parse::<u64>().map(MessageId::from))
+                    .collect::<Result<_, _>>()?,
+                Err(diesel::NotFound) => Vec::new(),
+                Err(e) => return Err(e.into()),
+            },
         };
         // overwrite at once and not value by value
         let _ = mem::replace(self, new_board);
@@ -103,23 +563,33 @@ impl SignupBoard {
 
     pub async fn save_to_db(&self, ctx: &Context) -> Result<()> {
         if let Some(oci) = self.overview_channel_id {
-            db::Config {
-                name: OVERVIEW_CHANNEL_ID.to_string(),
-                value: oci.to_string(),
-            }
+            db::Config::new(
+                self.guild_id,
+                OVERVIEW_CHANNEL_ID.to_string(),
+                oci.to_string(),
+            )
             .save(ctx)
             .await?;
         }
 
         if let Some(omi) = self.overview_message_id {
-            db::Config {
-                name: OVERVIEW_MESSAGE_ID.to_string(),
-                value: omi.to_string(),
-            }
+            db::Config::new(
+                self.guild_id,
+                OVERVIEW_MESSAGE_ID.to_string(),
+                omi.to_string(),
+            )
             .save(ctx)
             .await?;
         }
 
+        db::Config::new(
+            self.guild_id,
+            OVERVIEW_EXTRA_MESSAGE_IDS.to_string(),
+            self.overview_extra_message_ids.iter().join(","),
+        )
+        .save(ctx)
+        .await?;
+
         Ok(())
     }
 
@@ -131,9 +601,15 @@ impl SignupBoard {
         trace: LogTrace,
     ) -> Result<()> {
         trace.step("Looking for channel in guild");
-        let gid = load_guild_id(ctx).await?;
-        let channels = gid.channels(ctx).await?;
+        let channels = self.guild_id.channels(ctx).await?;
         if let Some(channel) = channels.get(&chan) {
+            if let Some(old) = self.overview_channel_id {
+                trace.step("Removing additional messages from old channel");
+                for id in self.overview_extra_message_ids.drain(..) {
+                    // might have been deleted by hand already
+                    let _ = old.delete_message(ctx, id).await;
+                }
+            }
             trace.step("Found. Setting new channel internally");
             self.overview_channel_id = Some(channel.id);
         } else {
@@ -164,7 +640,7 @@ impl SignupBoard {
     }
 
     /// Loads all relevant raid(s) from the db and updates the overview message
-    pub async fn update_overview(&self, ctx: &Context, trace: LogTrace) -> Result<()> {
+    pub async fn update_overview(&mut self, ctx: &Context, trace: LogTrace) -> Result<()> {
         trace.step("Loading overview information");
         let msg = match self.overview_message_id {
             Some(m) => m,
@@ -176,59 +652,26 @@ impl SignupBoard {
         };
 
         trace.step("Loading raid(s)");
-        let active_raids = db::Raid::all_active(ctx).await?;
-
-        struct TierInfo {
-            _tier: db::Tier,
-            discord: Vec<RoleId>,
-        }
-
-        struct RaidInfo {
-            raid: db::Raid,
-            signup_count: i64,
-            tier_info: Option<TierInfo>,
-            bosses: Vec<db::RaidBoss>,
-        }
+        let active_raids = db::Raid::all_active(ctx, self.guild_id).await?;
 
         trace.step("Loading additional traning info");
         let mut raids: Vec<RaidInfo> = Vec::new();
         for raid in active_raids {
-            let signup_count = raid.get_signup_count(ctx).await?;
-            let tier = raid.get_tier(ctx).await.transpose()?;
-            let tier_info = if let Some(_tier) = tier {
-                let discord = _tier
-                    .get_discord_roles(ctx)
-                    .await?
-                    .into_iter()
-                    .map(|t| RoleId::from(t.discord_role_id as u64))
-                    .collect::<Vec<_>>();
-
-                Some(TierInfo { _tier, discord })
-            } else {
-                None
-            };
-            let mut bosses = raid.all_raid_bosses(ctx).await?;
-            bosses.sort_by_key(|b| b.position);
-            bosses.sort_by_key(|b| b.wing);
-
-            raids.push(RaidInfo {
-                raid,
-                signup_count,
-                tier_info,
-                bosses,
-            });
+            raids.push(RaidInfo::load(ctx, raid).await?);
         }
 
-        // Sort by custom names and dates
-        raids.sort_by(|a, b| title_sort_value(&b.raid).cmp(&title_sort_value(&a.raid)));
-        raids.sort_by(|a, b| a.raid.date.date().cmp(&b.raid.date.date()));
+        // Days are the ones of the guild time zone
+        let tz = timezone::load(ctx, self.guild_id).await?;
+        let local_day = |r: &db::Raid| timezone::to_local(&tz, r.date).date();
+
+        // Sort by day, then category and time. Raids of a category stay together
+        raids.sort_by_key(|r| r.raid.date);
+        raids.sort_by_key(|r| r.raid.category_id);
+        raids.sort_by_key(|r| Reverse(sort_weight(r.category.as_ref())));
+        raids.sort_by_key(|r| local_day(&r.raid));
 
         let mut _groups: Vec<(NaiveDate, Vec<&RaidInfo>)> = Vec::new();
-        for (d, v) in raids
-            .iter()
-            .group_by(|t| t.raid.date.date())
-            .into_iter()
-        {
+        for (d, v) in raids.iter().group_by(|t| local_day(&t.raid)).into_iter() {
             _groups.push((d, v.collect()));
         }
 
@@ -236,90 +679,284 @@ impl SignupBoard {
             Vec::with_capacity(_groups.len());
         for (d, v) in _groups {
             // FIXME do this without extra db access
-            let mut total_users = db::User::by_signed_up_and_date(ctx, d).await?;
+            let (from, to) = timezone::day_bounds(&tz, d);
+            let mut total_users =
+                db::User::by_signed_up_between(ctx, self.guild_id, from, to).await?;
             total_users.sort_by_key(|u| u.id);
             total_users.dedup_by_key(|u| u.id);
             groups.push((d, v, total_users.len()));
         }
 
-        let base_emb = CreateEmbed::xdefault();
+        trace.step("Laying out overview");
+        let mut embeds = vec![OverviewEmbed::header()];
+        for (date, raids, total) in groups {
+            let title = date.format("__**%A**, %v__").to_string();
+            let mut emb = OverviewEmbed::new(title.clone());
+            emb.description = Some(format!("Total sign-up count: {}", total));
+            // without any categories on that day there is only one section
+            let sections = raids.iter().any(|t| t.category.is_some());
+            let mut section = None;
+            for t in raids {
+                let name = format!("{}    **{}**", state_emoji(&t.raid.state), &t.raid.title);
+                let mut fields = vec![(
+                    truncate(&name, FIELD_NAME_MAX_CHARS),
+                    truncate(&t.details(), FIELD_VALUE_MAX_CHARS),
+                )];
+                let heading = section_heading(t.category.as_ref());
+                if sections && section != Some(t.raid.category_id) {
+                    fields.insert(0, heading.clone());
+                }
+                // days with a lot of raids continue in another embed
+                if !emb.fits(&fields) {
+                    let next = OverviewEmbed::new(format!("{} (continued)", title));
+                    embeds.push(mem::replace(&mut emb, next));
+                    // so does the section
+                    if sections && fields.len() == 1 {
+                        fields.insert(0, heading);
+                    }
+                }
+                section = Some(t.raid.category_id);
+                emb.fields.extend(fields);
+                emb.raids += 1;
+            }
+            embeds.push(emb);
+        }
+        let pages = paginate(embeds);
 
-        trace.step("Updating overview message");
-        chan.edit_message(ctx, msg, |m| {
-            m.add_embed(|e| {
-                e.0 = base_emb.0.clone();
-                e.title("Sign up for a raid");
-                e.field(
-                    "How to",
-                    "\
-Before you can sign up you have to be __registered__. \
-To do so simply use the `/register` command in any channel you have write permissions in.\n\n\
-To **sign up**, **sign out** or to **edit** your sign-up click the button at the end of the message",
-                    false);
-                e.field(
-                    "Legend",
-                    format!(
-                        "{} => {}\n{} => {}\n{} => {}",
-                        GREEN_CIRCLE_EMOJI, "You can join this raid or edit/remove your sign-up",
-                        LOCK_EMOJI, "The raid is locked. Most likely squadmaking is in progress",
-                        RUNNING_EMOJI, "The raid is currently ongoing"
-                        ),
-                    false);
-                e.footer(|f| f.text("Last update"));
-                e.timestamp(&chrono::Utc::now())
-            });
-            for (date, raids, total) in groups {
-                m.add_embed(|e| {
-                    e.0 = base_emb.0.clone();
-                    e.title(date.format("__**%A**, %v__"));
-                    e.description(&format!("Total sign-up count: {}", total));
-                    for t in raids {
-                        let mut details = format!("`     Time    `   <t:{}:t>", t.raid.date.timestamp());
-                        if let Some(tier) = &t.tier_info {
-                            details.push_str(&format!("\n`Tier required`   {}", tier.discord.iter().map(|d| Mention::from(*d)).join(" ")));
-                        } else {
-                            details.push_str("\n`Tier required`   None");
-                        }
-                        details.push_str(&format!("\n`Sign-up count`   {}", t.signup_count));
-                        match t.bosses.len() {
-                            0 => (),
-                            1 => details.push_str("\n`     Boss    `   "),
-                            _ => details.push_str("\n`  Boss Pool  `   "),
-                        }
-                        let boss_emojis = t.bosses
-                            .iter()
-                            .map(|b| Mention::from(EmojiId::from(b.emoji as u64)).to_string())
-                            .collect::<Vec<_>>()
-                            .join(" ");
-                        details.push_str(&boss_emojis);
-
-                        e.field(
-                            format!(
-                                "{}    **{}**",
-                                match t.raid.state {
-                                    db::RaidState::Created => CONSTRUCTION_SITE_EMOJI,
-                                    db::RaidState::Open => GREEN_CIRCLE_EMOJI,
-                                    db::RaidState::Closed => LOCK_EMOJI,
-                                    db::RaidState::Started => RUNNING_EMOJI,
-                                    db::RaidState::Finished => CROSS_EMOJI,
-                                },
-                                &t.raid.title),
-                            details,
-                            false
-                        );
+        let base_emb = CreateEmbed::xdefault();
+        let mut extra_ids = self.overview_extra_message_ids.iter().copied();
+        let mut new_extra_ids = Vec::with_capacity(pages.len() - 1);
+
+        trace.step("Updating overview message(s)");
+        for (i, page) in pages.iter().enumerate() {
+            // the button is always at the very end of the board
+            let button = i == pages.len() - 1 && !raids.is_empty();
+            let build = |m: &mut EditMessage| {
+                for emb in page {
+                    m.add_embed(|e| {
+                        e.0 = base_emb.0.clone();
+                        emb.build(e)
+                    });
+                }
+                m.components(|c| {
+                    if button {
+                        c.add_action_row(interactions::overview_action_row());
                     }
-                    e
+                    c
                 });
+            };
+
+            if i == 0 {
+                chan.edit_message(ctx, msg, |m| {
+                    build(m);
+                    m
+                })
+                .await?;
+                continue;
+            }
+
+            if let Some(id) = extra_ids.next() {
+                let edited = chan
+                    .edit_message(ctx, id, |m| {
+                        build(m);
+                        m
+                    })
+                    .await;
+                // otherwise the message was deleted by hand and is created again
+                if edited.is_ok() {
+                    new_extra_ids.push(id);
+                    continue;
+                }
+            }
+
+            trace.step("Creating additional overview message");
+            let created = chan
+                .send_message(ctx, |m| {
+                    m.set_embed(CreateEmbed::info_box("Setting up overview message"))
+                })
+                .await?;
+            chan.edit_message(ctx, created.id, |m| {
+                build(m);
+                m
+            })
+            .await?;
+            new_extra_ids.push(created.id);
+        }
+
+        for id in extra_ids {
+            trace.step("Removing surplus overview message");
+            // might have been deleted by hand already
+            let _ = chan.delete_message(ctx, id).await;
+        }
+
+        if new_extra_ids != self.overview_extra_message_ids {
+            self.overview_extra_message_ids = new_extra_ids;
+            self.save_to_db(ctx).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Creates, updates or removes the board message of a single raid. Raids
+    /// from opening until they finish have a message in the channel of their weekday
+    pub async fn update_raid(&self, ctx: &Context, raid_id: i32, trace: LogTrace) -> Result<()> {
+        trace.step("Loading raid for board message");
+        let raid = match db::Raid::by_id(ctx, self.guild_id, raid_id).await {
+            Ok(raid) => raid,
+            // deleted in the database. Nothing left to show
+            Err(diesel::NotFound) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+
+        match raid.state {
+            db::RaidState::Open | db::RaidState::Closed | db::RaidState::Started => {
+                self.post_raid_message(ctx, raid, trace).await
             }
-            m.components(|c| {
-                if !raids.is_empty() {
-                    c.add_action_row(interactions::overview_action_row());
+            db::RaidState::Finished | db::RaidState::Cancelled => {
+                let mode =
+                    match db::Config::load(ctx, self.guild_id, BOARD_FINISHED_NAME.to_string())
+                        .await
+                    {
+                        Ok(conf) => conf.value,
+                        Err(diesel::NotFound) => BOARD_FINISHED_DELETE.to_string(),
+                        Err(e) => return Err(e.into()),
+                    };
+                if mode == BOARD_FINISHED_ARCHIVE {
+                    archive_raid_message(ctx, raid, trace).await
+                } else {
+                    remove_raid_message(ctx, raid, trace).await
                 }
-                c
-            });
-            m
-        }).await?;
+            }
+            db::RaidState::Created => remove_raid_message(ctx, raid, trace).await,
+        }
+    }
+
+    /// Brings the board messages of all active raids up to date. Used after the
+    /// board channels changed
+    pub async fn update_raids(&self, ctx: &Context, trace: LogTrace) -> Result<()> {
+        trace.step("Loading raid(s)");
+        let ids: Vec<i32> = db::Raid::all_active(ctx, self.guild_id)
+            .await?
+            .into_iter()
+            .map(|r| r.id)
+            .collect();
+        self.update_raid_list(ctx, ids, trace).await
+    }
+
+    /// Updates the board messages of the given raids. A failing raid does not
+    /// keep the others from being updated
+    pub async fn update_raid_list(
+        &self,
+        ctx: &Context,
+        raid_ids: impl IntoIterator<Item = i32>,
+        trace: LogTrace,
+    ) -> Result<()> {
+        let mut failed = Vec::new();
+        for id in raid_ids {
+            if let Err(e) = self.update_raid(ctx, id, trace.clone()).await {
+                error!("Failed to update board message of raid {}: {:?}", id, e);
+                failed.push(id);
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Failed to update board message of raid(s): {}",
+                failed.iter().join(", ")
+            ))
+        }
+    }
+
+    async fn post_raid_message(
+        &self,
+        ctx: &Context,
+        raid: db::Raid,
+        trace: LogTrace,
+    ) -> Result<()> {
+        trace.step("Loading board channel");
+        let tz = timezone::load(ctx, self.guild_id).await?;
+        let weekday = timezone::to_local(&tz, raid.date).weekday();
+        let chan = match db::BoardChannel::by_weekday(ctx, self.guild_id, weekday).await {
+            Ok(bc) => bc.channel(),
+            // no channel for that day. Raid is only on the overview
+            Err(diesel::NotFound) => return remove_raid_message(ctx, raid, trace).await,
+            Err(e) => return Err(e.into()),
+        };
+
+        let open = raid.state == db::RaidState::Open;
+        let emb = RaidInfo::load(ctx, raid.clone()).await?.embed();
+
+        if let Some((old_chan, old_msg)) = raid.board_message() {
+            if old_chan == chan {
+                trace.step("Updating board message");
+                let edited = chan
+                    .edit_message(ctx, old_msg, |m| {
+                        m.add_embed(|e| {
+                            e.0 = emb.0.clone();
+                            e
+                        });
+                        m.components(|c| {
+                            if open {
+                                c.add_action_row(interactions::raid_action_row(raid.id));
+                            }
+                            c
+                        })
+                    })
+                    .await;
+                // otherwise the message was deleted by hand and is created again
+                if edited.is_ok() {
+                    return Ok(());
+                }
+            } else {
+                trace.step("Removing board message from old channel");
+                // might have been deleted by hand already
+                let _ = old_chan.delete_message(ctx, old_msg).await;
+            }
+        }
+
+        trace.step("Creating board message");
+        let msg = chan
+            .send_message(ctx, |m| {
+                m.set_embed(emb);
+                if open {
+                    m.components(|c| c.add_action_row(interactions::raid_action_row(raid.id)));
+                }
+                m
+            })
+            .await?;
+        raid.set_board_msg(ctx, Some((chan, msg.id))).await?;
 
         Ok(())
     }
 }
+
+async fn remove_raid_message(ctx: &Context, raid: db::Raid, trace: LogTrace) -> Result<()> {
+    if let Some((chan, msg)) = raid.board_message() {
+        trace.step("Removing board message");
+        // might have been deleted by hand already
+        let _ = chan.delete_message(ctx, msg).await;
+        raid.set_board_msg(ctx, None).await?;
+    }
+    Ok(())
+}
+
+// Keeps the message with the final state but without the sign-up button
+async fn archive_raid_message(ctx: &Context, raid: db::Raid, trace: LogTrace) -> Result<()> {
+    if let Some((chan, msg)) = raid.board_message() {
+        trace.step("Archiving board message");
+        let emb = RaidInfo::load(ctx, raid.clone()).await?.embed();
+        // might have been deleted by hand already
+        let _ = chan
+            .edit_message(ctx, msg, |m| {
+                m.add_embed(|e| {
+                    e.0 = emb.0;
+                    e
+                });
+                m.components(|c| c)
+            })
+            .await;
+        raid.set_board_msg(ctx, None).await?;
+    }
+    Ok(())
+}
@@ -3,14 +3,17 @@ use std::str::FromStr;
 use serenity::{
     builder::{CreateApplicationCommand, CreateApplicationCommandPermissions},
     client::Context,
-    model::interactions::application_command::{
-        ApplicationCommand, ApplicationCommandInteraction, ApplicationCommandPermissionType,
+    model::{
+        id::{GuildId, UserId},
+        interactions::application_command::{
+            ApplicationCommand, ApplicationCommandInteraction, ApplicationCommandPermissionType,
+        },
     },
 };
 
 use tracing::error;
 
-use crate::data::ConfigValues;
+use crate::db;
 
 #[derive(Debug)]
 pub struct SlashCommandParseError(String);
@@ -23,34 +26,48 @@ impl std::fmt::Display for SlashCommandParseError {
 
 impl std::error::Error for SlashCommandParseError {}
 
+mod calendar;
 mod config;
 mod register;
 mod raid;
 mod raid_boss;
 mod raid_role;
+mod raid_schedule;
 mod raid_tier;
+mod setup;
+mod stats;
 
 /// All slash commands
 #[derive(Debug)]
 pub enum AppCommands {
     Register,
     Unregister,
+    Reminders,
+    Stats,
+    Calendar,
     Raid,
     RaidBoss,
     RaidRole,
     RaidTier,
+    RaidSchedule,
     Config,
+    Setup,
 }
 
 /// All commands that should be created when the bot starts
-const DEFAULT_COMMANDS: [AppCommands; 7] = [
+const DEFAULT_COMMANDS: [AppCommands; 12] = [
     AppCommands::Register,
     AppCommands::Unregister,
+    AppCommands::Reminders,
+    AppCommands::Stats,
+    AppCommands::Calendar,
     AppCommands::Raid,
     AppCommands::RaidBoss,
     AppCommands::RaidRole,
     AppCommands::RaidTier,
+    AppCommands::RaidSchedule,
     AppCommands::Config,
+    AppCommands::Setup,
 ];
 
 impl FromStr for AppCommands {
@@ -60,11 +77,16 @@ impl FromStr for AppCommands {
         match s {
             register::CMD_REGISTER => Ok(Self::Register),
             register::CMD_UNREGISTER => Ok(Self::Unregister),
+            register::CMD_REMINDERS => Ok(Self::Reminders),
+            stats::CMD_STATS => Ok(Self::Stats),
+            calendar::CMD_CALENDAR => Ok(Self::Calendar),
             raid::CMD_RAID => Ok(Self::Raid),
             raid_boss::CMD_RAID_BOSS => Ok(Self::RaidBoss),
             raid_role::CMD_RAID_ROLE => Ok(Self::RaidRole),
             raid_tier::CMD_RAID_TIER => Ok(Self::RaidTier),
+            raid_schedule::CMD_RAID_SCHEDULE => Ok(Self::RaidSchedule),
             config::CMD_CONFIG => Ok(Self::Config),
+            setup::CMD_SETUP => Ok(Self::Setup),
             _ => Err(SlashCommandParseError(s.to_owned())),
         }
     }
@@ -75,11 +97,16 @@ impl AppCommands {
         match self {
             Self::Register => register::create_reg(),
             Self::Unregister => register::create_unreg(),
+            Self::Reminders => register::create_reminders(),
+            Self::Stats => stats::create(),
+            Self::Calendar => calendar::create(),
             Self::Raid => raid::create(),
             Self::RaidBoss => raid_boss::create(),
             Self::RaidRole => raid_role::create(),
             Self::RaidTier => raid_tier::create(),
+            Self::RaidSchedule => raid_schedule::create(),
             Self::Config => config::create(),
+            Self::Setup => setup::create(),
         }
     }
 
@@ -93,7 +120,8 @@ impl AppCommands {
     pub fn permission(
         &self,
         ac: &ApplicationCommand,
-        conf: &ConfigValues,
+        guild: &db::Guild,
+        owner_id: UserId,
     ) -> CreateApplicationCommandPermissions {
         let mut perms = CreateApplicationCommandPermissions::default();
         perms.id(ac.id.0);
@@ -104,16 +132,33 @@ impl AppCommands {
             | Self::RaidBoss
             | Self::RaidRole
             | Self::RaidTier
+            | Self::RaidSchedule
             | Self::Config => perms.create_permissions(|p| {
                 p.permission(true)
                     .kind(ApplicationCommandPermissionType::Role)
-                    .id(conf.squadmaker_role_id.0)
+                    .id(guild.squadmaker_role_id())
             }),
-            Self::Register | Self::Unregister => perms.create_permissions(|p| {
+            Self::Register
+            | Self::Unregister
+            | Self::Reminders
+            | Self::Stats
+            | Self::Calendar => perms.create_permissions(|p| {
                 p.permission(true)
                     .kind(ApplicationCommandPermissionType::Role)
-                    .id(conf.main_guild_id.0) // Guild id is same as @everyone
+                    .id(guild.guild_id()) // Guild id is same as @everyone
             }),
+            Self::Setup => {
+                perms.create_permissions(|p| {
+                    p.permission(true)
+                        .kind(ApplicationCommandPermissionType::User)
+                        .id(owner_id.0)
+                });
+                perms.create_permissions(|p| {
+                    p.permission(true)
+                        .kind(ApplicationCommandPermissionType::Role)
+                        .id(guild.admin_role_id())
+                })
+            }
         };
 
         perms
@@ -123,15 +168,75 @@ impl AppCommands {
         match self {
             Self::Register => register::handle_reg(ctx, aci).await,
             Self::Unregister => register::handle_unreg(ctx, aci).await,
+            Self::Reminders => register::handle_reminders(ctx, aci).await,
+            Self::Stats => stats::handle(ctx, aci).await,
+            Self::Calendar => calendar::handle(ctx, aci).await,
             Self::Raid => raid::handle(ctx, aci).await,
             Self::RaidBoss => raid_boss::handle(ctx, aci).await,
             Self::RaidRole => raid_role::handle(ctx, aci).await,
             Self::RaidTier => raid_tier::handle(ctx, aci).await,
+            Self::RaidSchedule => raid_schedule::handle(ctx, aci).await,
             Self::Config => config::handle(ctx, aci).await,
+            Self::Setup => setup::handle(ctx, aci).await,
         }
     }
 }
 
+/// Creates all commands with their permissions in a guild that is set up
+pub async fn register_commands(ctx: &Context, guild: &db::Guild) -> anyhow::Result<()> {
+    let guild_id = guild.guild();
+    let owner_id = guild_id.to_partial_guild(ctx).await?.owner_id;
+
+    let cmds = guild_id
+        .set_application_commands(ctx, |cmds| {
+            cmds.set_application_commands(AppCommands::create_default())
+        })
+        .await?;
+
+    let perms = cmds
+        .iter()
+        .map(|c| AppCommands::from_str(&c.name).map(|ac| ac.permission(c, guild, owner_id)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    guild_id
+        .set_application_commands_permissions(ctx, |p| p.set_application_commands(perms))
+        .await?;
+
+    Ok(())
+}
+
+/// Creates only the setup command in a guild that is not set up yet.
+/// Only the owner of the guild can use it
+pub async fn register_setup_command(ctx: &Context, guild_id: GuildId) -> anyhow::Result<()> {
+    let owner_id = guild_id.to_partial_guild(ctx).await?.owner_id;
+
+    let cmds = guild_id
+        .set_application_commands(ctx, |cmds| {
+            cmds.set_application_commands(vec![AppCommands::Setup.create()])
+        })
+        .await?;
+
+    let perms = cmds
+        .iter()
+        .map(|c| {
+            let mut perms = CreateApplicationCommandPermissions::default();
+            perms.id(c.id.0);
+            perms.create_permissions(|p| {
+                p.permission(true)
+                    .kind(ApplicationCommandPermissionType::User)
+                    .id(owner_id.0)
+            });
+            perms
+        })
+        .collect::<Vec<_>>();
+
+    guild_id
+        .set_application_commands_permissions(ctx, |p| p.set_application_commands(perms))
+        .await?;
+
+    Ok(())
+}
+
 pub async fn slash_command_interaction(ctx: &Context, aci: &ApplicationCommandInteraction) {
     // Consider reworking to aci.data.id
     match AppCommands::from_str(&aci.data.name) {
@@ -143,8 +248,19 @@ pub async fn slash_command_interaction(ctx: &Context, aci: &ApplicationCommandIn
 pub mod helpers {
     use std::collections::HashMap;
 
+    use anyhow::{bail, Context, Result};
     use serde_json::Value;
-    use serenity::model::interactions::application_command::ApplicationCommandInteractionDataOption;
+    use serenity::model::{
+        id::GuildId,
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+    };
+
+    /// The guild the command was used in. Commands only exist in guilds
+    pub fn command_guild(aci: &ApplicationCommandInteraction) -> Result<GuildId> {
+        aci.guild_id.context("Command used outside of a guild")
+    }
 
     /// Helps to quickly access commands
     pub fn command_map(opt: &ApplicationCommandInteractionDataOption) -> HashMap<String, Value> {
@@ -153,4 +269,27 @@ pub mod helpers {
             .filter_map(|o| o.value.as_ref().map(|v| (o.name.clone(), v.clone())))
             .collect()
     }
+
+    /// Parses a comma separated list of repr's each with an optional amount
+    /// Example: dps:6,druid:2,qfb
+    pub fn parse_repr_amounts(value: &str) -> Result<Vec<(String, Option<i32>)>> {
+        value
+            .split(',')
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(|s| match s.split_once(':') {
+                Some((repr, amount)) => {
+                    let amount = amount
+                        .trim()
+                        .parse::<i32>()
+                        .with_context(|| format!("Invalid amount for {}", repr))?;
+                    if amount < 0 {
+                        bail!("The amount for {} can not be negative", repr.trim());
+                    }
+                    Ok((repr.trim().to_string(), Some(amount)))
+                }
+                None => Ok((s.to_string(), None)),
+            })
+            .collect()
+    }
 }
@@ -0,0 +1,253 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, bail, Context as ErrContext, Result};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateEmbed},
+    client::Context,
+    http::AttachmentType,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+            ApplicationCommandOptionType,
+        },
+        InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+    },
+};
+use serenity_tools::interactions::ApplicationCommandInteractionExt;
+
+use crate::{
+    calendar_server,
+    data::ConfigValuesData,
+    db,
+    embeds::CrossroadsEmbeds,
+    ical,
+    logging::{self, log_discord, LogTrace, ReplyHelper},
+};
+
+pub(super) const CMD_CALENDAR: &str = "calendar";
+
+pub fn create() -> CreateApplicationCommand {
+    let mut app = CreateApplicationCommand::default();
+    app.name(CMD_CALENDAR);
+    app.description("Get upcoming raids into your calendar");
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("download");
+        o.description("Get a calendar file (.ics) of upcoming raids to import into your calendar");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("raids");
+            o.description("Which raids to export. Defaults to the ones you signed up for");
+            o.add_string_choice("signed up", "signed_up");
+            o.add_string_choice("open", "open")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("link");
+        o.description("Get the links to subscribe to with your calendar app")
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("reset");
+        o.description("Create a new personal link. The old one stops working")
+    });
+    app
+}
+
+pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
+    log_discord(ctx, aci, |trace| async move {
+        trace.step("Parsing command");
+        if let Some(sub) = aci.data.options.get(0) {
+            match sub.name.as_ref() {
+                "download" => download(ctx, aci, sub, trace).await,
+                "link" => link(ctx, aci, false, trace).await,
+                "reset" => link(ctx, aci, true, trace).await,
+                _ => bail!("{} not yet available", sub.name),
+            }
+        } else {
+            bail!("Invalid command")
+        }
+    })
+    .await;
+}
+
+/// Loads the invoking user. Replies and returns None if not registered
+async fn registered_user(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+) -> Result<Option<db::User>> {
+    match db::User::by_discord_id(ctx, aci.user.id).await {
+        Ok(u) => Ok(Some(u)),
+        Err(diesel::NotFound) => {
+            Err(diesel::NotFound)
+                .context(logging::InfoError::NotRegistered)
+                .context("Not registered yet. You are not signed up for any raid")
+                .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+                .await?;
+            Ok(None)
+        }
+        Err(e) => {
+            Err(e)
+                .context("Unexpected error fetching user information")
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+async fn download(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let open = option
+        .options
+        .get(0) // only one option anyway
+        .and_then(|v| v.value.as_ref())
+        .and_then(|v| v.as_str())
+        == Some("open");
+
+    let since = ical::export_since();
+
+    let (name, raids) = if open {
+        trace.step("Loading open raids");
+        let guild_id = super::helpers::command_guild(aci)?;
+        let raids = db::Raid::upcoming(ctx, guild_id, since)
+            .await
+            .context("Unexpected error loading raids")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        ("Raids", raids)
+    } else {
+        trace.step("Looking for user");
+        let db_user = match registered_user(ctx, aci).await? {
+            Some(u) => u,
+            None => return Ok(()),
+        };
+
+        trace.step("Loading sign-ups");
+        let raids = db_user
+            .joined_upcoming_raids(ctx, since)
+            .await
+            .context("Unexpected error loading your sign-ups")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        ("My Raids", raids)
+    };
+
+    trace.step("Replying to user");
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Calendar");
+    emb.description(format!(
+        "Contains {} raid(s). Import the attached file into your calendar. \
+        Importing a newer file again updates the events instead of adding them twice",
+        raids.len()
+    ));
+
+    let file = AttachmentType::Bytes {
+        data: Cow::from(ical::calendar(name, &raids).into_bytes()),
+        filename: String::from("raids.ics"),
+    };
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    aci.create_followup_message(ctx, |f| {
+        f.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+        f.add_file(file)
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn link(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    reset: bool,
+    trace: LogTrace,
+) -> Result<()> {
+    let base_url = ctx
+        .data
+        .read()
+        .await
+        .get::<ConfigValuesData>()
+        .unwrap()
+        .calendar_url
+        .clone();
+
+    let guild_id = super::helpers::command_guild(aci)?;
+    let base_url = match base_url {
+        Some(u) => u,
+        None => {
+            Err(anyhow!("Calendar subscriptions are not enabled"))
+                .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    trace.step("Looking for user");
+    let db_user = match registered_user(ctx, aci).await? {
+        Some(u) => u,
+        None => return Ok(()),
+    };
+
+    let token = match db_user.calendar_token.clone() {
+        Some(t) if !reset => t,
+        _ => {
+            trace.step("Creating new token");
+            let token = calendar_server::new_token();
+            db_user
+                .set_calendar_token(ctx, Some(token.clone()))
+                .await
+                .context("Unexpected error saving your calendar link")
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+            token
+        }
+    };
+
+    trace.step("Replying to user");
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Calendar Subscription");
+    emb.description(
+        "Subscribe to one of these links with your calendar app to keep it up to date automatically",
+    );
+    emb.field(
+        "Your sign-ups",
+        format!(
+            "{}\nDo not share this link. If you did use `/calendar reset`",
+            calendar_server::user_feed_url(&base_url, &token)
+        ),
+        false,
+    );
+    emb.field(
+        "All upcoming raids",
+        calendar_server::open_feed_url(&base_url, guild_id),
+        false,
+    );
+    if reset {
+        emb.footer(|f| f.text("Your previous link no longer works"));
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use anyhow::{bail, Result, Context as ErrContext};
+use chrono::Weekday;
 use serenity::{
     builder::{CreateApplicationCommand, CreateEmbed},
     client::Context,
@@ -18,15 +19,29 @@ use serenity::{
 use serenity_tools::{builder::CreateEmbedExt, interactions::ApplicationCommandInteractionExt};
 
 use crate::{
-    data::{ConfigValuesData, LogConfigData, INFO_LOG_NAME},
+    data::{LogConfigData, INFO_LOG_NAME},
     db,
     embeds::CrossroadsEmbeds,
     logging::{log_discord, LogTrace, ReplyHelper},
-    signup_board,
+    reliability::{COOLDOWN_NAME, LATE_SIGNOUT_NAME, MIN_SCORE_NAME},
+    reminders::REMINDER_NAME,
+    signup_board::{self, BOARD_FINISHED_ARCHIVE, BOARD_FINISHED_DELETE, BOARD_FINISHED_NAME},
+    timezone::{self, TIMEZONE_NAME},
+    transitions::{AUTO_CLOSE_NAME, AUTO_FINISH_NAME, AUTO_OPEN_NAME, AUTO_START_NAME},
 };
 
 pub(super) const CMD_CONFIG: &str = "config";
 
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
 pub fn create() -> CreateApplicationCommand {
     let mut app = CreateApplicationCommand::default();
     app.name(CMD_CONFIG);
@@ -43,6 +58,39 @@ pub fn create() -> CreateApplicationCommand {
             o.description("The channel in which the overview message will be posted")
         })
     });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("board");
+        o.description("set the channel for the board messages of raids on a weekday");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("weekday");
+            o.description("The weekday of the raids in the guild time zone");
+            for day in WEEKDAYS {
+                o.add_string_choice(day, day);
+            }
+            o
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Channel);
+            o.name("channel");
+            o.description("If left empty raids of that weekday are only shown on the overview")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("board_finished");
+        o.description("What happens to the board message of a finished or cancelled raid");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("mode");
+            o.description("Default: delete");
+            o.add_string_choice("delete: remove the message", BOARD_FINISHED_DELETE);
+            o.add_string_choice("archive: keep the message without button", BOARD_FINISHED_ARCHIVE)
+        })
+    });
     app.create_option(|o| {
         o.kind(ApplicationCommandOptionType::SubCommand);
         o.name("log");
@@ -54,6 +102,68 @@ pub fn create() -> CreateApplicationCommand {
             o.description("The channel to which all logs are posted")
         })
     });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("auto");
+        o.description("Configure automatic raid state changes");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("transition");
+            o.description("The state change to configure");
+            o.add_string_choice("open: minutes before the raid", "open");
+            o.add_string_choice("close: minutes before the raid", "close");
+            o.add_string_choice("start: minutes after the raid date", "start");
+            o.add_string_choice("finish: minutes after the raid end", "finish")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("minutes");
+            o.description("Offset in minutes. If left empty the state change is done manually only");
+            o.min_int_value(0)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("reminder");
+        o.description("Configure the reminders sent to signed up users");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("minutes");
+            o.description("Minutes before the raid the reminder is sent. If left empty no reminders are sent");
+            o.min_int_value(1)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("reliability");
+        o.description("Configure when unreliable users are blocked from signing up");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("setting");
+            o.description("The setting to configure");
+            o.add_string_choice("min_score: percent below which users are blocked", "min_score");
+            o.add_string_choice("cooldown: days blocked after the last incident. Default: 7", "cooldown");
+            o.add_string_choice("late_signout: hours before the raid. Default: 24", "late_signout")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("value");
+            o.description("If left empty the default is used. Without min_score nobody is blocked");
+            o.min_int_value(0)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("timezone");
+        o.description("Configure the time zone used for raid input and the signup board");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("name");
+            o.description("IANA time zone name. Example: Europe/Berlin. If left empty UTC is used")
+        })
+    });
     app.create_option(|o| {
         o.kind(ApplicationCommandOptionType::SubCommand);
         o.name("emoji_list");
@@ -68,7 +178,13 @@ pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
         if let Some(sub) = aci.data.options.get(0) {
             match sub.name.as_ref() {
                 "overview" => overview(ctx, aci, sub, trace).await,
+                "board" => board(ctx, aci, sub, trace).await,
+                "board_finished" => board_finished(ctx, aci, sub, trace).await,
                 "log" => log(ctx, aci, sub, trace).await,
+                "auto" => auto(ctx, aci, sub, trace).await,
+                "reminder" => reminder(ctx, aci, sub, trace).await,
+                "reliability" => reliability(ctx, aci, sub, trace).await,
+                "timezone" => time_zone(ctx, aci, sub, trace).await,
                 "emoji_list" => emoji_list(ctx, aci, trace).await,
                 _ => bail!("{} not yet available", sub.name),
             }
@@ -85,6 +201,7 @@ async fn overview(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let channel_id = option
         .options
         .get(0)
@@ -102,7 +219,7 @@ async fn overview(
         .await?;
 
     trace.step("Loading signup board");
-    let board = signup_board::SignupBoard::get(ctx).await;
+    let board = signup_board::SignupBoard::get(ctx, guild_id).await?;
     let mut lock = board.write().await;
 
     trace.step("Set channel");
@@ -135,12 +252,124 @@ async fn overview(
     Ok(())
 }
 
+async fn board(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    // required and pre defined so fine to unwrap
+    let weekday = cmds
+        .get("weekday")
+        .and_then(|v| v.as_str())
+        .unwrap()
+        .parse::<Weekday>()
+        .unwrap();
+
+    match cmds.get("channel").and_then(|v| v.as_str()) {
+        Some(channel) => {
+            let channel_id = channel
+                .parse::<ChannelId>()
+                .context("Unexpected channel value")
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            trace.step("Looking for channel in guild");
+            if !guild_id.channels(ctx).await?.contains_key(&channel_id) {
+                Err(signup_board::SignupBoardError::ChannelNotFound(channel_id))
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+            }
+
+            trace.step("Saving to db");
+            db::BoardChannel::new(guild_id, weekday, channel_id)
+                .save(ctx)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(
+                ctx,
+                format!(
+                    "Board messages of raids on {} are posted in {}",
+                    weekday,
+                    Mention::from(channel_id)
+                ),
+                true,
+            )
+            .await?;
+        }
+        None => {
+            trace.step("Removing from db");
+            db::BoardChannel::delete(ctx, guild_id, weekday)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(
+                ctx,
+                format!("Raids on {} are only shown on the overview", weekday),
+                true,
+            )
+            .await?;
+        }
+    }
+
+    // moves existing messages over to the new channel
+    trace.step("Updating board messages");
+    signup_board::SignupBoard::get(ctx, guild_id)
+        .await?
+        .read()
+        .await
+        .update_raids(ctx, trace.clone())
+        .await?;
+
+    Ok(())
+}
+
+async fn board_finished(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    // required and pre defined so fine to unwrap
+    let mode = cmds.get("mode").and_then(|v| v.as_str()).unwrap();
+
+    trace.step("Saving to db");
+    let conf = db::Config::new(
+        guild_id,
+        String::from(BOARD_FINISHED_NAME),
+        mode.to_string(),
+    );
+    conf.save(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let what = if mode == BOARD_FINISHED_ARCHIVE {
+        "Board messages of finished raids are kept without the sign-up button"
+    } else {
+        "Board messages of finished raids are deleted"
+    };
+    aci.create_quick_info(ctx, what, true).await?;
+
+    Ok(())
+}
+
 async fn log(
     ctx: &Context,
     aci: &ApplicationCommandInteraction,
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let channel_id = option
         .options
         .get(0)
@@ -166,14 +395,15 @@ async fn log(
             .get::<LogConfigData>()
             .unwrap()
             .clone();
-        write_lock.write().await.log = Some(channel_id);
+        write_lock.write().await.log.insert(guild_id, channel_id);
     }
 
     trace.step("Saving log channel to db");
-    let conf = db::Config {
-        name: String::from(INFO_LOG_NAME),
-        value: channel_id.to_string(),
-    };
+    let conf = db::Config::new(
+        guild_id,
+        String::from(INFO_LOG_NAME),
+        channel_id.to_string(),
+    );
 
     conf.save(ctx)
         .await
@@ -186,20 +416,191 @@ async fn log(
     Ok(())
 }
 
+async fn auto(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    // required and pre defined so fine to unwrap
+    let transition = cmds.get("transition").and_then(|v| v.as_str()).unwrap();
+    let name = match transition {
+        "open" => AUTO_OPEN_NAME,
+        "close" => AUTO_CLOSE_NAME,
+        "start" => AUTO_START_NAME,
+        "finish" => AUTO_FINISH_NAME,
+        _ => bail!("Unknown transition: {}", transition),
+    };
+
+    match cmds.get("minutes").and_then(|v| v.as_i64()) {
+        Some(minutes) => {
+            trace.step("Saving to db");
+            let conf = db::Config::new(guild_id, String::from(name), minutes.to_string());
+            conf.save(ctx)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(
+                ctx,
+                format!("Automatic {} set to {} minutes", transition, minutes),
+                true,
+            )
+            .await?;
+        }
+        None => {
+            trace.step("Removing from db");
+            db::Config::delete(ctx, guild_id, String::from(name))
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, format!("Automatic {} disabled", transition), true)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn reminder(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    match cmds.get("minutes").and_then(|v| v.as_i64()) {
+        Some(minutes) => {
+            trace.step("Saving to db");
+            let conf = db::Config::new(guild_id, String::from(REMINDER_NAME), minutes.to_string());
+            conf.save(ctx)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(
+                ctx,
+                format!("Reminders are sent {} minutes before a raid", minutes),
+                true,
+            )
+            .await?;
+        }
+        None => {
+            trace.step("Removing from db");
+            db::Config::delete(ctx, guild_id, String::from(REMINDER_NAME))
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, "Reminders disabled", true)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn time_zone(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    match cmds.get("name").and_then(|v| v.as_str()) {
+        Some(name) => {
+            let tz = timezone::parse(name)
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            trace.step("Saving to db");
+            let conf =
+                db::Config::new(guild_id, String::from(TIMEZONE_NAME), tz.name().to_string());
+            conf.save(ctx)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, format!("Time zone set to {}", tz.name()), true)
+                .await?;
+        }
+        None => {
+            trace.step("Removing from db");
+            db::Config::delete(ctx, guild_id, String::from(TIMEZONE_NAME))
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, "Time zone set to UTC", true)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn reliability(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = super::helpers::command_map(option);
+
+    // required and pre defined so fine to unwrap
+    let setting = cmds.get("setting").and_then(|v| v.as_str()).unwrap();
+    let name = match setting {
+        "min_score" => MIN_SCORE_NAME,
+        "cooldown" => COOLDOWN_NAME,
+        "late_signout" => LATE_SIGNOUT_NAME,
+        _ => bail!("Unknown setting: {}", setting),
+    };
+
+    match cmds.get("value").and_then(|v| v.as_i64()) {
+        Some(value) => {
+            trace.step("Saving to db");
+            let conf = db::Config::new(guild_id, String::from(name), value.to_string());
+            conf.save(ctx)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, format!("Reliability {} set to {}", setting, value), true)
+                .await?;
+        }
+        None => {
+            trace.step("Removing from db");
+            db::Config::delete(ctx, guild_id, String::from(name))
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+
+            aci.create_quick_info(ctx, format!("Reliability {} reset", setting), true)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
+
 async fn emoji_list(
     ctx: &Context,
     aci: &ApplicationCommandInteraction,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     // load all emojis from discord emoji guild
     trace.step("Loading from emoji guild");
-    let gid = ctx
-        .data
-        .read()
-        .await
-        .get::<ConfigValuesData>()
-        .unwrap()
-        .emoji_guild_id;
+    let gid = db::Guild::by_id(ctx, guild_id).await?.emoji_guild();
     let emoji_guild = Guild::get(ctx, gid).await?;
     let emojis = emoji_guild.emojis(ctx).await?;
 
@@ -2,41 +2,46 @@ use std::{borrow::Cow, collections::HashMap, time::Duration};
 
 use super::helpers::*;
 use crate::{
-    data,
+    compose, date_input,
     db::{self, Tier, RaidState},
-    embeds::{embed_add_roles, CrossroadsEmbeds},
+    embeds::{self, embed_add_raid_details, embed_add_roles, CrossroadsEmbeds},
+    ical,
     logging::*,
-    signup_board, status,
+    reliability::{Reliability, ReliabilityConfig},
+    signup_board, status, timezone, waitlist,
 };
 use anyhow::{anyhow, bail, Context as ErrContext, Result};
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono_tz::Tz;
 use itertools::Itertools;
 use serde::Serialize;
 use serenity::model::{
+    channel::{ChannelType, ReactionType},
     id::EmojiId,
     interactions::{
         application_command::{
             ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
             ApplicationCommandOptionType,
         },
+        message_component::ButtonStyle,
         InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
     },
 };
 use serenity::{
-    builder::{CreateApplicationCommand, CreateEmbed},
+    builder::{CreateApplicationCommand, CreateComponents, CreateEmbed},
     client::Context,
     futures::future,
     futures::future::OptionFuture,
     http::AttachmentType,
     model::{
         guild::{Member, PartialGuild, Role},
-        id::RoleId,
+        id::{ChannelId, GuildId, RoleId, UserId},
         misc::Mention,
     },
 };
 use serenity_tools::{
     builder::{CreateActionRowExt, CreateEmbedExt},
-    collectors::MessageCollectorExt,
+    collectors::{MessageCollectorExt, PagedSelectorConfig, PagedSelectorError, UpdatAbleMessage},
     components::Button,
     interactions::{ApplicationCommandInteractionExt, MessageComponentInteractionExt},
 };
@@ -57,38 +62,455 @@ pub fn create() -> CreateApplicationCommand {
         o.description("Add a new Raid");
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
-            o.name("name");
-            o.description("The name of the raid");
+            o.name("day");
+            o.description(
+                "Day in the guild time zone. Example: 2022-06-17, tomorrow, next wed, fri 19:30, +3d",
+            );
             o.required(true)
         });
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
-            o.name("day");
-            o.description("Day in UTC. Format: yyyy-mm-dd");
-            o.required(true)
+            o.name("time");
+            o.description(
+                "Time in the guild time zone. Format: HH:MM. Not needed if part of the day",
+            )
         });
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
-            o.name("time");
-            o.description("Time in UTC. Format: HH:MM:SS");
-            o.required(true)
+            o.name("template");
+            o.description("Name of a template to fill in everything not set otherwise")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("name");
+            o.description("The name of the raid. Required without template")
         });
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
             o.name("roles");
-            o.description("The roles available for the raid. Comma separated list of repr's. Example: dps,druid,qfb");
-            o.required(true)
+            o.description("Comma separated list of repr's with optional quota. Example: dps:6,druid:2,qfb")
         });
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
             o.name("bosses");
-            o.description("The bosses available for the raid. Comma separated list of repr's. Example: vg,gorse,trio");
-            o.required(true)
+            o.description("The bosses available for the raid. Comma separated list of repr's. Example: vg,gorse")
         });
         o.create_sub_option(|o| {
             o.kind(ApplicationCommandOptionType::String);
             o.name("tier");
             o.description("The required tier for the raid. If left empty raid is open for everyone")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("category");
+            o.description("Name of the category the raid is listed under")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("capacity");
+            o.description("Maximum amount of sign-ups. Further sign-ups go onto a waitlist");
+            o.min_int_value(1)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("open_at");
+            o.description("Open the raid automatically. In the guild time zone. Format: yyyy-mm-dd HH:MM")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("description");
+            o.description("Markdown description of the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::User);
+            o.name("leader");
+            o.description("Commander leading the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Channel);
+            o.name("voice");
+            o.description("Voice channel used during the raid");
+            o.channel_types(&[ChannelType::Voice])
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("duration");
+            o.description("Expected duration in minutes. Defaults to 120");
+            o.min_int_value(1)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("edit");
+        o.description("Edit an existing raid. Everything left empty stays the same");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("name");
+            o.description("The new name of the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("day");
+            o.description(
+                "New day in the guild time zone. Example: 2022-06-17, tomorrow, fri 19:30, +3d",
+            )
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("time");
+            o.description("New time in the guild time zone. Format: HH:MM")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("roles");
+            o.description("Replaces all roles. Comma separated list of repr's with optional quota")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("bosses");
+            o.description("Replaces the boss pool. Comma separated list of repr's. Example: vg,gorse")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("description");
+            o.description("New Markdown description of the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::User);
+            o.name("leader");
+            o.description("New Commander leading the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Channel);
+            o.name("voice");
+            o.description("New Voice channel used during the raid");
+            o.channel_types(&[ChannelType::Voice])
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("duration");
+            o.description("New Expected duration in minutes");
+            o.min_int_value(1)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("category");
+            o.description("Name of the new category of the raid")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("clear");
+            o.description("Remove an optional detail of the raid");
+            o.add_string_choice("description", "description");
+            o.add_string_choice("leader", "leader");
+            o.add_string_choice("voice", "voice");
+            o.add_string_choice("category", "category")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("cancel");
+        o.description("Cancel a raid and notify everyone signed up");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("reason");
+            o.description("Why the raid is cancelled. Sent to everyone signed up")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("attendance");
+        o.description("Record who attended a finished raid");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommandGroup);
+        o.name("signup");
+        o.description("Manage the sign-ups of other users");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("add");
+            o.description("Sign up a user for a raid");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.required(true);
+                o.name("raid");
+                o.description("The id of the raid");
+                o.min_int_value(0)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::User);
+                o.required(true);
+                o.name("user");
+                o.description("The user to manage. Has to be registered")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.required(true);
+                o.name("roles");
+                o.description("Comma separated list of role repr's. Example: dps,druid")
+            })
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("remove");
+            o.description("Remove the sign-up of a user");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.required(true);
+                o.name("raid");
+                o.description("The id of the raid");
+                o.min_int_value(0)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::User);
+                o.required(true);
+                o.name("user");
+                o.description("The user to manage. Has to be registered")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("reason");
+                o.description("Sent to the user by DM")
+            })
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("edit");
+            o.description("Change the roles of a sign-up");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.required(true);
+                o.name("raid");
+                o.description("The id of the raid");
+                o.min_int_value(0)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::User);
+                o.required(true);
+                o.name("user");
+                o.description("The user to manage. Has to be registered")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.required(true);
+                o.name("roles");
+                o.description("Comma separated list of role repr's. Example: dps,druid")
+            })
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommandGroup);
+        o.name("template");
+        o.description("Manage raid templates");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("add");
+            o.description("Add a new raid template");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("name");
+                o.description("The name of the template");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("title");
+                o.description("The name of raids created with this template");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("roles");
+                o.description("Comma separated list of repr's with optional quota. Example: dps:6,druid:2,qfb");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("bosses");
+                o.description("Comma separated list of boss repr's. Example: vg,gorse");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("tier");
+                o.description("The required tier. If left empty raids are open for everyone")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("description");
+                o.description("A description of the raid")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.name("capacity");
+                o.description("Maximum amount of sign-ups. Further sign-ups go onto a waitlist");
+                o.min_int_value(1)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("category");
+                o.description("Name of the category raids from this template are listed under")
+            })
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("list");
+            o.description("List all raid templates")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("remove");
+            o.description("Remove a raid template");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("name");
+                o.description("The name of the template");
+                o.required(true)
+            })
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommandGroup);
+        o.name("category");
+        o.description("Manage the categories raids are ordered and grouped by");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("add");
+            o.description("Add a new raid category");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("name");
+                o.description("The name of the category");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.name("weight");
+                o.description("Categories with a higher weight are listed first. Default: 0")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("color");
+                o.description("Color of the board messages. Hex value. Example: #1abc9c")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("emoji");
+                o.description("Name of an emoji from the emoji server shown next to the raids")
+            })
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("edit");
+            o.description("Edit a raid category. Everything left empty stays the same");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("name");
+                o.description("The name of the category");
+                o.required(true)
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::Integer);
+                o.name("weight");
+                o.description("Categories with a higher weight are listed first")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("color");
+                o.description("Color of the board messages. Hex value. Example: #1abc9c")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("emoji");
+                o.description("Name of an emoji from the emoji server shown next to the raids")
+            });
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("clear");
+                o.description("Remove an optional detail of the category");
+                o.add_string_choice("color", "color");
+                o.add_string_choice("emoji", "emoji")
+            })
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("list");
+            o.description("List all raid categories")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::SubCommand);
+            o.name("remove");
+            o.description("Remove a raid category. Its raids are left without one");
+            o.create_sub_option(|o| {
+                o.kind(ApplicationCommandOptionType::String);
+                o.name("name");
+                o.description("The name of the category");
+                o.required(true)
+            })
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("capacity");
+        o.description("Change the capacity of a raid");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("capacity");
+            o.description("Maximum amount of sign-ups. If left empty the raid has no limit");
+            o.min_int_value(1)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("quota");
+        o.description("Change how many sign-ups are needed for a role of a raid");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("role");
+            o.description("The repr of the role")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("amount");
+            o.description("Amount of sign-ups needed for the role. If left empty the role has no quota");
+            o.min_int_value(0)
         })
     });
     app.create_option(|o| {
@@ -110,7 +532,7 @@ pub fn create() -> CreateApplicationCommand {
             o.kind(ApplicationCommandOptionType::String);
             o.name("day");
             o.description(
-                "Select all raids from that day. Format: yyyy-mm-dd. Comma separated list",
+                "Select all raids from those days. Comma separated. Example: today, fri, mon..sun, +3d",
             )
         });
         o.create_sub_option(|o| {
@@ -128,8 +550,13 @@ pub fn create() -> CreateApplicationCommand {
             o.required(true);
             o.name("day");
             o.description(
-                "Select all raids from that day. Format: yyyy-mm-dd. Comma separated list",
+                "Select all raids from those days. Comma separated. Example: today, fri, mon..sun, +3d",
             )
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("category");
+            o.description("Only list raids of this category")
         })
     });
     app.create_option(|o| {
@@ -140,7 +567,7 @@ pub fn create() -> CreateApplicationCommand {
             o.kind(ApplicationCommandOptionType::String);
             o.name("day");
             o.description(
-                "Select all raids from that day. Format: yyyy-mm-dd. Comma separated list",
+                "Select all raids from those days. Comma separated. Example: today, fri, mon..sun, +3d",
             )
         });
         o.create_sub_option(|o| {
@@ -156,6 +583,30 @@ pub fn create() -> CreateApplicationCommand {
             o.add_string_choice("csv", "csv")
         })
     });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("compose");
+        o.description("Build a squad with subgroups out of the sign-ups of a raid");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the raid");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("composition");
+            o.description("Comma separated list of repr's with amount. Default: role quotas. Example: dps:6,druid:2")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("format");
+            o.description("Select the download format. Default: csv");
+            o.add_string_choice("json", "json");
+            o.add_string_choice("csv", "csv")
+        })
+    });
     app.create_option(|o| {
         o.kind(ApplicationCommandOptionType::SubCommand);
         o.name("info");
@@ -182,7 +633,16 @@ pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
         if let Some(sub) = aci.data.options.get(0) {
             match sub.name.as_ref() {
                 "add" => add(ctx, aci, sub, trace).await,
+                "edit" => edit(ctx, aci, sub, trace).await,
+                "cancel" => cancel(ctx, aci, sub, trace).await,
+                "attendance" => attendance(ctx, aci, sub, trace).await,
                 "set" => set(ctx, aci, sub, trace).await,
+                "capacity" => capacity(ctx, aci, sub, trace).await,
+                "quota" => quota(ctx, aci, sub, trace).await,
+                "compose" => compose(ctx, aci, sub, trace).await,
+                "template" => template(ctx, aci, sub, trace).await,
+                "category" => category(ctx, aci, sub, trace).await,
+                "signup" => signup(ctx, aci, sub, trace).await,
                 "download" => download(ctx, aci, sub, trace).await,
                 "info" => info(ctx, aci, sub, trace).await,
                 "list" => list(ctx, aci, sub, trace).await,
@@ -195,26 +655,35 @@ pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
     .await;
 }
 
-async fn raids_from_days(ctx: &Context, value: &str) -> Result<Vec<db::Raid>> {
-    let days: Vec<NaiveDate> = value
-        .split(',')
-        .map(|s| s.parse())
-        .collect::<Result<Vec<_>, _>>()
-        .context("Could not parse date")?;
-
-    let raids_fut = days
-        .into_iter()
-        .map(|d| db::Raid::by_date(ctx, d))
-        .collect::<Vec<_>>();
+/// Loads all raids of the given days. Also returns the resolved days so they
+/// can be shown to the user
+async fn raids_from_days(
+    ctx: &Context,
+    guild_id: GuildId,
+    value: &str,
+) -> Result<(Vec<NaiveDate>, Vec<db::Raid>)> {
+    let tz = timezone::load(ctx, guild_id).await?;
+    let days = date_input::parse_days(value, timezone::today(&tz))?;
+
+    let mut raids_fut = Vec::with_capacity(days.len());
+    for d in &days {
+        let (from, to) = timezone::day_bounds(&tz, *d);
+        raids_fut.push(db::Raid::between(ctx, guild_id, from, to));
+    }
 
-    Ok(future::try_join_all(raids_fut)
+    let raids = future::try_join_all(raids_fut)
         .await?
         .into_iter()
         .flatten()
-        .collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    Ok((days, raids))
+}
+
+fn days_fmt(days: &[NaiveDate]) -> String {
+    days.iter().map(|d| d.format("%a, %Y-%m-%d")).join("\n")
 }
 
-async fn raids_from_ids(ctx: &Context, value: &str) -> Result<Vec<db::Raid>> {
+async fn raids_from_ids(ctx: &Context, guild_id: GuildId, value: &str) -> Result<Vec<db::Raid>> {
     let i: Vec<i32> = value
         .split(',')
         .map(|s| s.parse())
@@ -222,7 +691,7 @@ async fn raids_from_ids(ctx: &Context, value: &str) -> Result<Vec<db::Raid>> {
 
     let raids_fut = i
         .into_iter()
-        .map(|i| db::Raid::by_id(ctx, i))
+        .map(|i| db::Raid::by_id(ctx, guild_id, i))
         .collect::<Vec<_>>();
 
     Ok(future::try_join_all(raids_fut)
@@ -236,39 +705,146 @@ async fn add(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     let cmds = command_map(option);
 
+    trace.step("Loading template");
+    let template_fut: OptionFuture<_> = cmds
+        .get("template")
+        .and_then(|v| v.as_str())
+        .map(|t| db::RaidTemplate::by_name(ctx, guild_id, t.to_owned()))
+        .into();
+
+    let template = template_fut
+        .await
+        .transpose()
+        .context("Failed to load template")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    if template.is_none()
+        && !(cmds.contains_key("name") && cmds.contains_key("roles") && cmds.contains_key("bosses"))
+    {
+        Err(anyhow!("name, roles and bosses are required without a template"))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
     trace.step("Parsing basic raid data");
 
-    let name = cmds
-        .get("name")
-        .and_then(|n| n.as_str())
-        .context("name not set")?;
+    // Everything set on the command overrides the template
+    let name = match cmds.get("name").and_then(|n| n.as_str()) {
+        Some(n) => n.to_string(),
+        None => template.as_ref().context("name not set")?.title.clone(),
+    };
 
-    let day: NaiveDate = cmds
-        .get("day")
-        .and_then(|n| n.as_str())
-        .context("day not set")?
-        .parse()
-        .context("Could not parse date")
+    let tz = timezone::load(ctx, guild_id).await?;
+    let (day, day_time) = date_input::parse_day_time(
+        cmds.get("day")
+            .and_then(|n| n.as_str())
+            .context("day not set")?,
+        timezone::today(&tz),
+    )
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    // an explicit time wins over one given with the day
+    let time: NaiveTime = match cmds.get("time").and_then(|n| n.as_str()) {
+        Some(t) => date_input::parse_time(t)
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?,
+        None => day_time
+            .context("time not set. Either add it to the day or set it separately")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?,
+    };
+
+    let datetime = timezone::to_utc(&tz, day.and_time(time))
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
 
-    let time: NaiveTime = cmds
-        .get("time")
+    let capacity = cmds
+        .get("capacity")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Capacity out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .or_else(|| template.as_ref().and_then(|t| t.capacity));
+
+    let open_at = cmds
+        .get("open_at")
         .and_then(|n| n.as_str())
-        .context("time not set")?
-        .parse()
-        .context("Could not parse time")
+        .map(|o| NaiveDateTime::parse_from_str(o, "%Y-%m-%d %H:%M"))
+        .transpose()
+        .context("Could not parse open_at")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .map(|o| timezone::to_utc(&tz, o))
+        .transpose()
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
 
-    let datetime: NaiveDateTime = day.and_time(time);
+    let details = db::RaidDetails {
+        description: template.as_ref().and_then(|t| t.description.clone()),
+        ..Default::default()
+    };
+    let details = parse_raid_details(ctx, aci, &cmds, details).await?;
 
-    let mut emb = CreateEmbed::xdefault();
+    trace.step("Loading category");
+    let category_fut: OptionFuture<_> = cmds
+        .get("category")
+        .and_then(|v| v.as_str())
+        .map(|c| db::RaidCategory::by_name(ctx, guild_id, c.to_owned()))
+        .into();
+
+    let category = category_fut
+        .await
+        .transpose()
+        .context("Failed to load category")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let category = match (category, &template) {
+        (None, Some(t)) => {
+            t.get_category(ctx)
+                .await
+                .transpose()
+                .context("Failed to load template category")
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?
+        }
+        (c, _) => c,
+    };
+
+    let mut emb = CreateEmbed::xdefault();
     emb.title("Creating a new raid");
-    emb.field("Name", name, false);
-    emb.field("Date/Time", format!("<t:{}>", datetime.timestamp()), false);
+    if let Some(t) = &template {
+        emb.field("Template", &t.name, false);
+    }
+    emb.field("Name", &name, false);
+    if let Some(c) = &category {
+        emb.field("Category", &c.name, false);
+    }
+    emb.field(
+        "Date/Time",
+        format!(
+            "{} ({})\n<t:{}>",
+            day.and_time(time).format("%a, %Y-%m-%d %H:%M"),
+            tz.name(),
+            datetime.timestamp()
+        ),
+        false,
+    );
+    match capacity {
+        Some(c) => emb.field("Capacity", c, false),
+        None => emb.field("Capacity", "Unlimited", false),
+    };
+    if let Some(o) = open_at {
+        emb.field("Opens", format!("<t:{}>", o.timestamp()), false);
+    }
+    embed_add_raid_details(&mut emb, &details);
 
     let mut emb_loading_roles = emb.clone();
     emb_loading_roles.field("Roles", "Loading...", false);
@@ -285,26 +861,59 @@ async fn add(
 
     trace.step("Loading raid roles");
 
-    let roles_str: Vec<&str> = cmds
-        .get("roles")
-        .and_then(|n| n.as_str())
-        .context("roles not set")?
-        .split(',')
-        .into_iter()
-        .map(|s| s.trim())
-        .collect();
+    let roles_with_quota: Vec<(db::Role, Option<i32>)> =
+        match cmds.get("roles").and_then(|n| n.as_str()) {
+            Some(roles_str) => {
+                let roles_str = parse_repr_amounts(roles_str)
+                    .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                    .await?;
+                let mut roles = Vec::with_capacity(roles_str.len());
+                for (r, q) in roles_str {
+                    let nr = db::Role::by_repr(ctx, guild_id, r.clone())
+                        .await
+                        .with_context(|| format!("Failed to load role: {}", r))
+                        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                        .await?;
+                    roles.push((nr, q));
+                }
+                roles
+            }
+            None => template
+                .as_ref()
+                .context("roles not set")?
+                .get_roles(ctx)
+                .await
+                .context("Failed to load template roles")
+                .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                .await?,
+        };
 
-    let mut roles: Vec<db::Role> = Vec::with_capacity(roles_str.len());
-    for r in roles_str {
-        let nr = db::Role::by_repr(ctx, r.to_string())
-            .await
-            .with_context(|| format!("Failed to load role: {}", r))
-            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-            .await?;
-        roles.push(nr);
-    }
+    let quotas: HashMap<i32, i32> = roles_with_quota
+        .iter()
+        .filter_map(|(r, q)| q.map(|q| (r.id, q)))
+        .collect();
+    let roles: Vec<db::Role> = roles_with_quota.into_iter().map(|(r, _)| r).collect();
 
     embed_add_roles(&mut emb, &roles, true, false);
+    if !quotas.is_empty() {
+        emb.fields_chunked_fmt(
+            &roles
+                .iter()
+                .filter_map(|r| quotas.get(&r.id).map(|q| (r, q)))
+                .collect::<Vec<_>>(),
+            |(r, q)| {
+                format!(
+                    "{} | {}: {}",
+                    Mention::from(EmojiId::from(r.emoji as u64)),
+                    r.title,
+                    q
+                )
+            },
+            "Role Quotas",
+            true,
+            10,
+        );
+    }
 
     let mut emb_loading_bosses = emb.clone();
     emb_loading_bosses.field("Bosses", "Loading...", false);
@@ -313,24 +922,29 @@ async fn add(
 
     trace.step("Loading raid bosses");
 
-    let bosses_str: Vec<&str> = cmds
-        .get("bosses")
-        .and_then(|n| n.as_str())
-        .context("bosses not set")?
-        .split(',')
-        .into_iter()
-        .map(|s| s.trim())
-        .collect();
-
-    let mut bosses: Vec<db::RaidBoss> = Vec::with_capacity(bosses_str.len());
-    for b in bosses_str {
-        let nb = db::RaidBoss::by_repr(ctx, b.to_string())
+    let bosses: Vec<db::RaidBoss> = match cmds.get("bosses").and_then(|n| n.as_str()) {
+        Some(bosses_str) => {
+            let bosses_str: Vec<&str> = bosses_str.split(',').map(|s| s.trim()).collect();
+            let mut bosses = Vec::with_capacity(bosses_str.len());
+            for b in bosses_str {
+                let nb = db::RaidBoss::by_repr(ctx, guild_id, b.to_string())
+                    .await
+                    .with_context(|| format!("Failed to load boss {}", b))
+                    .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                    .await?;
+                bosses.push(nb);
+            }
+            bosses
+        }
+        None => template
+            .as_ref()
+            .context("bosses not set")?
+            .get_raid_bosses(ctx)
             .await
-            .with_context(|| format!("Failed to load boss {}", b))
+            .context("Failed to load template bosses")
             .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-            .await?;
-        bosses.push(nb);
-    }
+            .await?,
+    };
 
     emb.fields_chunked_fmt(&bosses, |b| b.name.clone(), "Boss Pool", false, 10);
 
@@ -340,14 +954,15 @@ async fn add(
         .await?;
 
     trace.step("Loading tier");
-    let tier_fut: OptionFuture<_> = cmds
-        .get("tier")
-        .and_then(|v| v.as_str())
-        .map(|t| Tier::by_name(ctx, t.to_owned()))
-        .into();
+    let tier = match cmds.get("tier").and_then(|v| v.as_str()) {
+        Some(t) => Some(Tier::by_name(ctx, guild_id, t.to_owned()).await),
+        None => match &template {
+            Some(t) => t.get_tier(ctx).await,
+            None => None,
+        },
+    };
 
-    let tier = tier_fut
-        .await
+    let tier = tier
         .transpose()
         .context("Failed to load tier")
         .map_err_reply(|what| aci.edit_quick_error(ctx, what))
@@ -375,29 +990,26 @@ async fn add(
         match react.parse_button()? {
             Button::Confirm => {
                 trace.step("Confirmed. Saving raid");
-                let raid =
-                    db::Raid::insert(ctx, name.to_string(), datetime, tier.map(|t| t.id))
-                        .await
-                        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-                        .await?;
-
-                trace.step("Saving roles");
-                for r in roles {
-                    raid
-                        .add_role(ctx, r.id)
-                        .await
-                        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-                        .await?;
-                }
-
-                trace.step("Saving raid bosses");
-                for tb in bosses {
-                    raid
-                        .add_raid_boss(ctx, tb.id)
-                        .await
-                        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-                        .await?;
-                }
+                let raid_roles = roles
+                    .iter()
+                    .map(|r| (r.id, quotas.get(&r.id).copied()))
+                    .collect();
+                let raid = db::Raid::insert(
+                    ctx,
+                    guild_id,
+                    name,
+                    datetime,
+                    tier.map(|t| t.id),
+                    capacity,
+                    category.as_ref().map(|c| c.id),
+                    open_at,
+                    details,
+                    raid_roles,
+                    bosses.iter().map(|b| b.id).collect(),
+                )
+                .await
+                .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                .await?;
 
                 emb.field("Raid ID", raid.id, false);
                 emb.footer(|f| f.text(format!("Raid added {}", CHECK_EMOJI)));
@@ -406,6 +1018,8 @@ async fn add(
                     d.components(|c| c)
                 })
                 .await?;
+
+                signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
             }
             Button::Abort => {
                 trace.step("Aborted");
@@ -422,12 +1036,1796 @@ async fn add(
     Ok(())
 }
 
+/// Parses the description, leader, voice and duration options.
+/// Everything not set is taken from `details`
+async fn parse_raid_details(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    cmds: &HashMap<String, serde_json::Value>,
+    mut details: db::RaidDetails,
+) -> Result<db::RaidDetails> {
+    if let Some(d) = cmds.get("description").and_then(|v| v.as_str()) {
+        details.description = Some(d.to_string());
+    }
+
+    if let Some(l) = cmds.get("leader").and_then(|v| v.as_str()) {
+        let leader = l
+            .parse::<UserId>()
+            .context("Invalid leader")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        details.leader_id = Some(leader.0 as i64);
+    }
+
+    if let Some(v) = cmds.get("voice").and_then(|v| v.as_str()) {
+        let voice = v
+            .parse::<ChannelId>()
+            .context("Invalid voice channel")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        details.voice_channel_id = Some(voice.0 as i64);
+    }
+
+    if let Some(d) = cmds.get("duration").and_then(|v| v.as_i64()) {
+        details.duration = i32::try_from(d)
+            .context("Duration out of range")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    Ok(details)
+}
+
+fn role_emoji_fmt(r: &db::Role) -> String {
+    format!(
+        "{} | {}",
+        Mention::from(EmojiId::from(r.emoji as u64)),
+        r.title
+    )
+}
+
+async fn edit(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Parsing basic raid data");
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .map_or_else(|| raid.title.clone(), |n| n.to_string());
+
+    let tz = timezone::load(ctx, guild_id).await?;
+    let (day, day_time) = match cmds.get("day").and_then(|n| n.as_str()) {
+        Some(d) => date_input::parse_day_time(d, timezone::today(&tz))
+            .map(|(d, t)| (Some(d), t))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?,
+        None => (None, None),
+    };
+
+    // an explicit time wins over one given with the day
+    let time: Option<NaiveTime> = cmds
+        .get("time")
+        .and_then(|n| n.as_str())
+        .map(date_input::parse_time)
+        .transpose()
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .or(day_time);
+
+    let datetime = if day.is_none() && time.is_none() {
+        raid.date
+    } else {
+        // whatever is left empty stays the same in the guild time zone
+        let local = timezone::to_local(&tz, raid.date);
+        let day = day.unwrap_or_else(|| local.date());
+        let time = time.unwrap_or_else(|| local.time());
+        timezone::to_utc(&tz, day.and_time(time))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?
+    };
+
+    let mut details = raid.details();
+    match cmds.get("clear").and_then(|v| v.as_str()) {
+        Some("description") => details.description = None,
+        Some("leader") => details.leader_id = None,
+        Some("voice") => details.voice_channel_id = None,
+        _ => (),
+    }
+    let details = parse_raid_details(ctx, aci, &cmds, details).await?;
+    let curr_details = raid.details();
+
+    trace.step("Loading category");
+    let curr_category = raid
+        .get_category(ctx)
+        .await
+        .transpose()
+        .context("Failed to load the current category")?;
+    let category = match cmds.get("category").and_then(|v| v.as_str()) {
+        Some(c) => Some(
+            db::RaidCategory::by_name(ctx, guild_id, c.to_owned())
+                .await
+                .with_context(|| format!("Category **{}** does not exist", c))
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?,
+        ),
+        None if cmds.get("clear").and_then(|v| v.as_str()) == Some("category") => None,
+        None => curr_category.clone(),
+    };
+    let category_changed = category.as_ref().map(|c| c.id) != raid.category_id;
+
+    trace.step("Loading raid roles");
+    let curr_roles: Vec<(db::Role, Option<i32>)> = raid
+        .role_slots(ctx)
+        .await?
+        .into_iter()
+        .map(|s| (s.role, s.quota))
+        .collect();
+
+    let new_roles: Vec<(db::Role, Option<i32>)> = match cmds.get("roles").and_then(|n| n.as_str()) {
+        Some(roles_str) => {
+            let roles_str = parse_repr_amounts(roles_str)
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+            let mut roles = Vec::with_capacity(roles_str.len());
+            for (r, q) in roles_str {
+                let nr = db::Role::by_repr(ctx, guild_id, r.clone())
+                    .await
+                    .with_context(|| format!("Failed to load role: {}", r))
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                roles.push((nr, q));
+            }
+            roles
+        }
+        None => curr_roles.clone(),
+    };
+
+    let removed_roles: Vec<&db::Role> = curr_roles
+        .iter()
+        .filter(|(r, _)| !new_roles.iter().any(|(n, _)| n.id == r.id))
+        .map(|(r, _)| r)
+        .collect();
+    let added_roles: Vec<&(db::Role, Option<i32>)> = new_roles
+        .iter()
+        .filter(|(n, _)| !curr_roles.iter().any(|(r, _)| r.id == n.id))
+        .collect();
+    let changed_quotas: Vec<&(db::Role, Option<i32>)> = new_roles
+        .iter()
+        .filter(|(n, q)| curr_roles.iter().any(|(r, cq)| r.id == n.id && cq != q))
+        .collect();
+
+    trace.step("Loading raid bosses");
+    let curr_bosses = raid.all_raid_bosses(ctx).await?;
+    let new_bosses: Vec<db::RaidBoss> = match cmds.get("bosses").and_then(|n| n.as_str()) {
+        Some(bosses_str) => {
+            let bosses_str: Vec<&str> = bosses_str.split(',').map(|s| s.trim()).collect();
+            let mut bosses = Vec::with_capacity(bosses_str.len());
+            for b in bosses_str {
+                let nb = db::RaidBoss::by_repr(ctx, guild_id, b.to_string())
+                    .await
+                    .with_context(|| format!("Failed to load boss {}", b))
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                bosses.push(nb);
+            }
+            bosses
+        }
+        None => curr_bosses.clone(),
+    };
+
+    let removed_bosses: Vec<&db::RaidBoss> = curr_bosses
+        .iter()
+        .filter(|b| !new_bosses.iter().any(|n| n.id == b.id))
+        .collect();
+    let added_bosses: Vec<&db::RaidBoss> = new_bosses
+        .iter()
+        .filter(|n| !curr_bosses.iter().any(|b| b.id == n.id))
+        .collect();
+
+    let title_changed = name != raid.title;
+    let date_changed = datetime != raid.date;
+    let roles_changed = !removed_roles.is_empty() || !added_roles.is_empty();
+
+    if !title_changed
+        && !date_changed
+        && details == curr_details
+        && !category_changed
+        && !roles_changed
+        && changed_quotas.is_empty()
+        && removed_bosses.is_empty()
+        && added_bosses.is_empty()
+    {
+        Err(anyhow!("Nothing to change"))
+            .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+            .await?;
+    }
+
+    let quota_fmt = |(r, q): &&(db::Role, Option<i32>)| match q {
+        Some(q) => format!("{}: {}", role_emoji_fmt(r), q),
+        None => format!("{}: -", role_emoji_fmt(r)),
+    };
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title(format!("Editing raid {}", raid.id));
+    if title_changed {
+        emb.field("Name", format!("{} → {}", raid.title, name), false);
+    }
+    if date_changed {
+        emb.field(
+            "Date/Time",
+            format!(
+                "<t:{}> → <t:{}>",
+                raid.date.timestamp(),
+                datetime.timestamp()
+            ),
+            false,
+        );
+    }
+    if details.description != curr_details.description {
+        emb.field(
+            "Description",
+            format!(
+                "{} → {}",
+                curr_details.description.as_deref().unwrap_or("-"),
+                details.description.as_deref().unwrap_or("-")
+            ),
+            false,
+        );
+    }
+    let mention_fmt = |m: Option<Mention>| m.map_or_else(|| "-".to_string(), |m| m.to_string());
+    if details.leader_id != curr_details.leader_id {
+        let leader = |l: Option<i64>| mention_fmt(l.map(|l| UserId::from(l as u64).into()));
+        emb.field(
+            "Leader",
+            format!(
+                "{} → {}",
+                leader(curr_details.leader_id),
+                leader(details.leader_id)
+            ),
+            false,
+        );
+    }
+    if details.voice_channel_id != curr_details.voice_channel_id {
+        let voice = |v: Option<i64>| mention_fmt(v.map(|v| ChannelId::from(v as u64).into()));
+        emb.field(
+            "Voice Channel",
+            format!(
+                "{} → {}",
+                voice(curr_details.voice_channel_id),
+                voice(details.voice_channel_id)
+            ),
+            false,
+        );
+    }
+    if details.duration != curr_details.duration {
+        emb.field(
+            "Duration",
+            format!(
+                "{} → {}",
+                embeds::duration_fmt(curr_details.duration),
+                embeds::duration_fmt(details.duration)
+            ),
+            false,
+        );
+    }
+    if category_changed {
+        let category_name = |c: &Option<db::RaidCategory>| {
+            c.as_ref()
+                .map_or_else(|| "-".to_string(), |c| c.name.clone())
+        };
+        emb.field(
+            "Category",
+            format!(
+                "{} → {}",
+                category_name(&curr_category),
+                category_name(&category)
+            ),
+            false,
+        );
+    }
+    if !added_roles.is_empty() {
+        emb.fields_chunked_fmt(&added_roles, quota_fmt, "Roles added", true, 10);
+    }
+    if !removed_roles.is_empty() {
+        emb.fields_chunked_fmt(
+            &removed_roles,
+            |r| role_emoji_fmt(r),
+            "Roles removed",
+            true,
+            10,
+        );
+    }
+    if !changed_quotas.is_empty() {
+        emb.fields_chunked_fmt(&changed_quotas, quota_fmt, "Quotas changed", true, 10);
+    }
+    if !added_bosses.is_empty() {
+        emb.fields_chunked_fmt(&added_bosses, |b| b.name.clone(), "Bosses added", false, 10);
+    }
+    if !removed_bosses.is_empty() {
+        emb.fields_chunked_fmt(
+            &removed_bosses,
+            |b| b.name.clone(),
+            "Bosses removed",
+            false,
+            10,
+        );
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb.clone());
+            d.components(|c| c.create_action_row(|a| a.confirm_button().abort_button()))
+        })
+    })
+    .await?;
+
+    let msg = aci.get_interaction_response(ctx).await?;
+
+    trace.step("Waiting for confirm");
+    let react = match msg
+        .await_confirm_abort_interaction(ctx)
+        .timeout(Duration::from_secs(60))
+        .await
+    {
+        Some(r) => r,
+        None => {
+            Err(anyhow!("Timed out"))
+                .map_err_reply(|what| aci.edit_quick_info(ctx, what))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    react.defer(ctx).await?;
+    match react.parse_button()? {
+        Button::Confirm => (),
+        Button::Abort => {
+            trace.step("Aborted");
+            aci.edit_quick_info(ctx, "Aborted").await?;
+            return Ok(());
+        }
+        _ => bail!("Unexpected interaction"),
+    }
+
+    trace.step("Confirmed. Saving raid");
+    let updated = raid
+        .set_title_and_date(ctx, name, datetime)
+        .await
+        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+        .await?;
+
+    let updated = if details != curr_details {
+        updated
+            .set_details(ctx, details)
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?
+    } else {
+        updated
+    };
+
+    let updated = if category_changed {
+        updated
+            .set_category(ctx, category.as_ref().map(|c| c.id))
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?
+    } else {
+        updated
+    };
+
+    if date_changed {
+        // The new date needs its own reminder
+        updated
+            .set_reminded(ctx, false)
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?;
+
+        // Otherwise the schedule would create the raid again for the old date
+        if let Some(schedule_id) = raid.schedule_id {
+            let schedule = db::RaidSchedule::by_id(ctx, guild_id, schedule_id).await?;
+            // skips are days in the guild time zone
+            let old_day = timezone::to_local(&tz, raid.date).date();
+            if !schedule.get_skips(ctx).await?.contains(&old_day) {
+                schedule.skip(ctx, old_day).await?;
+            }
+        }
+    }
+
+    trace.step("Saving roles");
+    for (r, q) in &added_roles {
+        updated
+            .add_role(ctx, r.id, *q)
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?;
+    }
+    for (r, q) in &changed_quotas {
+        updated
+            .set_role_quota(ctx, r.id, *q)
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?;
+    }
+    for r in &removed_roles {
+        updated
+            .remove_role(ctx, r.id)
+            .await
+            .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+            .await?;
+    }
+
+    trace.step("Saving raid bosses");
+    updated
+        .update_raid_bosses(
+            ctx,
+            added_bosses.iter().map(|b| b.id).collect(),
+            removed_bosses.iter().map(|b| b.id).collect(),
+        )
+        .await
+        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+        .await?;
+
+    let mut not_notified: Vec<String> = Vec::new();
+    if date_changed || roles_changed {
+        trace.step("Notifying sign-ups");
+        for signup in updated.get_signups(ctx).await? {
+            // Selected roles that are no longer available have to be picked again
+            let lost_roles: Vec<db::Role> = signup
+                .get_roles(ctx)
+                .await?
+                .into_iter()
+                .filter(|r| removed_roles.iter().any(|rr| rr.id == r.id))
+                .collect();
+            for r in &lost_roles {
+                signup.remove_role(ctx, r.id).await?;
+            }
+            if !lost_roles.is_empty() {
+                signup.set_needs_role_update(ctx, true).await?;
+            }
+
+            let mut dm_emb = CreateEmbed::xdefault();
+            dm_emb.title(format!("Raid changed: {}", updated.title));
+            dm_emb.description("A raid you signed up for was changed");
+            let (a, b, c) = embeds::field_raid_date(&updated, &tz);
+            dm_emb.field(a, b, c);
+            if date_changed {
+                dm_emb.field(
+                    "Previous Date",
+                    format!("<t:{}:F>", raid.date.timestamp()),
+                    false,
+                );
+            }
+            if roles_changed {
+                let roles = new_roles.iter().map(|(r, _)| r.clone()).collect::<Vec<_>>();
+                embed_add_roles(&mut dm_emb, &roles, true, false);
+            }
+            if !lost_roles.is_empty() {
+                dm_emb.field(
+                    "⚠️ Please select your roles again",
+                    format!(
+                        "These roles you selected were removed: {}",
+                        lost_roles.iter().map(|r| r.title.as_str()).join(", ")
+                    ),
+                    false,
+                );
+            }
+
+            let user = signup.get_user(ctx).await?;
+            let sent = match UserId::from(user.discord_id()).to_user(ctx).await {
+                Ok(u) => u.dm(ctx, |m| m.set_embed(dm_emb)).await.is_ok(),
+                Err(_) => false,
+            };
+            if !sent {
+                not_notified.push(user.gw2_id);
+            }
+        }
+    }
+
+    emb.footer(|f| f.text(format!("Raid updated {}", CHECK_EMOJI)));
+    if !not_notified.is_empty() {
+        emb.fields_chunked_fmt(
+            &not_notified,
+            |u| u.clone(),
+            "Could not be notified",
+            true,
+            10,
+        );
+    }
+    aci.edit_original_interaction_response(ctx, |d| {
+        d.add_embed(emb);
+        d.components(|c| c)
+    })
+    .await?;
+
+    signup_board::notify_change(ctx, guild_id, &[updated.id]).await;
+
+    trace.step("Updating status");
+    status::update_status(ctx).await;
+
+    Ok(())
+}
+
+async fn cancel(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let reason = cmds
+        .get("reason")
+        .and_then(|v| v.as_str())
+        .context("Expected reason field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .to_string();
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    if raid.state == RaidState::Finished || raid.state == RaidState::Cancelled {
+        Err(anyhow!("Raid is already {}", raid.state))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    let signups = raid.get_signups(ctx).await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    let tz = timezone::load(ctx, guild_id).await?;
+    emb.title(format!("Cancelling raid {}", raid.id));
+    emb.field("Name", &raid.title, false);
+    let (a, b, c) = embeds::field_raid_date(&raid, &tz);
+    emb.field(a, b, c);
+    emb.field("Reason", &reason, false);
+    emb.field("Sign-ups to notify", signups.len(), false);
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb.clone());
+            d.components(|c| c.create_action_row(|a| a.confirm_button().abort_button()))
+        })
+    })
+    .await?;
+
+    let msg = aci.get_interaction_response(ctx).await?;
+
+    trace.step("Waiting for confirm");
+    let react = match msg
+        .await_confirm_abort_interaction(ctx)
+        .timeout(Duration::from_secs(60))
+        .await
+    {
+        Some(r) => r,
+        None => {
+            Err(anyhow!("Timed out"))
+                .map_err_reply(|what| aci.edit_quick_info(ctx, what))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    react.defer(ctx).await?;
+    match react.parse_button()? {
+        Button::Confirm => (),
+        Button::Abort => {
+            trace.step("Aborted");
+            aci.edit_quick_info(ctx, "Aborted").await?;
+            return Ok(());
+        }
+        _ => bail!("Unexpected interaction"),
+    }
+
+    trace.step("Confirmed. Cancelling raid");
+    let not_notified = cancel_raid(ctx, raid, reason, trace)
+        .await
+        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+        .await?;
+
+    emb.footer(|f| f.text(format!("Raid cancelled {}", CHECK_EMOJI)));
+    if !not_notified.is_empty() {
+        emb.fields_chunked_fmt(
+            &not_notified,
+            |u| u.clone(),
+            "Could not be notified",
+            true,
+            10,
+        );
+    }
+    aci.edit_original_interaction_response(ctx, |d| {
+        d.add_embed(emb);
+        d.components(|c| c)
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// Cancels the raid and lets everyone signed up know by DM. Returns the
+/// sign-ups that could not be notified
+pub(super) async fn cancel_raid(
+    ctx: &Context,
+    raid: db::Raid,
+    reason: String,
+    trace: LogTrace,
+) -> Result<Vec<String>> {
+    let signups = raid.get_signups(ctx).await?;
+
+    // Raid and sign-ups are kept for the history
+    let raid = raid
+        .set_cancel_reason(ctx, Some(reason.clone()))
+        .await?
+        .set_state(ctx, RaidState::Cancelled)
+        .await?;
+
+    trace.step("Notifying sign-ups");
+    let tz = timezone::load(ctx, raid.guild()).await?;
+    let mut not_notified: Vec<String> = Vec::new();
+    for signup in signups {
+        let mut dm_emb = CreateEmbed::xdefault();
+        dm_emb.title(format!("Raid cancelled: {}", raid.title));
+        dm_emb.description("A raid you signed up for was cancelled");
+        let (a, b, c) = embeds::field_raid_date(&raid, &tz);
+        dm_emb.field(a, b, c);
+        dm_emb.field("Reason", &reason, false);
+
+        let user = signup.get_user(ctx).await?;
+        let sent = match UserId::from(user.discord_id()).to_user(ctx).await {
+            Ok(u) => u.dm(ctx, |m| m.set_embed(dm_emb)).await.is_ok(),
+            Err(_) => false,
+        };
+        if !sent {
+            not_notified.push(user.gw2_id);
+        }
+    }
+
+    signup_board::notify_change(ctx, GuildId::from(raid.guild_id()), &[raid.id]).await;
+
+    trace.step("Updating status");
+    status::update_status(ctx).await;
+
+    Ok(not_notified)
+}
+
+const ATTENDANCE_BUTTONS: [(db::AttendanceStatus, &str, &str); 3] = [
+    (
+        db::AttendanceStatus::NoShow,
+        "raid_attendance_no_show",
+        "No-Shows",
+    ),
+    (db::AttendanceStatus::Late, "raid_attendance_late", "Late"),
+    (
+        db::AttendanceStatus::Substitute,
+        "raid_attendance_substitute",
+        "Substitutes",
+    ),
+];
+
+fn attendance_components(c: &mut CreateComponents) -> &mut CreateComponents {
+    c.create_action_row(|ar| {
+        for (_, id, label) in ATTENDANCE_BUTTONS {
+            ar.create_button(|b| b.style(ButtonStyle::Primary).label(label).custom_id(id));
+        }
+        ar
+    });
+    c.create_action_row(|ar| ar.confirm_button().abort_button())
+}
+
+fn attendance_embed(
+    raid: &db::Raid,
+    tz: &Tz,
+    users: &[db::User],
+    statuses: &[Option<db::AttendanceStatus>],
+) -> CreateEmbed {
+    let mut emb = CreateEmbed::xdefault();
+    emb.title(format!("Attendance: {}", raid.title));
+    emb.description(
+        "Everyone signed up is marked as attended. \
+        Use the buttons to select the exceptions and confirm to save",
+    );
+    let (a, b, c) = embeds::field_raid_date(raid, tz);
+    emb.field(a, b, c);
+    for (status, title) in [
+        (Some(db::AttendanceStatus::Attended), "Attended"),
+        (Some(db::AttendanceStatus::Late), "Late"),
+        (Some(db::AttendanceStatus::Substitute), "Substitutes"),
+        (Some(db::AttendanceStatus::NoShow), "No-Shows"),
+        (None, "Not participating"),
+    ] {
+        let names: Vec<&str> = users
+            .iter()
+            .zip(statuses)
+            .filter(|(_, s)| **s == status)
+            .map(|(u, _)| u.gw2_id.as_str())
+            .collect();
+        if !names.is_empty() {
+            emb.fields_chunked_fmt(&names, |n| n.to_string(), title, true, 20);
+        }
+    }
+    emb
+}
+
+async fn attendance(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    if raid.state != RaidState::Finished {
+        Err(anyhow!(
+            "Attendance can only be recorded for finished raids"
+        ))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+    }
+
+    trace.step("Loading sign-ups");
+    let tz = timezone::load(ctx, guild_id).await?;
+    let signups = raid.get_signups(ctx).await?;
+    let users = future::try_join_all(signups.iter().map(|s| s.get_user(ctx))).await?;
+    let recorded: HashMap<i32, db::AttendanceStatus> = raid
+        .get_attendances(ctx)
+        .await?
+        .into_iter()
+        .map(|a| (a.signup_id, a.status))
+        .collect();
+
+    // Everyone who got a slot took part unless told otherwise
+    let default_status = |s: &db::Signup| {
+        if s.waitlisted {
+            None
+        } else {
+            Some(db::AttendanceStatus::Attended)
+        }
+    };
+
+    let mut statuses: Vec<Option<db::AttendanceStatus>> = signups
+        .iter()
+        .map(|s| recorded.get(&s.id).copied().or_else(|| default_status(s)))
+        .collect();
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(attendance_embed(&raid, &tz, &users, &statuses));
+            d.components(|c| attendance_components(c))
+        })
+    })
+    .await?;
+
+    let mut msg = aci.get_interaction_response(ctx).await?;
+
+    loop {
+        trace.step("Waiting for interaction");
+        let mci = match msg
+            .await_component_interaction(ctx)
+            .timeout(Duration::from_secs(60 * 5))
+            .await
+        {
+            Some(mci) => mci,
+            None => {
+                Err(anyhow!(InfoError::TimedOut))
+                    .map_err_reply(|what| aci.edit_quick_info(ctx, what))
+                    .await?;
+                return Ok(());
+            }
+        };
+        mci.defer(ctx).await?;
+
+        let status = match ATTENDANCE_BUTTONS
+            .iter()
+            .find(|(_, id, _)| *id == mci.data.custom_id)
+        {
+            Some((status, _, _)) => *status,
+            None => match mci.parse_button()? {
+                Button::Confirm => break,
+                Button::Abort => {
+                    trace.step("Aborted");
+                    aci.edit_quick_info(ctx, "Aborted").await?;
+                    return Ok(());
+                }
+                _ => bail!("Unexpected interaction"),
+            },
+        };
+
+        trace.step("Selecting attendance exceptions");
+        let pre_sel: Vec<&db::User> = users
+            .iter()
+            .zip(&statuses)
+            .filter(|(_, s)| **s == Some(status))
+            .map(|(u, _)| u)
+            .collect();
+
+        let mut selector = UpdatAbleMessage::ComponentInteraction(&mci, &mut msg);
+        let mut selector_conf = PagedSelectorConfig::default();
+        let mut sel_emb = attendance_embed(&raid, &tz, &users, &statuses);
+        sel_emb.description(format!("Select everyone who is: **{}**", status));
+        selector_conf
+            .base_embed(sel_emb)
+            .items_per_row(4)
+            .rows_per_page(3)
+            .pre_selected(&pre_sel);
+
+        match selector
+            .paged_selector(ctx, selector_conf, &users, |u| {
+                (ReactionType::from('👤'), u.gw2_id.to_string())
+            })
+            .await
+        {
+            Ok(selected) => {
+                for ((u, s), signup) in users.iter().zip(statuses.iter_mut()).zip(&signups) {
+                    if selected.iter().any(|sel| sel.id == u.id) {
+                        *s = Some(status);
+                    } else if *s == Some(status) {
+                        *s = default_status(signup);
+                    }
+                }
+            }
+            Err(PagedSelectorError::Aborted) => (),
+            Err(PagedSelectorError::TimedOut) => {
+                Err(anyhow!(InfoError::TimedOut))
+                    .map_err_reply(|what| aci.edit_quick_info(ctx, what))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        mci.edit_original_interaction_response(ctx, |r| {
+            r.add_embed(attendance_embed(&raid, &tz, &users, &statuses));
+            r.components(|c| attendance_components(c))
+        })
+        .await?;
+    }
+
+    trace.step("Saving attendances");
+    for (signup, status) in signups.iter().zip(&statuses) {
+        match status {
+            Some(s) => {
+                signup.set_attendance(ctx, *s).await?;
+            }
+            None => {
+                signup.clear_attendance(ctx).await?;
+            }
+        }
+    }
+
+    let mut emb = attendance_embed(&raid, &tz, &users, &statuses);
+    emb.footer(|f| f.text(format!("Attendance saved {}", CHECK_EMOJI)));
+    aci.edit_original_interaction_response(ctx, |d| {
+        d.add_embed(emb);
+        d.components(|c| c)
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn signup(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    if let Some(sub) = option.options.get(0) {
+        match sub.name.as_ref() {
+            "add" => signup_add(ctx, aci, sub, trace).await,
+            "remove" => signup_remove(ctx, aci, sub, trace).await,
+            "edit" => signup_edit(ctx, aci, sub, trace).await,
+            _ => bail!("{} not yet available", sub.name),
+        }
+    } else {
+        bail!("Invalid command")
+    }
+}
+
+/// Loads the raid and the registered user selected on a signup sub command
+async fn signup_raid_and_user(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    cmds: &HashMap<String, serde_json::Value>,
+) -> Result<(db::Raid, db::User)> {
+    let guild_id = command_guild(aci)?;
+    let id = cmds
+        .get("raid")
+        .and_then(|v| v.as_i64())
+        .context("Expected raid field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let user_id = cmds
+        .get("user")
+        .and_then(|v| v.as_str())
+        .context("Expected user field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .parse::<UserId>()
+        .context("Invalid user")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let user = db::User::by_discord_id(ctx, user_id)
+        .await
+        .with_context(|| format!("{} is not registered", Mention::from(user_id)))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    Ok((raid, user))
+}
+
+/// Parses a comma separated list of repr's of roles available for the raid
+async fn signup_roles(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    raid: &db::Raid,
+    value: &str,
+) -> Result<Vec<db::Role>> {
+    let available = raid
+        .all_roles(ctx)
+        .await
+        .context("Failed to load roles of the raid")?;
+    let mut roles = Vec::new();
+    for repr in value.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+        let role = available
+            .iter()
+            .find(|r| r.repr == repr)
+            .with_context(|| format!("Role {} is not available for the raid", repr))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        roles.push(role.clone());
+    }
+
+    if roles.is_empty() {
+        Err(anyhow!("Select at least one role"))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    Ok(roles)
+}
+
+async fn signup_add(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Loading raid and user");
+    let (raid, user) = signup_raid_and_user(ctx, aci, &cmds).await?;
+
+    if raid.state == RaidState::Finished || raid.state == RaidState::Cancelled {
+        Err(anyhow!("Raid is already {}", raid.state))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    if db::Signup::by_user_and_raid(ctx, &user, &raid).await.is_ok() {
+        Err(anyhow!("{} is already signed up", user.gw2_id))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    trace.step("Parsing roles");
+    let roles_str = cmds
+        .get("roles")
+        .and_then(|v| v.as_str())
+        .context("roles not set")?;
+    let roles = signup_roles(ctx, aci, &raid, roles_str).await?;
+
+    trace.step("Saving signup");
+    let signup = db::Signup::insert(ctx, &user, &raid)
+        .await
+        .context("Failed to create signup")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    for r in &roles {
+        signup
+            .add_role(ctx, r)
+            .await
+            .with_context(|| format!("Failed to add role: {}", r.title))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    let mut what = format!(
+        "Signed up **{}** for **{}** as {}",
+        user.gw2_id,
+        raid.title,
+        roles.iter().map(|r| r.title.as_str()).join(", ")
+    );
+    if signup.waitlisted {
+        what.push_str("\nThe raid is full. The sign-up is on the waitlist");
+    }
+    aci.create_quick_success(ctx, what, true).await?;
+
+    signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+
+    trace.step("Updating status");
+    status::update_status(ctx).await;
+
+    Ok(())
+}
+
+async fn signup_remove(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Loading raid and user");
+    let (raid, user) = signup_raid_and_user(ctx, aci, &cmds).await?;
+    let reason = cmds.get("reason").and_then(|v| v.as_str());
+
+    trace.step("Loading signup");
+    let signup = db::Signup::by_user_and_raid(ctx, &user, &raid)
+        .await
+        .with_context(|| format!("{} is not signed up", user.gw2_id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Removing signup");
+    let waitlisted = signup.waitlisted;
+    signup
+        .remove(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    if !waitlisted {
+        trace.step("Promoting from waitlist");
+        waitlist::promote(ctx, &raid).await;
+    }
+
+    trace.step("Notifying user");
+    let mut emb = CreateEmbed::xdefault();
+    let tz = timezone::load(ctx, guild_id).await?;
+    emb.title(format!("Removed from raid: {}", raid.title));
+    emb.description("A squadmaker removed your sign-up");
+    let (a, b, c) = embeds::field_raid_date(&raid, &tz);
+    emb.field(a, b, c);
+    if let Some(r) = reason {
+        emb.field("Reason", r, false);
+    }
+    let notified = match UserId::from(user.discord_id()).to_user(ctx).await {
+        Ok(u) => u.dm(ctx, |m| m.set_embed(emb)).await.is_ok(),
+        Err(_) => false,
+    };
+
+    let mut what = format!("Removed **{}** from **{}**", user.gw2_id, raid.title);
+    if !notified {
+        what.push_str("\nThe user could not be notified by DM");
+    }
+    aci.create_quick_success(ctx, what, true).await?;
+
+    signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+
+    trace.step("Updating status");
+    status::update_status(ctx).await;
+
+    Ok(())
+}
+
+async fn signup_edit(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Loading raid and user");
+    let (raid, user) = signup_raid_and_user(ctx, aci, &cmds).await?;
+
+    trace.step("Loading signup");
+    let signup = db::Signup::by_user_and_raid(ctx, &user, &raid)
+        .await
+        .with_context(|| format!("{} is not signed up", user.gw2_id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Parsing roles");
+    let roles_str = cmds
+        .get("roles")
+        .and_then(|v| v.as_str())
+        .context("roles not set")?;
+    let roles = signup_roles(ctx, aci, &raid, roles_str).await?;
+
+    trace.step("Saving roles");
+    signup
+        .clear_roles(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+    for r in &roles {
+        signup
+            .add_role(ctx, r)
+            .await
+            .with_context(|| format!("Failed to add role: {}", r.title))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+    if signup.needs_role_update {
+        signup.set_needs_role_update(ctx, false).await?;
+    }
+
+    aci.create_quick_success(
+        ctx,
+        format!(
+            "Roles of **{}** for **{}** set to {}",
+            user.gw2_id,
+            raid.title,
+            roles.iter().map(|r| r.title.as_str()).join(", ")
+        ),
+        true,
+    )
+    .await?;
+
+    signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+
+    trace.step("Updating status");
+    status::update_status(ctx).await;
+
+    Ok(())
+}
+
+async fn template(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    if let Some(sub) = option.options.get(0) {
+        match sub.name.as_ref() {
+            "add" => template_add(ctx, aci, sub, trace).await,
+            "list" => template_list(ctx, aci, trace).await,
+            "remove" => template_remove(ctx, aci, sub, trace).await,
+            _ => bail!("{} not yet available", sub.name),
+        }
+    } else {
+        bail!("Invalid command")
+    }
+}
+
+async fn template_add(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Parsing template data");
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    let title = cmds
+        .get("title")
+        .and_then(|n| n.as_str())
+        .context("title not set")?;
+
+    let description = cmds
+        .get("description")
+        .and_then(|n| n.as_str())
+        .map(|d| d.to_string());
+
+    let capacity = cmds
+        .get("capacity")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Capacity out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading roles");
+    let roles_str = parse_repr_amounts(
+        cmds.get("roles")
+            .and_then(|n| n.as_str())
+            .context("roles not set")?,
+    )
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    let mut roles: Vec<(db::Role, Option<i32>)> = Vec::with_capacity(roles_str.len());
+    for (r, q) in roles_str {
+        let nr = db::Role::by_repr(ctx, guild_id, r.clone())
+            .await
+            .with_context(|| format!("Failed to load role: {}", r))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        roles.push((nr, q));
+    }
+
+    trace.step("Loading raid bosses");
+    let bosses_str: Vec<&str> = cmds
+        .get("bosses")
+        .and_then(|n| n.as_str())
+        .context("bosses not set")?
+        .split(',')
+        .map(|s| s.trim())
+        .collect();
+
+    let mut bosses: Vec<db::RaidBoss> = Vec::with_capacity(bosses_str.len());
+    for b in bosses_str {
+        let nb = db::RaidBoss::by_repr(ctx, guild_id, b.to_string())
+            .await
+            .with_context(|| format!("Failed to load boss {}", b))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        bosses.push(nb);
+    }
+
+    trace.step("Loading tier");
+    let tier_fut: OptionFuture<_> = cmds
+        .get("tier")
+        .and_then(|v| v.as_str())
+        .map(|t| Tier::by_name(ctx, guild_id, t.to_owned()))
+        .into();
+
+    let tier = tier_fut
+        .await
+        .transpose()
+        .context("Failed to load tier")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading category");
+    let category_fut: OptionFuture<_> = cmds
+        .get("category")
+        .and_then(|v| v.as_str())
+        .map(|c| db::RaidCategory::by_name(ctx, guild_id, c.to_owned()))
+        .into();
+
+    let category = category_fut
+        .await
+        .transpose()
+        .context("Failed to load category")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Saving template");
+    let template = db::RaidTemplate::insert(
+        ctx,
+        guild_id,
+        name.to_string(),
+        title.to_string(),
+        description,
+        tier.as_ref().map(|t| t.id),
+        capacity,
+        category.as_ref().map(|c| c.id),
+    )
+    .await
+    .with_context(|| format!("Failed to save template {}. Maybe the name is taken?", name))
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    for (r, q) in &roles {
+        template
+            .add_role(ctx, r.id, *q)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    for b in &bosses {
+        template
+            .add_raid_boss(ctx, b.id)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid template added");
+    emb.field("Template", &template.name, false);
+    emb.field("Name", &template.title, false);
+    if let Some(d) = &template.description {
+        emb.field("Description", d, false);
+    }
+    match template.capacity {
+        Some(c) => emb.field("Capacity", c, false),
+        None => emb.field("Capacity", "Unlimited", false),
+    };
+    embed_add_roles(
+        &mut emb,
+        &roles.into_iter().map(|(r, _)| r).collect::<Vec<_>>(),
+        true,
+        false,
+    );
+    emb.fields_chunked_fmt(&bosses, |b| b.name.clone(), "Boss Pool", false, 10);
+    match &tier {
+        Some(t) => emb.field("Tier", &t.name, false),
+        None => emb.field("Tier", "Open for everyone", false),
+    };
+    if let Some(c) = &category {
+        emb.field("Category", &c.name, false);
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn template_list(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    trace.step("Loading templates");
+    let templates = db::RaidTemplate::all(ctx, guild_id).await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid templates");
+    if templates.is_empty() {
+        emb.description("No templates found");
+    }
+    for t in &templates {
+        let roles = t
+            .get_roles(ctx)
+            .await?
+            .into_iter()
+            .map(|(r, q)| match q {
+                Some(q) => format!("{}:{}", r.repr, q),
+                None => r.repr,
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let bosses = t
+            .get_raid_bosses(ctx)
+            .await?
+            .into_iter()
+            .map(|b| b.repr)
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut details = format!("Name: {}\nRoles: `{}`\nBosses: `{}`", t.title, roles, bosses);
+        if let Some(c) = t.capacity {
+            details.push_str(&format!("\nCapacity: {}", c));
+        }
+        if let Some(Ok(tier)) = t.get_tier(ctx).await {
+            details.push_str(&format!("\nTier: {}", tier.name));
+        }
+        if let Some(Ok(category)) = t.get_category(ctx).await {
+            details.push_str(&format!("\nCategory: {}", category.name));
+        }
+        if let Some(d) = &t.description {
+            details.push_str(&format!("\n{}", d));
+        }
+        emb.field(&t.name, details, false);
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn template_remove(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    trace.step("Loading template");
+    let template = db::RaidTemplate::by_name(ctx, guild_id, name.to_string())
+        .await
+        .with_context(|| format!("Template **{}** does not exist", name))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Deleting template");
+    template
+        .delete(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    aci.create_quick_success(ctx, format!("Removed template **{}**", name), true)
+        .await?;
+
+    Ok(())
+}
+
+/// Parses a hex color like #1abc9c into 0xRRGGBB
+fn parse_color(input: &str) -> Result<i32> {
+    let hex = input.trim().trim_start_matches('#');
+    match u32::from_str_radix(hex, 16) {
+        Ok(c) if hex.len() == 6 => Ok(c as i32),
+        _ => bail!("Invalid color: {}. Example: #1abc9c", input),
+    }
+}
+
+/// Looks up an emoji by name in the emoji guild
+async fn emoji_by_name(ctx: &Context, guild_id: GuildId, name: &str) -> Result<EmojiId> {
+    let gid = db::Guild::by_id(ctx, guild_id).await?.emoji_guild();
+    let emoji_guild = PartialGuild::get(ctx, gid).await?;
+    emoji_guild
+        .emojis
+        .values()
+        .find(|e| e.name == name.trim())
+        .map(|e| e.id)
+        .with_context(|| format!("The emoji {} was not found in the emoji guild", name))
+}
+
+fn category_fmt(c: &db::RaidCategory) -> String {
+    match c.emoji() {
+        Some(e) => format!("{} | {}", Mention::from(EmojiId::from(e)), c.name),
+        None => c.name.clone(),
+    }
+}
+
+fn category_details_fmt(c: &db::RaidCategory) -> String {
+    let mut details = format!("Weight: {}", c.sort_weight);
+    if let Some(color) = c.color {
+        details.push_str(&format!("\nColor: #{:06x}", color));
+    }
+    details
+}
+
+async fn category(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    if let Some(sub) = option.options.get(0) {
+        match sub.name.as_ref() {
+            "add" => category_add(ctx, aci, sub, trace).await,
+            "edit" => category_edit(ctx, aci, sub, trace).await,
+            "list" => category_list(ctx, aci, trace).await,
+            "remove" => category_remove(ctx, aci, sub, trace).await,
+            _ => bail!("{} not yet available", sub.name),
+        }
+    } else {
+        bail!("Invalid command")
+    }
+}
+
+async fn category_add(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Parsing category data");
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    let weight = cmds
+        .get("weight")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Weight out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .unwrap_or(0);
+
+    let color = cmds
+        .get("color")
+        .and_then(|n| n.as_str())
+        .map(parse_color)
+        .transpose()
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading emoji");
+    let emoji = match cmds.get("emoji").and_then(|n| n.as_str()) {
+        Some(e) => Some(
+            emoji_by_name(ctx, guild_id, e)
+                .await
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?,
+        ),
+        None => None,
+    };
+
+    trace.step("Saving category");
+    let category = db::RaidCategory::insert(
+        ctx,
+        guild_id,
+        name.to_string(),
+        weight,
+        color,
+        emoji.map(|e| e.0 as i64),
+    )
+    .await
+    .with_context(|| format!("Failed to save category {}. Maybe the name is taken?", name))
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid category added");
+    emb.field(
+        category_fmt(&category),
+        category_details_fmt(&category),
+        false,
+    );
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn category_edit(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    trace.step("Loading category");
+    let mut category = db::RaidCategory::by_name(ctx, guild_id, name.to_string())
+        .await
+        .with_context(|| format!("Category **{}** does not exist", name))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+    let curr_category = category.clone();
+
+    trace.step("Parsing category data");
+    match cmds.get("clear").and_then(|v| v.as_str()) {
+        Some("color") => category.color = None,
+        Some("emoji") => category.emoji = None,
+        _ => (),
+    }
+
+    if let Some(w) = cmds.get("weight").and_then(|n| n.as_i64()) {
+        category.sort_weight = i32::try_from(w)
+            .context("Weight out of range")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    if let Some(c) = cmds.get("color").and_then(|n| n.as_str()) {
+        category.color = Some(
+            parse_color(c)
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?,
+        );
+    }
+
+    if let Some(e) = cmds.get("emoji").and_then(|n| n.as_str()) {
+        let emoji = emoji_by_name(ctx, guild_id, e)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        category.emoji = Some(emoji.0 as i64);
+    }
+
+    if category == curr_category {
+        Err(anyhow!("Nothing to change"))
+            .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+            .await?;
+    }
+
+    trace.step("Saving category");
+    let category = category
+        .save(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid category changed");
+    emb.field(
+        category_fmt(&category),
+        category_details_fmt(&category),
+        false,
+    );
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    // the order, colors and emojis on the board may have changed
+    let raids = db::Raid::all_active(ctx, guild_id).await?;
+    let ids = raids
+        .iter()
+        .filter(|r| r.category_id == Some(category.id))
+        .map(|r| r.id)
+        .collect::<Vec<_>>();
+    signup_board::notify_change(ctx, guild_id, &ids).await;
+
+    Ok(())
+}
+
+async fn category_list(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    trace.step("Loading categories");
+    let categories = db::RaidCategory::all(ctx, guild_id).await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid categories");
+    if categories.is_empty() {
+        emb.description("No categories found");
+    }
+    for c in &categories {
+        emb.field(category_fmt(c), category_details_fmt(c), false);
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn category_remove(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    trace.step("Loading category");
+    let category = db::RaidCategory::by_name(ctx, guild_id, name.to_string())
+        .await
+        .with_context(|| format!("Category **{}** does not exist", name))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let raids = db::Raid::all_active(ctx, guild_id).await?;
+    let ids = raids
+        .iter()
+        .filter(|r| r.category_id == Some(category.id))
+        .map(|r| r.id)
+        .collect::<Vec<_>>();
+
+    trace.step("Deleting category");
+    category
+        .delete(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    aci.create_quick_success(ctx, format!("Removed category **{}**", name), true)
+        .await?;
+
+    signup_board::notify_change(ctx, guild_id, &ids).await;
+
+    Ok(())
+}
+
 async fn set(
     ctx: &Context,
     aci: &ApplicationCommandInteraction,
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     // Get subcommands
     let cmds = option
         .options
@@ -450,18 +2848,19 @@ async fn set(
     // Although loading full raids is a bit overhead
     // it also guarantees they exist
     let mut raids: Vec<db::Raid> = Vec::new();
+    let mut days: Vec<NaiveDate> = Vec::new();
 
-    if let Some(days) = cmds
+    if let Some(value) = cmds
         .get("day")
         .and_then(|d| d.value.as_ref())
         .and_then(|d| d.as_str())
     {
-        raids.append(
-            &mut raids_from_days(ctx, days)
-                .await
-                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
-                .await?,
-        );
+        let (mut d, mut r) = raids_from_days(ctx, guild_id, value)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        days.append(&mut d);
+        raids.append(&mut r);
     }
 
     if let Some(ids) = cmds
@@ -470,7 +2869,7 @@ async fn set(
         .and_then(|d| d.as_str())
     {
         raids.append(
-            &mut raids_from_ids(ctx, ids)
+            &mut raids_from_ids(ctx, guild_id, ids)
                 .await
                 .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
                 .await?,
@@ -493,6 +2892,9 @@ async fn set(
     let mut te = CreateEmbed::xdefault();
     te.title("Change raid state");
     te.description(format!("Setting the following raids to: **{}**", state));
+    if !days.is_empty() {
+        te.field("Days", days_fmt(&days), false);
+    }
     te.fields(raids.iter().map(|id| {
         (
             format!("{} | {}", id.id, id.title),
@@ -537,7 +2939,16 @@ async fn set(
                         .into_iter()
                         .map(|t| t.set_state(ctx, state.clone()))
                         .collect();
-                    let _ = future::try_join_all(update_futs).await?;
+                    let updated = future::try_join_all(update_futs).await?;
+
+                    if state == RaidState::Finished {
+                        trace.step("Pre-filling attendances");
+                        let prefill_futs: Vec<_> = updated
+                            .iter()
+                            .map(|t| t.prefill_attendances(ctx))
+                            .collect();
+                        future::try_join_all(prefill_futs).await?;
+                    }
 
                     response
                         .edit_original_interaction_response(ctx, |m| {
@@ -548,20 +2959,22 @@ async fn set(
                         })
                         .await?;
 
-                    trace.step("Updating signup board");
-                    signup_board::SignupBoard::get(ctx)
-                        .await
-                        .read()
-                        .await
-                        .update_overview(ctx, trace.clone())
-                        .await?;
+                    let raid_ids = updated.iter().map(|r| r.id).collect::<Vec<_>>();
+                    signup_board::notify_change(ctx, guild_id, &raid_ids).await;
 
                     trace.step("Updating status");
                     status::update_status(ctx).await;
 
                     response
                         .edit_original_interaction_response(ctx, |m| {
-                            m.add_embed(CreateEmbed::info_box("Everything updated"))
+                            m.add_embed(CreateEmbed::info_box("Everything updated"));
+                            if state == RaidState::Finished {
+                                m.add_embed(CreateEmbed::info_box(
+                                    "Everyone signed up was marked as attended. \
+                                    Use /raid attendance to record no-shows",
+                                ));
+                            }
+                            m
                         })
                         .await?;
                 }
@@ -593,6 +3006,386 @@ async fn set(
     Ok(())
 }
 
+async fn capacity(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let capacity = cmds
+        .get("capacity")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Capacity out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Updating capacity");
+    let raid = raid
+        .set_capacity(ctx, capacity)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    // A bigger capacity might free up slots for the waitlist
+    trace.step("Promoting from waitlist");
+    waitlist::promote(ctx, &raid).await;
+
+    let signup_count = raid.get_signup_count(ctx).await?;
+    let waitlist_count = raid.get_waitlist_count(ctx).await?;
+
+    aci.create_quick_success(
+        ctx,
+        format!(
+            "Capacity of **{}** set to {}\nSign-ups: {}\nWaitlist: {}",
+            raid.title,
+            capacity.map_or_else(|| String::from("unlimited"), |c| c.to_string()),
+            signup_count,
+            waitlist_count
+        ),
+        true,
+    )
+    .await?;
+
+    signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+
+    Ok(())
+}
+
+async fn quota(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let repr = cmds
+        .get("role")
+        .and_then(|v| v.as_str())
+        .context("Expected role field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let amount = cmds
+        .get("amount")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Amount out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading role");
+    let role = db::Role::by_repr(ctx, guild_id, repr.to_string())
+        .await
+        .with_context(|| format!("Failed to load role: {}", repr))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Updating quota");
+    raid.set_role_quota(ctx, role.id, amount)
+        .await
+        .with_context(|| format!("{} is not a role of {}", role.title, raid.title))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    aci.create_quick_success(
+        ctx,
+        format!(
+            "Quota of **{}** for **{}** set to {}",
+            role.title,
+            raid.title,
+            amount.map_or_else(|| String::from("none"), |a| a.to_string()),
+        ),
+        true,
+    )
+    .await?;
+
+    signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ComposeMember<'a> {
+    subgroup: Option<usize>,
+    gw2_id: &'a str,
+    discord_id: u64,
+    role: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct ComposeMissing<'a> {
+    role: &'a str,
+    missing: usize,
+}
+
+#[derive(Serialize)]
+struct ComposeData<'a> {
+    raid: &'a db::Raid,
+    members: Vec<ComposeMember<'a>>,
+    unfilled: Vec<ComposeMissing<'a>>,
+}
+
+async fn compose(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    let id = cmds
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let format = match cmds.get("format").and_then(|v| v.as_str()) {
+        Some("json") => DonwloadFormat::Json,
+        _ => DonwloadFormat::Csv,
+    };
+
+    trace.step("Loading raid");
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load raid with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let roles = raid.all_roles(ctx).await?;
+
+    trace.step("Loading composition");
+    let needs: Vec<compose::RoleNeed> = match cmds.get("composition").and_then(|v| v.as_str()) {
+        Some(c) => {
+            let parsed = parse_repr_amounts(c)
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+            let mut needs = Vec::with_capacity(parsed.len());
+            for (repr, amount) in parsed {
+                let role = roles
+                    .iter()
+                    .find(|r| r.repr == repr)
+                    .with_context(|| format!("{} is not a role of this raid", repr))
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                let amount = amount
+                    .with_context(|| format!("Missing amount for {}", repr))
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                needs.push(compose::RoleNeed {
+                    role_id: role.id,
+                    priority: role.priority,
+                    amount: amount.max(0) as usize,
+                });
+            }
+            needs
+        }
+        None => raid
+            .role_slots(ctx)
+            .await?
+            .into_iter()
+            .filter_map(|rs| {
+                rs.quota.map(|q| compose::RoleNeed {
+                    role_id: rs.role.id,
+                    priority: rs.role.priority,
+                    amount: q.max(0) as usize,
+                })
+            })
+            .collect(),
+    };
+
+    if needs.is_empty() {
+        Err(anyhow!("The raid has no role quotas. Please provide a composition"))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    aci.create_quick_info(ctx, "Composing squad...", true)
+        .await?;
+
+    let msg = aci.get_interaction_response(ctx).await?;
+
+    trace.step("Loading sign-ups");
+    let mut users: HashMap<i32, db::User> = HashMap::new();
+    let mut candidates: Vec<compose::Candidate> = Vec::new();
+    for s in raid.get_signups(ctx).await? {
+        // Only sign-ups on the raid itself take part
+        if s.waitlisted {
+            continue;
+        }
+        let user = s.get_user(ctx).await?;
+        candidates.push(compose::Candidate {
+            user_id: user.id,
+            roles: s.get_roles(ctx).await?.into_iter().map(|r| r.id).collect(),
+        });
+        users.insert(user.id, user);
+    }
+
+    trace.step("Composing");
+    let squad = compose::solve(&needs, &candidates);
+
+    let roles = roles.into_iter().map(|r| (r.id, r)).collect::<HashMap<_, _>>();
+
+    let mut members: Vec<ComposeMember> = Vec::with_capacity(candidates.len());
+    for (i, g) in squad.subgroups.iter().enumerate() {
+        for a in g {
+            let user = users.get(&a.user_id).unwrap();
+            members.push(ComposeMember {
+                subgroup: Some(i + 1),
+                gw2_id: &user.gw2_id,
+                discord_id: user.discord_id(),
+                role: roles.get(&a.role_id).map(|r| r.repr.as_str()),
+            });
+        }
+    }
+    for b in &squad.bench {
+        let user = users.get(b).unwrap();
+        members.push(ComposeMember {
+            subgroup: None,
+            gw2_id: &user.gw2_id,
+            discord_id: user.discord_id(),
+            role: None,
+        });
+    }
+
+    let data = ComposeData {
+        raid: &raid,
+        members,
+        unfilled: squad
+            .unfilled
+            .iter()
+            .filter_map(|(r, m)| {
+                roles.get(r).map(|r| ComposeMissing {
+                    role: &r.repr,
+                    missing: *m,
+                })
+            })
+            .collect(),
+    };
+
+    let data_bytes = match format {
+        DonwloadFormat::Csv => {
+            let mut wrt = csv::Writer::from_writer(vec![]);
+            for m in &data.members {
+                wrt.serialize(m)?;
+            }
+            String::from_utf8(wrt.into_inner()?)?.into_bytes()
+        }
+        DonwloadFormat::Json => {
+            let json = serde_json::to_string_pretty(&data)?;
+            json.as_bytes().to_vec()
+        }
+    };
+
+    let file = AttachmentType::Bytes {
+        data: Cow::from(data_bytes),
+        filename: format!("composition_{}.{}", raid.id, format),
+    };
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title(format!("Composition for {}", raid.title));
+    emb.description(format!(
+        "<t:{}>\n{} of {} sign-ups assigned",
+        raid.date.timestamp(),
+        squad.assigned_count(),
+        candidates.len()
+    ));
+    for (i, g) in squad.subgroups.iter().enumerate() {
+        emb.field(
+            format!("Subgroup {}", i + 1),
+            g.iter()
+                .map(|a| {
+                    let role = roles.get(&a.role_id).unwrap();
+                    format!(
+                        "{} | {}",
+                        Mention::from(EmojiId::from(role.emoji as u64)),
+                        users.get(&a.user_id).unwrap().gw2_id
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            true,
+        );
+    }
+    if !squad.bench.is_empty() {
+        emb.fields_chunked_fmt(
+            &squad.bench,
+            |b| users.get(b).unwrap().gw2_id.clone(),
+            "Bench",
+            false,
+            20,
+        );
+    }
+    if !squad.unfilled.is_empty() {
+        emb.fields_chunked_fmt(
+            &squad.unfilled,
+            |(r, m)| {
+                let role = roles.get(r).unwrap();
+                format!(
+                    "{} | {} : {} missing",
+                    Mention::from(EmojiId::from(role.emoji as u64)),
+                    role.title,
+                    m
+                )
+            },
+            "Unfilled",
+            false,
+            20,
+        );
+    }
+
+    trace.step("Sending composition");
+    let msg = msg
+        .channel_id
+        .send_message(ctx, |m| {
+            m.set_embed(emb);
+            m.add_file(file)
+        })
+        .await?;
+
+    aci.edit_quick_success(ctx, format!("[Done]({})", msg.link()))
+        .await?;
+
+    Ok(())
+}
+
 #[derive(Serialize)]
 enum DonwloadFormat {
     Json,
@@ -613,7 +3406,10 @@ struct SignupData {
     user: db::User,
     member: Member,
     roles: Vec<String>, // we only need the repr here
+    preferred_bosses: Vec<String>,
     comment: Option<String>,
+    waitlisted: bool,
+    reliability: Option<u8>,
 }
 
 // since csv is all row based edit on the fly
@@ -629,14 +3425,21 @@ struct SignupDataCsv<'a> {
     raid_name: &'a str,
     #[serde(rename = "Roles")]
     roles: String,
+    #[serde(rename = "Preferred Bosses")]
+    preferred_bosses: String,
     #[serde(rename = "Comment")]
     comment: Option<&'a str>,
+    #[serde(rename = "Waitlisted")]
+    waitlisted: bool,
+    #[serde(rename = "Reliability")]
+    reliability: Option<u8>,
 }
 
 #[derive(Serialize)]
 struct RaidData {
     raid: db::Raid,
     available_roles: Vec<db::Role>,
+    available_bosses: Vec<db::RaidBoss>,
     signups: Vec<SignupData>,
 }
 
@@ -672,7 +3475,15 @@ impl DownloadData {
                         .map(|r| r.as_str())
                         .collect::<Vec<_>>()
                         .join(", "),
+                    preferred_bosses: s
+                        .preferred_bosses
+                        .iter()
+                        .map(|b| b.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
                     comment: s.comment.as_deref(),
+                    waitlisted: s.waitlisted,
+                    reliability: s.reliability,
                 };
 
                 v.push(elem);
@@ -689,13 +3500,7 @@ async fn download(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
-    let guild_id = match ctx.data.read().await.get::<data::ConfigValuesData>() {
-        Some(conf) => conf.main_guild_id,
-        None => {
-            bail!("Guild configuration could not be loaded");
-        }
-    };
-
+    let guild_id = command_guild(aci)?;
     let guild = PartialGuild::get(ctx, guild_id).await?;
 
     // Get subcommands
@@ -716,10 +3521,11 @@ async fn download(
         .and_then(|d| d.as_str())
     {
         raids.append(
-            &mut raids_from_days(ctx, days)
+            &mut raids_from_days(ctx, guild_id, days)
                 .await
                 .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
-                .await?,
+                .await?
+                .1,
         );
     }
 
@@ -730,7 +3536,7 @@ async fn download(
         .and_then(|d| d.as_str())
     {
         raids.append(
-            &mut raids_from_ids(ctx, ids)
+            &mut raids_from_ids(ctx, guild_id, ids)
                 .await
                 .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
                 .await?,
@@ -771,6 +3577,7 @@ async fn download(
 
     let mut log: Vec<String> = Vec::new();
     let mut tds: Vec<RaidData> = Vec::with_capacity(raids.len());
+    let rel_conf = ReliabilityConfig::load(ctx, guild_id).await?;
 
     for t in raids {
         let signups = t.get_signups(ctx).await?;
@@ -797,24 +3604,38 @@ async fn download(
                 .map(|r| r.repr)
                 .collect::<Vec<_>>();
 
+            let preferred_bosses = s
+                .get_preferred_bosses(ctx)
+                .await?
+                .into_iter()
+                .map(|b| b.repr)
+                .collect::<Vec<_>>();
+
+            let reliability = Reliability::load(ctx, &user, &rel_conf).await?.score();
+
             sds.push(SignupData {
                 user,
                 member,
                 roles,
+                preferred_bosses,
                 comment: s.comment,
+                waitlisted: s.waitlisted,
+                reliability,
             });
         }
 
         let available_roles = t.all_roles(ctx).await?;
+        let available_bosses = t.all_raid_bosses(ctx).await?;
 
         tds.push(RaidData {
             raid: t,
             available_roles,
+            available_bosses,
             signups: sds,
         });
     }
 
-    let dbtiers = db::Tier::all(ctx).await?;
+    let dbtiers = db::Tier::all(ctx, guild_id).await?;
     let mut tiers: Vec<TierData> = Vec::with_capacity(dbtiers.len());
 
     for t in dbtiers {
@@ -932,6 +3753,7 @@ async fn info(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     let cmds = command_map(option);
 
     let id = cmds
@@ -948,28 +3770,40 @@ async fn info(
 
     trace.step("Loading raid information");
 
-    let raid = db::Raid::by_id(ctx, id as i32)
+    let raid = db::Raid::by_id(ctx, guild_id, id as i32)
         .await
         .with_context(|| format!("Failed to load raid with id: {}", id))
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
 
-    let bosses = raid.all_raid_bosses(ctx).await?;
+    let mut bosses = raid.all_raid_bosses(ctx).await?;
+    bosses.sort_by_key(|b| b.position);
+    bosses.sort_by_key(|b| b.wing);
 
-    let roles = raid.all_roles(ctx).await?;
+    trace.step("Loading role slots");
+    let role_slots = raid.role_slots(ctx).await?;
 
-    // HashMap with Role id as key and value to keep count
-    let mut roles_count = roles.iter().map(|r| (r.id, 0)).collect::<HashMap<_, _>>();
+    // HashMap with Boss id as key and value to keep count
+    let mut bosses_count = bosses.iter().map(|b| (b.id, 0)).collect::<HashMap<_, _>>();
 
-    trace.step("Loading signups to calculate role count");
     let signups = raid.get_signups(ctx).await?;
 
-    future::try_join_all(signups.iter().map(|s| s.get_roles(ctx)))
+    trace.step("Loading reliability of signups");
+    let rel_conf = ReliabilityConfig::load(ctx, guild_id).await?;
+    let mut reliabilities: Vec<(db::User, Reliability, bool)> = Vec::with_capacity(signups.len());
+    for s in &signups {
+        let user = s.get_user(ctx).await?;
+        let rel = Reliability::load(ctx, &user, &rel_conf).await?;
+        reliabilities.push((user, rel, s.waitlisted));
+    }
+
+    trace.step("Loading signups to calculate boss preferences");
+    future::try_join_all(signups.iter().map(|s| s.get_preferred_bosses(ctx)))
         .await?
         .into_iter()
         .flatten()
-        .for_each(|sr| {
-            roles_count.entry(sr.id).and_modify(|e| *e += 1);
+        .for_each(|b| {
+            bosses_count.entry(b.id).and_modify(|e| *e += 1);
         });
 
     trace.step("Replying to user");
@@ -980,12 +3814,26 @@ async fn info(
                 d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
             }
             let mut emb = CreateEmbed::xdefault();
-            emb.field("Raid", raid.title, false);
+            emb.field("Raid", &raid.title, false);
             emb.field(
                 "Date/Time",
                 format!("<t:{}>", raid.date.timestamp()),
                 false,
             );
+            if let Some(reason) = &raid.cancel_reason {
+                emb.field("Cancelled", reason, false);
+            }
+            embed_add_raid_details(&mut emb, &raid.details());
+            let signed_up = signups.iter().filter(|s| !s.waitlisted).count();
+            let waiting = signups.len() - signed_up;
+            match raid.capacity {
+                Some(cap) => emb.field(
+                    "Sign-ups",
+                    format!("{}/{} (+{} waiting)", signed_up, cap, waiting),
+                    false,
+                ),
+                None => emb.field("Sign-ups", signed_up, false),
+            };
 
             if !bosses.is_empty() {
                 emb.fields_chunked_fmt(
@@ -996,35 +3844,68 @@ async fn info(
                             None => b.name.to_string(),
                         };
                         format!(
-                            "{} | {}",
+                            "{} | {} : {}",
                             Mention::from(EmojiId::from(b.emoji as u64)),
-                            boss_link
+                            boss_link,
+                            bosses_count.get(&b.id).unwrap()
                         )
                     },
-                    "Boss Pool",
+                    "Boss Preferences",
                     false,
                     20,
                 );
             }
             emb.fields_chunked_fmt(
-                &roles,
-                |r| {
+                &role_slots,
+                |rs| {
+                    let count = match rs.quota {
+                        Some(q) => format!("{}/{}", rs.candidates, q),
+                        None => rs.candidates.to_string(),
+                    };
                     format!(
                         "{} | {} : {}",
-                        Mention::from(EmojiId::from(r.emoji as u64)),
-                        r.title,
-                        roles_count.get(&r.id).unwrap()
+                        Mention::from(EmojiId::from(rs.role.emoji as u64)),
+                        rs.role.title,
+                        count
                     )
                 },
-                "Sign-up Count",
+                "Candidates per Role",
                 true,
                 20,
             );
+            if !reliabilities.is_empty() {
+                emb.fields_chunked_fmt(
+                    &reliabilities,
+                    |(u, rel, waitlisted)| {
+                        if *waitlisted {
+                            format!("{} | {} ⏳", u.gw2_id, rel)
+                        } else {
+                            format!("{} | {}", u.gw2_id, rel)
+                        }
+                    },
+                    "Reliability",
+                    true,
+                    20,
+                );
+            }
             d.add_embed(emb)
         })
     })
     .await?;
 
+    trace.step("Attaching calendar file");
+    let file = AttachmentType::Bytes {
+        data: Cow::from(ical::calendar(&raid.title, std::slice::from_ref(&raid)).into_bytes()),
+        filename: format!("raid_{}.ics", raid.id),
+    };
+    aci.create_followup_message(ctx, |f| {
+        if !public {
+            f.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+        }
+        f.add_file(file)
+    })
+    .await?;
+
     Ok(())
 }
 
@@ -1034,29 +3915,65 @@ async fn list(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     let cmds = command_map(option);
 
+    trace.step("Loading categories");
+    let categories: HashMap<i32, db::RaidCategory> = db::RaidCategory::all(ctx, guild_id)
+        .await?
+        .into_iter()
+        .map(|c| (c.id, c))
+        .collect();
+
+    let filter = match cmds.get("category").and_then(|v| v.as_str()) {
+        Some(f) => Some(
+            categories
+                .values()
+                .find(|c| c.name == f)
+                .with_context(|| format!("Category **{}** does not exist", f))
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?,
+        ),
+        None => None,
+    };
+
     trace.step("Loading raid's");
-    let mut raids = raids_from_days(ctx, cmds.get("day").unwrap().as_str().unwrap())
+    let (days, mut raids) = raids_from_days(ctx, guild_id, cmds.get("day").unwrap().as_str().unwrap())
         .await
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
+    if let Some(f) = filter {
+        raids.retain(|r| r.category_id == Some(f.id));
+    }
     raids.sort_by_key(|t| t.date);
 
+    if raids.is_empty() {
+        let what = match filter {
+            Some(f) => format!("No {} raids on:\n{}", f.name, days_fmt(&days)),
+            None => format!("No raids on:\n{}", days_fmt(&days)),
+        };
+        aci.create_quick_info(ctx, what, true).await?;
+        return Ok(());
+    }
+
+    let tz = timezone::load(ctx, guild_id).await?;
     let mut embeds: Vec<CreateEmbed> = Vec::new();
     let mut data_grouped = Vec::new();
-    for (key, group) in &raids.into_iter().group_by(|a| a.date.date()) {
+    for (key, group) in &raids
+        .into_iter()
+        .group_by(|a| timezone::to_local(&tz, a.date).date())
+    {
         data_grouped.push((key, group.collect::<Vec<_>>()));
     }
     for (d, ts) in data_grouped {
         let mut emb = CreateEmbed::xdefault();
         emb.title(d);
         for t in ts {
-            emb.field(
-                &t.title,
-                format!("<t:{}>\nId: {}", t.date.timestamp(), t.id),
-                true,
-            );
+            let mut value = format!("<t:{}>\nId: {}", t.date.timestamp(), t.id);
+            if let Some(c) = t.category_id.and_then(|c| categories.get(&c)) {
+                value.push_str(&format!("\nCategory: {}", category_fmt(c)));
+            }
+            emb.field(&t.title, value, true);
         }
         embeds.push(emb);
     }
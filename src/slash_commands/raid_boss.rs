@@ -20,7 +20,6 @@ use serenity::{
 use url::Url;
 
 use crate::{
-    data::ConfigValuesData,
     db::{self, RaidBoss},
     embeds::CrossroadsEmbeds,
     logging::*,
@@ -33,7 +32,7 @@ use serenity_tools::{
     interactions::{ApplicationCommandInteractionExt, MessageComponentInteractionExt},
 };
 
-use super::helpers::command_map;
+use super::helpers::{command_guild, command_map};
 
 pub(super) const CMD_RAID_BOSS: &str = "raid_boss";
 
@@ -138,6 +137,7 @@ async fn add(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     let cmds = command_map(option);
 
     let name = cmds
@@ -203,13 +203,7 @@ async fn add(
         .await?;
 
     // load all emojis from discord emoji guild
-    let gid = ctx
-        .data
-        .read()
-        .await
-        .get::<ConfigValuesData>()
-        .unwrap()
-        .emoji_guild_id;
+    let gid = db::Guild::by_id(ctx, guild_id).await?.emoji_guild();
     let emoji_guild = Guild::get(ctx, gid).await?;
 
     let emoji_id = match emoji_guild
@@ -265,10 +259,11 @@ async fn add(
         match react.parse_button()? {
             Button::Confirm => {
                 trace.step("Confirmed, inserting to database");
-                let boss = db::RaidBoss::insert(ctx, name, repr, wing, position, emoji_id, url)
-                    .await
-                    .map_err_reply(|what| aci.edit_quick_error(ctx, what))
-                    .await?;
+                let boss =
+                    db::RaidBoss::insert(ctx, guild_id, name, repr, wing, position, emoji_id, url)
+                        .await
+                        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+                        .await?;
                 aci.edit_quick_info(ctx, format!("Created boss:\n{}", boss))
                     .await?;
             }
@@ -293,6 +288,7 @@ async fn remove(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     let boss_repr = option
         .options
         .get(0)
@@ -303,7 +299,7 @@ async fn remove(
         .await?;
 
     trace.step("Loading boss");
-    let boss = match db::RaidBoss::by_repr(ctx, boss_repr.to_string()).await {
+    let boss = match db::RaidBoss::by_repr(ctx, guild_id, boss_repr.to_string()).await {
         Ok(o) => o,
         Err(diesel::NotFound) => {
             Err(diesel::NotFound)
@@ -337,8 +333,9 @@ async fn list(
     _option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = command_guild(aci)?;
     trace.step("Loading raid bosses");
-    let mut bosses = db::RaidBoss::all(ctx)
+    let mut bosses = db::RaidBoss::all(ctx, guild_id)
         .await
         .context("Failed to load raid bosses =(")
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
@@ -18,7 +18,7 @@ use serenity::{
 
 use serenity_tools::{builder::CreateEmbedExt, interactions::ApplicationCommandInteractionExt};
 
-use crate::{data::ConfigValuesData, db, embeds::CrossroadsEmbeds, logging::*};
+use crate::{db, embeds::CrossroadsEmbeds, logging::*};
 
 pub(super) const CMD_RAID_ROLE: &str = "raid_role";
 pub fn create() -> CreateApplicationCommand {
@@ -104,6 +104,7 @@ async fn add(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let cmds = super::helpers::command_map(option);
 
     let name = cmds
@@ -152,13 +153,7 @@ async fn add(
 
     trace.step("Searching for emoji");
     // load all emojis from discord emoji guild
-    let gid = ctx
-        .data
-        .read()
-        .await
-        .get::<ConfigValuesData>()
-        .unwrap()
-        .emoji_guild_id;
+    let gid = db::Guild::by_id(ctx, guild_id).await?.emoji_guild();
     let emoji_guild = Guild::get(ctx, gid).await?;
 
     let emoji_id = match emoji_guild
@@ -182,6 +177,7 @@ async fn add(
     trace.step("Saving role");
     let raid_role = db::Role::insert(
         ctx,
+        guild_id,
         name.to_string(),
         repr.to_string(),
         emoji_id.0,
@@ -203,6 +199,7 @@ async fn remove(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     trace.step("Load role");
     let cmds = super::helpers::command_map(option);
     let repr = cmds
@@ -212,7 +209,7 @@ async fn remove(
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
 
-    let role = match db::Role::by_repr(ctx, repr.to_string()).await {
+    let role = match db::Role::by_repr(ctx, guild_id, repr.to_string()).await {
         Ok(r) => r,
         Err(diesel::NotFound) => {
             Err(diesel::NotFound)
@@ -242,8 +239,9 @@ async fn remove(
 }
 
 async fn list(ctx: &Context, aci: &ApplicationCommandInteraction, trace: LogTrace) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     trace.step("Load roles from db");
-    let mut roles = db::Role::all_active(ctx).await?;
+    let mut roles = db::Role::all_active(ctx, guild_id).await?;
     roles.sort_by_key(|r| r.title.clone());
     roles.sort_by_key(|r| r.priority);
 
@@ -0,0 +1,617 @@
+use anyhow::{anyhow, bail, Context as ErrContext, Result};
+use chrono::{Datelike, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use serenity::{
+    builder::{CreateApplicationCommand, CreateEmbed},
+    client::Context,
+    futures::future::OptionFuture,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+            ApplicationCommandOptionType,
+        },
+        InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+    },
+};
+use serenity_tools::{
+    builder::{CreateActionRowExt, CreateEmbedExt},
+    collectors::MessageCollectorExt,
+    components::Button,
+    interactions::{ApplicationCommandInteractionExt, MessageComponentInteractionExt},
+};
+use std::time::Duration;
+
+use super::helpers::*;
+use crate::{
+    db,
+    embeds::{embed_add_roles, CrossroadsEmbeds},
+    logging::{log_discord, LogTrace, ReplyHelper},
+    timezone,
+};
+
+pub(super) const CMD_RAID_SCHEDULE: &str = "raid_schedule";
+
+pub fn create() -> CreateApplicationCommand {
+    let mut app = CreateApplicationCommand::default();
+    app.name(CMD_RAID_SCHEDULE);
+    app.description("Manage recurring raids");
+    app.default_permission(false);
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("add");
+        o.description("Add a new weekly raid schedule");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("name");
+            o.description("The name of the raids");
+            o.required(true)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("weekday");
+            o.description("The day of the week the raid takes place on");
+            o.required(true);
+            o.add_string_choice("Monday", "mon");
+            o.add_string_choice("Tuesday", "tue");
+            o.add_string_choice("Wednesday", "wed");
+            o.add_string_choice("Thursday", "thu");
+            o.add_string_choice("Friday", "fri");
+            o.add_string_choice("Saturday", "sat");
+            o.add_string_choice("Sunday", "sun")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("time");
+            o.description("Time in the guild time zone. Format: HH:MM:SS");
+            o.required(true)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("roles");
+            o.description("Comma separated list of repr's with optional quota. Example: dps:6,druid:2,qfb");
+            o.required(true)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("bosses");
+            o.description("The bosses available for the raids. Comma separated list of repr's. Example: vg,gorse");
+            o.required(true)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("tier");
+            o.description("The required tier for the raids. If left empty raids are open for everyone")
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("capacity");
+            o.description("Maximum amount of sign-ups. Further sign-ups go onto a waitlist");
+            o.min_int_value(1)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.name("lookahead");
+            o.description("How many upcoming raids are created ahead of time. Default: 2");
+            o.min_int_value(1);
+            o.max_int_value(8)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.name("category");
+            o.description("Name of the category the raids are listed under")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("list");
+        o.description("List all raid schedules")
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("skip");
+        o.description("Skip a single occurrence of a schedule");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the schedule");
+            o.min_int_value(0)
+        });
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::String);
+            o.required(true);
+            o.name("day");
+            o.description("Day of the occurrence. Format: yyyy-mm-dd")
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("pause");
+        o.description("Stop creating raids for a schedule");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the schedule");
+            o.min_int_value(0)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("resume");
+        o.description("Continue creating raids for a paused schedule");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the schedule");
+            o.min_int_value(0)
+        })
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::SubCommand);
+        o.name("remove");
+        o.description("Delete a schedule. Already created raids are kept");
+        o.create_sub_option(|o| {
+            o.kind(ApplicationCommandOptionType::Integer);
+            o.required(true);
+            o.name("id");
+            o.description("The id of the schedule");
+            o.min_int_value(0)
+        })
+    });
+    app
+}
+
+pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
+    log_discord(ctx, aci, |trace| async move {
+        trace.step("Parsing command");
+        if let Some(sub) = aci.data.options.get(0) {
+            match sub.name.as_ref() {
+                "add" => add(ctx, aci, sub, trace).await,
+                "list" => list(ctx, aci, trace).await,
+                "skip" => skip(ctx, aci, sub, trace).await,
+                "pause" => set_active(ctx, aci, sub, false, trace).await,
+                "resume" => set_active(ctx, aci, sub, true, trace).await,
+                "remove" => remove(ctx, aci, sub, trace).await,
+                _ => bail!("{} not yet available", sub.name),
+            }
+        } else {
+            bail!("Invalid command")
+        }
+    })
+    .await;
+}
+
+fn schedule_summary(schedule: &db::RaidSchedule, tz: &Tz) -> String {
+    let next = schedule.upcoming(tz, chrono::Utc::now().naive_utc())[0];
+    format!(
+        "Every {} at {} {}\nNext: <t:{}>\nCreated ahead: {}\nStatus: {}",
+        timezone::to_local(tz, next).format("%A"),
+        schedule.time.format("%H:%M"),
+        tz.name(),
+        next.timestamp(),
+        schedule.lookahead,
+        if schedule.active { "active" } else { "paused" }
+    )
+}
+
+async fn schedule_by_id(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+) -> Result<db::RaidSchedule> {
+    let guild_id = command_guild(aci)?;
+    let id = command_map(option)
+        .get("id")
+        .and_then(|v| v.as_i64())
+        .context("Expected id field")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    db::RaidSchedule::by_id(ctx, guild_id, id as i32)
+        .await
+        .with_context(|| format!("Failed to load schedule with id: {}", id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await
+}
+
+async fn add(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    let cmds = command_map(option);
+
+    trace.step("Parsing schedule data");
+    let name = cmds
+        .get("name")
+        .and_then(|n| n.as_str())
+        .context("name not set")?;
+
+    let weekday: Weekday = cmds
+        .get("weekday")
+        .and_then(|n| n.as_str())
+        .context("weekday not set")?
+        .parse()
+        .map_err(|_| anyhow!("Could not parse weekday"))?;
+
+    let time: NaiveTime = cmds
+        .get("time")
+        .and_then(|n| n.as_str())
+        .context("time not set")?
+        .parse()
+        .context("Could not parse time")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let capacity = cmds
+        .get("capacity")
+        .and_then(|n| n.as_i64())
+        .map(i32::try_from)
+        .transpose()
+        .context("Capacity out of range")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let lookahead = cmds
+        .get("lookahead")
+        .and_then(|n| n.as_i64())
+        .unwrap_or(2) as i16;
+
+    trace.step("Loading roles");
+    let roles_str = parse_repr_amounts(
+        cmds.get("roles")
+            .and_then(|n| n.as_str())
+            .context("roles not set")?,
+    )
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    let mut roles: Vec<(db::Role, Option<i32>)> = Vec::with_capacity(roles_str.len());
+    for (r, q) in roles_str {
+        let nr = db::Role::by_repr(ctx, guild_id, r.clone())
+            .await
+            .with_context(|| format!("Failed to load role: {}", r))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        roles.push((nr, q));
+    }
+
+    trace.step("Loading raid bosses");
+    let bosses_str: Vec<&str> = cmds
+        .get("bosses")
+        .and_then(|n| n.as_str())
+        .context("bosses not set")?
+        .split(',')
+        .map(|s| s.trim())
+        .collect();
+
+    let mut bosses: Vec<db::RaidBoss> = Vec::with_capacity(bosses_str.len());
+    for b in bosses_str {
+        let nb = db::RaidBoss::by_repr(ctx, guild_id, b.to_string())
+            .await
+            .with_context(|| format!("Failed to load boss {}", b))
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+        bosses.push(nb);
+    }
+
+    trace.step("Loading tier");
+    let tier_fut: OptionFuture<_> = cmds
+        .get("tier")
+        .and_then(|v| v.as_str())
+        .map(|t| db::Tier::by_name(ctx, guild_id, t.to_owned()))
+        .into();
+
+    let tier = tier_fut
+        .await
+        .transpose()
+        .context("Failed to load tier")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Loading category");
+    let category_fut: OptionFuture<_> = cmds
+        .get("category")
+        .and_then(|v| v.as_str())
+        .map(|c| db::RaidCategory::by_name(ctx, guild_id, c.to_owned()))
+        .into();
+
+    let category = category_fut
+        .await
+        .transpose()
+        .context("Failed to load category")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Saving schedule");
+    let schedule = db::RaidSchedule::insert(
+        ctx,
+        guild_id,
+        name.to_string(),
+        weekday,
+        time,
+        tier.as_ref().map(|t| t.id),
+        capacity,
+        lookahead,
+        category.as_ref().map(|c| c.id),
+    )
+    .await
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    for (r, q) in &roles {
+        schedule
+            .add_role(ctx, r.id, *q)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    for b in &bosses {
+        schedule
+            .add_raid_boss(ctx, b.id)
+            .await
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+    }
+
+    let tz = timezone::load(ctx, guild_id).await?;
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid schedule added");
+    emb.field("Name", &schedule.title, false);
+    emb.field("Schedule", schedule_summary(&schedule, &tz), false);
+    embed_add_roles(
+        &mut emb,
+        &roles.into_iter().map(|(r, _)| r).collect::<Vec<_>>(),
+        true,
+        false,
+    );
+    emb.fields_chunked_fmt(&bosses, |b| b.name.clone(), "Boss Pool", false, 10);
+    match &tier {
+        Some(t) => emb.field("Tier", &t.name, false),
+        None => emb.field("Tier", "Open for everyone", false),
+    };
+    if let Some(c) = &category {
+        emb.field("Category", &c.name, false);
+    }
+    emb.field("Schedule ID", schedule.id, false);
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn list(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    trace.step("Loading schedules");
+    let schedules = db::RaidSchedule::all(ctx, guild_id).await?;
+    let tz = timezone::load(ctx, guild_id).await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Raid schedules");
+    if schedules.is_empty() {
+        emb.description("No schedules found");
+    }
+    for s in &schedules {
+        let mut summary = schedule_summary(s, &tz);
+        if let Some(Ok(category)) = s.get_category(ctx).await {
+            summary.push_str(&format!("\nCategory: {}", category.name));
+        }
+        emb.field(format!("{} | {}", s.id, s.title), summary, false);
+    }
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb)
+        })
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn skip(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    let guild_id = command_guild(aci)?;
+    trace.step("Loading schedule");
+    let schedule = schedule_by_id(ctx, aci, option).await?;
+
+    let day: NaiveDate = command_map(option)
+        .get("day")
+        .and_then(|v| v.as_str())
+        .context("day not set")?
+        .parse()
+        .context("Could not parse date")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    if day.weekday() != schedule.weekday() {
+        Err(anyhow!(
+            "**{}** does not take place on {}",
+            schedule.title,
+            day.format("%A, %v")
+        ))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+    }
+
+    trace.step("Saving skip");
+    schedule
+        .skip(ctx, day)
+        .await
+        .context("Failed to skip. Maybe it is skipped already?")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    // Raids are created ahead of time, so it might exist already
+    let tz = timezone::load(ctx, guild_id).await?;
+    let (from, _) = timezone::day_bounds(&tz, day);
+    let existing = schedule
+        .get_raids_after(ctx, from)
+        .await?
+        .into_iter()
+        .find(|r| timezone::to_local(&tz, r.date).date() == day)
+        .filter(|r| r.state != db::RaidState::Finished && r.state != db::RaidState::Cancelled);
+
+    let msg = format!(
+        "Skipping **{}** on {}",
+        schedule.title,
+        day.format("%A, %v")
+    );
+    let raid = match existing {
+        Some(r) => r,
+        None => {
+            aci.create_quick_success(ctx, msg, true).await?;
+            return Ok(());
+        }
+    };
+
+    let signups = raid.get_signups(ctx).await?;
+    let mut emb = CreateEmbed::xdefault();
+    emb.title(msg);
+    emb.description(format!(
+        "The raid was already created with id {}. Cancel it?",
+        raid.id
+    ));
+    emb.field("Sign-ups to notify", signups.len(), false);
+
+    aci.create_interaction_response(ctx, |r| {
+        r.kind(InteractionResponseType::ChannelMessageWithSource);
+        r.interaction_response_data(|d| {
+            d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+            d.add_embed(emb.clone());
+            d.components(|c| c.create_action_row(|a| a.confirm_button().abort_button()))
+        })
+    })
+    .await?;
+
+    let msg = aci.get_interaction_response(ctx).await?;
+
+    trace.step("Waiting for confirm");
+    let react = match msg
+        .await_confirm_abort_interaction(ctx)
+        .timeout(Duration::from_secs(60))
+        .await
+    {
+        Some(r) => r,
+        None => {
+            Err(anyhow!("Timed out. The raid was not cancelled"))
+                .map_err_reply(|what| aci.edit_quick_info(ctx, what))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    react.defer(ctx).await?;
+    match react.parse_button()? {
+        Button::Confirm => (),
+        Button::Abort => {
+            trace.step("Aborted");
+            aci.edit_quick_info(ctx, "Skipped. The raid was not cancelled")
+                .await?;
+            return Ok(());
+        }
+        _ => bail!("Unexpected interaction"),
+    }
+
+    trace.step("Confirmed. Cancelling raid");
+    let reason = format!("{} does not take place this week", schedule.title);
+    let not_notified = super::raid::cancel_raid(ctx, raid, reason, trace)
+        .await
+        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+        .await?;
+
+    emb.description("The raid was cancelled");
+    emb.footer(|f| f.text("Raid cancelled"));
+    if !not_notified.is_empty() {
+        emb.fields_chunked_fmt(
+            &not_notified,
+            |u| u.clone(),
+            "Could not be notified",
+            true,
+            10,
+        );
+    }
+    aci.edit_original_interaction_response(ctx, |d| {
+        d.add_embed(emb);
+        d.components(|c| c)
+    })
+    .await?;
+
+    Ok(())
+}
+
+async fn set_active(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    active: bool,
+    trace: LogTrace,
+) -> Result<()> {
+    trace.step("Loading schedule");
+    let schedule = schedule_by_id(ctx, aci, option).await?;
+
+    trace.step("Updating schedule");
+    let schedule = schedule
+        .set_active(ctx, active)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    aci.create_quick_success(
+        ctx,
+        format!(
+            "**{}** is now {}",
+            schedule.title,
+            if schedule.active { "active" } else { "paused" }
+        ),
+        true,
+    )
+    .await?;
+
+    Ok(())
+}
+
+async fn remove(
+    ctx: &Context,
+    aci: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+    trace: LogTrace,
+) -> Result<()> {
+    trace.step("Loading schedule");
+    let schedule = schedule_by_id(ctx, aci, option).await?;
+    let title = schedule.title.clone();
+
+    trace.step("Deleting schedule");
+    schedule
+        .delete(ctx)
+        .await
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    aci.create_quick_success(ctx, format!("Removed schedule **{}**", title), true)
+        .await?;
+
+    Ok(())
+}
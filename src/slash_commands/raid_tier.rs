@@ -107,6 +107,7 @@ async fn new(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let cmds = super::helpers::command_map(option);
     let name = cmds
         .get("name")
@@ -116,7 +117,7 @@ async fn new(
         .await?;
 
     trace.step("Saving new tier");
-    let tier = db::Tier::insert(ctx, name.to_string())
+    let tier = db::Tier::insert(ctx, guild_id, name.to_string())
         .await
         .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
         .await?;
@@ -137,6 +138,7 @@ async fn add(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let cmds = super::helpers::command_map(option);
     let name = cmds
         .get("name")
@@ -156,7 +158,7 @@ async fn add(
     );
 
     trace.step("Loading tier");
-    let tier = match db::Tier::by_name(ctx, name.to_string()).await {
+    let tier = match db::Tier::by_name(ctx, guild_id, name.to_string()).await {
         Ok(t) => t,
         Err(diesel::NotFound) => {
             Err(diesel::NotFound)
@@ -194,6 +196,7 @@ async fn remove(
     option: &ApplicationCommandInteractionDataOption,
     trace: LogTrace,
 ) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     let cmds = super::helpers::command_map(option);
     let name = cmds
         .get("name")
@@ -213,7 +216,7 @@ async fn remove(
     );
 
     trace.step("Loading tier");
-    let tier = match db::Tier::by_name(ctx, name.to_string()).await {
+    let tier = match db::Tier::by_name(ctx, guild_id, name.to_string()).await {
         Ok(t) => t,
         Err(diesel::NotFound) => {
             Err(diesel::NotFound)
@@ -255,8 +258,9 @@ async fn remove(
 }
 
 async fn list(ctx: &Context, aci: &ApplicationCommandInteraction, trace: LogTrace) -> Result<()> {
+    let guild_id = super::helpers::command_guild(aci)?;
     trace.step("Loading tiers");
-    let tiers = db::Tier::all(ctx).await?;
+    let tiers = db::Tier::all(ctx, guild_id).await?;
     trace.step("Loading roles");
     let tiers = serenity::futures::future::join_all(tiers.into_iter().map(|t| async {
         let r = t.get_discord_roles(ctx).await?;
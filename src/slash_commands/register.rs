@@ -160,3 +160,71 @@ pub async fn handle_unreg(ctx: &Context, aci: &ApplicationCommandInteraction) {
     })
     .await;
 }
+
+pub(super) const CMD_REMINDERS: &str = "reminders";
+
+pub fn create_reminders() -> CreateApplicationCommand {
+    let mut app = CreateApplicationCommand::default();
+    app.name(CMD_REMINDERS);
+    app.description("Choose whether you want to receive raid reminders by DM");
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::Boolean);
+        o.name("enabled");
+        o.required(true);
+        o.description("Receive a DM shortly before raids you signed up for")
+    });
+    app
+}
+
+pub async fn handle_reminders(ctx: &Context, aci: &ApplicationCommandInteraction) {
+    log_discord(ctx, aci, |trace| async move {
+        trace.step("Parsing command");
+        let enabled = aci
+            .data
+            .options
+            .get(0) // only one option anyway
+            .and_then(|v| v.value.as_ref())
+            .and_then(|v| v.as_bool())
+            .context("Unexpected! Missing enabled field")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+
+        trace.step("Looking for user");
+        let db_user = match db::User::by_discord_id(ctx, aci.user.id).await {
+            Ok(u) => u,
+            Err(diesel::NotFound) => {
+                Err(diesel::NotFound)
+                    .context(logging::InfoError::NotRegistered)
+                    .context("You are not registered yet. Use /register first")
+                    .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                Err(e)
+                    .context("Unexpected error fetching user information")
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        trace.step("Saving to db");
+        db_user
+            .set_reminders(ctx, enabled)
+            .await
+            .context("Unexpected error saving your reminder setting =(")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?;
+
+        let what = if enabled {
+            "You will be reminded about your raids by DM"
+        } else {
+            "You will no longer receive raid reminders"
+        };
+        aci.create_quick_success(ctx, what, true).await?;
+
+        Ok(())
+    })
+    .await;
+}
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context as ErrContext, Result};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateEmbed},
+    client::Context,
+    model::{
+        guild::PartialGuild,
+        id::{GuildId, RoleId},
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandOptionType,
+        },
+        misc::Mention,
+    },
+};
+use serenity_tools::interactions::ApplicationCommandInteractionExt;
+
+use crate::{
+    db,
+    embeds::CrossroadsEmbeds,
+    logging::{log_discord, LogTrace, ReplyHelper},
+};
+
+pub(super) const CMD_SETUP: &str = "setup";
+
+pub fn create() -> CreateApplicationCommand {
+    let mut app = CreateApplicationCommand::default();
+    app.name(CMD_SETUP);
+    app.description("Set up the bot for this server");
+    app.default_permission(false);
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::Role);
+        o.required(true);
+        o.name("admin_role");
+        o.description("Role that can change this setup besides the server owner")
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::Role);
+        o.required(true);
+        o.name("squadmaker_role");
+        o.description("Role that can manage raids and configure the bot")
+    });
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::String);
+        o.name("emoji_guild");
+        o.description("Id of the server with the role and boss emojis. Defaults to this server")
+    });
+    app
+}
+
+pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
+    log_discord(
+        ctx,
+        aci,
+        |trace| async move { setup(ctx, aci, trace).await },
+    )
+    .await;
+}
+
+async fn setup(ctx: &Context, aci: &ApplicationCommandInteraction, trace: LogTrace) -> Result<()> {
+    trace.step("Parsing command");
+    let guild_id = super::helpers::command_guild(aci)?;
+    let cmds = aci
+        .data
+        .options
+        .iter()
+        .filter_map(|o| o.value.as_ref().map(|v| (o.name.as_str(), v)))
+        .collect::<HashMap<_, _>>();
+
+    let admin_role_id = cmds
+        .get("admin_role")
+        .and_then(|v| v.as_str())
+        .map(|v| v.parse::<RoleId>())
+        .context("Unexpected missing field admin_role")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let squadmaker_role_id = cmds
+        .get("squadmaker_role")
+        .and_then(|v| v.as_str())
+        .map(|v| v.parse::<RoleId>())
+        .context("Unexpected missing field squadmaker_role")
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    let emoji_guild_id = match cmds.get("emoji_guild").and_then(|v| v.as_str()) {
+        Some(id) => GuildId::from(
+            id.trim()
+                .parse::<u64>()
+                .with_context(|| format!("Invalid server id: {}", id))
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?,
+        ),
+        None => guild_id,
+    };
+
+    trace.step("Checking emoji guild");
+    let emoji_guild = PartialGuild::get(ctx, emoji_guild_id)
+        .await
+        .map_err(|_| anyhow!("The bot is not a member of the server {}", emoji_guild_id))
+        .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+        .await?;
+
+    trace.step("Saving setup");
+    let guild = db::Guild {
+        id: guild_id.0 as i64,
+        admin_role_id: admin_role_id.0 as i64,
+        squadmaker_role_id: squadmaker_role_id.0 as i64,
+        emoji_guild_id: emoji_guild_id.0 as i64,
+    }
+    .save(ctx)
+    .await
+    .context("Unexpected error saving the setup")
+    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+    .await?;
+
+    // registering takes longer than discord waits for a response
+    aci.create_quick_info(ctx, "Setup saved. Registering commands...", true)
+        .await?;
+
+    trace.step("Registering commands");
+    super::register_commands(ctx, &guild)
+        .await
+        .context("Failed to register the commands")
+        .map_err_reply(|what| aci.edit_quick_error(ctx, what))
+        .await?;
+
+    let mut emb = CreateEmbed::xdefault();
+    emb.title("Setup complete");
+    emb.description(
+        "All commands are available now. Continue with `/config overview` and `/config log`",
+    );
+    emb.field("Admin role", Mention::from(admin_role_id), true);
+    emb.field("Squadmaker role", Mention::from(squadmaker_role_id), true);
+    emb.field("Emoji server", &emoji_guild.name, true);
+
+    aci.edit_original_interaction_response(ctx, |r| r.add_embed(emb))
+        .await?;
+
+    Ok(())
+}
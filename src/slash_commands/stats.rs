@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context as ErrContext};
+use serenity::{
+    builder::{CreateApplicationCommand, CreateEmbed},
+    client::Context,
+    model::{
+        id::{EmojiId, RoleId, UserId},
+        interactions::{
+            application_command::{ApplicationCommandInteraction, ApplicationCommandOptionType},
+            InteractionApplicationCommandCallbackDataFlags, InteractionResponseType,
+        },
+        misc::Mention,
+    },
+};
+use serenity_tools::{builder::CreateEmbedExt, interactions::ApplicationCommandInteractionExt};
+
+use crate::{
+    db::{self, AttendanceStatus, RaidState},
+    embeds::CrossroadsEmbeds,
+    logging::{self, log_discord, ReplyHelper},
+    reliability::{Reliability, ReliabilityConfig},
+};
+
+pub(super) const CMD_STATS: &str = "stats";
+
+pub fn create() -> CreateApplicationCommand {
+    let mut app = CreateApplicationCommand::default();
+    app.name(CMD_STATS);
+    app.description("Show statistics about the raids you took part in");
+    app.create_option(|o| {
+        o.kind(ApplicationCommandOptionType::User);
+        o.name("user");
+        o.description("Show the statistics of another user. Squadmakers only")
+    });
+    app
+}
+
+pub async fn handle(ctx: &Context, aci: &ApplicationCommandInteraction) {
+    log_discord(ctx, aci, |trace| async move {
+        trace.step("Parsing command");
+        let guild_id = super::helpers::command_guild(aci)?;
+        let target = aci
+            .data
+            .options
+            .get(0) // only one option anyway
+            .and_then(|v| v.value.as_ref())
+            .and_then(|v| v.as_str())
+            .map(|v| v.parse::<UserId>())
+            .transpose()
+            .context("Unexpected! Invalid user")
+            .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+            .await?
+            .unwrap_or(aci.user.id);
+
+        if target != aci.user.id {
+            trace.step("Checking permission");
+            let guild = db::Guild::by_id(ctx, guild_id).await?;
+            let squadmaker_role_id = RoleId::from(guild.squadmaker_role_id());
+            let is_squadmaker = aci
+                .member
+                .as_ref()
+                .map_or(false, |m| m.roles.contains(&squadmaker_role_id));
+            if !is_squadmaker {
+                Err(anyhow!(
+                    "Only squadmakers can look at the statistics of others"
+                ))
+                .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                .await?;
+            }
+        }
+
+        trace.step("Looking for user");
+        let db_user = match db::User::by_discord_id(ctx, target).await {
+            Ok(u) => u,
+            Err(diesel::NotFound) => {
+                Err(diesel::NotFound)
+                    .context(logging::InfoError::NotRegistered)
+                    .context("Not registered yet. There are no statistics")
+                    .map_err_reply(|what| aci.create_quick_info(ctx, what, true))
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                Err(e)
+                    .context("Unexpected error fetching user information")
+                    .map_err_reply(|what| aci.create_quick_error(ctx, what, true))
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        trace.step("Loading sign-ups");
+        let mut raids = 0;
+        let mut last_raid = None;
+        let mut roles: HashMap<i32, (db::Role, usize)> = HashMap::new();
+        let mut bosses: HashMap<i32, (db::RaidBoss, usize)> = HashMap::new();
+
+        let attendances: HashMap<i32, AttendanceStatus> = db_user
+            .attendances(ctx)
+            .await?
+            .into_iter()
+            .map(|a| (a.signup_id, a.status))
+            .collect();
+
+        for signup in db_user.all_signups(ctx).await? {
+            // Only count raids that actually took place with the user in it
+            if signup.waitlisted || attendances.get(&signup.id) == Some(&AttendanceStatus::NoShow) {
+                continue;
+            }
+            let raid = signup.get_raid(ctx).await?;
+            if raid.state != RaidState::Finished {
+                continue;
+            }
+
+            raids += 1;
+            last_raid = last_raid.max(Some(raid.date));
+            for r in signup.get_roles(ctx).await? {
+                roles.entry(r.id).or_insert((r, 0)).1 += 1;
+            }
+            for b in raid.all_raid_bosses(ctx).await? {
+                bosses.entry(b.id).or_insert((b, 0)).1 += 1;
+            }
+        }
+
+        let attended = attendances
+            .values()
+            .filter(|s| **s != AttendanceStatus::NoShow)
+            .count();
+
+        let rel_conf = ReliabilityConfig::load(ctx, guild_id).await?;
+        let reliability = Reliability::load(ctx, &db_user, &rel_conf).await?;
+
+        let mut roles = roles.into_values().collect::<Vec<_>>();
+        roles.sort_by(|(a, ac), (b, bc)| bc.cmp(ac).then_with(|| a.title.cmp(&b.title)));
+        let mut bosses = bosses.into_values().collect::<Vec<_>>();
+        bosses.sort_by(|(a, ac), (b, bc)| {
+            bc.cmp(ac)
+                .then_with(|| a.wing.cmp(&b.wing))
+                .then_with(|| a.position.cmp(&b.position))
+        });
+
+        trace.step("Replying to user");
+        let mut emb = CreateEmbed::xdefault();
+        emb.title(format!("Statistics of {}", db_user.gw2_id));
+        emb.field("Raids", raids, true);
+        if !attendances.is_empty() {
+            emb.field(
+                "Attendance",
+                format!(
+                    "{}% ({}/{})",
+                    attended * 100 / attendances.len(),
+                    attended,
+                    attendances.len()
+                ),
+                true,
+            );
+        }
+        emb.field("Reliability", &reliability, true);
+        if let Some(last) = last_raid {
+            emb.field("Last Raid", format!("<t:{}:D>", last.timestamp()), true);
+        }
+        if !roles.is_empty() {
+            emb.fields_chunked_fmt(
+                &roles,
+                |(r, c)| {
+                    format!(
+                        "{} | {}: {}",
+                        Mention::from(EmojiId::from(r.emoji as u64)),
+                        r.title,
+                        c
+                    )
+                },
+                "Roles",
+                true,
+                10,
+            );
+        }
+        if !bosses.is_empty() {
+            emb.fields_chunked_fmt(
+                &bosses,
+                |(b, c)| {
+                    format!(
+                        "{} | {}: {}",
+                        Mention::from(EmojiId::from(b.emoji as u64)),
+                        b.name,
+                        c
+                    )
+                },
+                "Bosses",
+                true,
+                10,
+            );
+        }
+
+        aci.create_interaction_response(ctx, |r| {
+            r.kind(InteractionResponseType::ChannelMessageWithSource);
+            r.interaction_response_data(|d| {
+                d.flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL);
+                d.add_embed(emb)
+            })
+        })
+        .await?;
+
+        Ok(())
+    })
+    .await;
+}
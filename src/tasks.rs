@@ -1,26 +1,251 @@
 // automatic task trigger by specific events or constantly running in the background
-use crate::{logging::*, signup_board::SignupBoard};
-use serenity::client::Context;
-use std::time::Duration;
+use crate::{
+    db::{self, RaidState},
+    logging::*,
+    reminders,
+    signup_board::{self, BoardChanges, SignupBoard},
+    status, timezone,
+    transitions::AutoTransitions,
+};
+use anyhow::{bail, Result};
+use serenity::{
+    client::Context,
+    model::{
+        id::{GuildId, RoleId},
+        misc::Mention,
+    },
+};
+use std::{collections::HashSet, time::Duration};
+use tokio::time::{timeout, Instant};
+use tracing::error;
+
+/// Time without further changes before the board is updated
+const BOARD_DEBOUNCE: Duration = Duration::from_secs(3);
+/// Longest an update is delayed while changes keep coming in
+const BOARD_MAX_DELAY: Duration = Duration::from_secs(15);
+/// Full update in case a change was missed
+const BOARD_REFRESH: Duration = Duration::from_secs(60 * 30);
+
+// all guilds the bot is set up for
+async fn configured_guilds(ctx: &Context) -> Vec<GuildId> {
+    match db::Guild::all(ctx).await {
+        Ok(guilds) => guilds.iter().map(|g| g.guild()).collect(),
+        Err(e) => {
+            error!("Failed to load guilds: {}", e);
+            Vec::new()
+        }
+    }
+}
 
 pub async fn signup_board_task(ctx: Context) {
+    let ctx = &ctx;
+    let changes = BoardChanges::get(ctx).await;
+    // first tick is right away and brings the board up to date on start
+    let mut refresh = tokio::time::interval(BOARD_REFRESH);
+    loop {
+        tokio::select! {
+            _ = refresh.tick() => {
+                for guild_id in configured_guilds(ctx).await {
+                    update_board(ctx, guild_id, None).await;
+                }
+            }
+            _ = changes.changed() => {
+                // wait for the changes to settle so a burst only updates once
+                let start = Instant::now();
+                while start.elapsed() < BOARD_MAX_DELAY {
+                    if timeout(BOARD_DEBOUNCE, changes.changed()).await.is_err() {
+                        break;
+                    }
+                }
+                for (guild_id, raid_ids) in changes.take().await {
+                    update_board(ctx, guild_id, Some(raid_ids)).await;
+                }
+                status::update_status(ctx).await;
+            }
+        }
+    }
+}
+
+// Updates the board messages of the given raids, or of all raids, and the overview
+async fn update_board(ctx: &Context, guild_id: GuildId, raid_ids: Option<HashSet<i32>>) {
+    log_discord_err_only(
+        ctx,
+        LogInfo::automatic("Update Signup Board").with_guild(guild_id),
+        |trace| async move {
+            trace.step("Updating board");
+            let board = SignupBoard::get(ctx, guild_id).await?;
+            let mut board = board.write().await;
+            let raids = match raid_ids {
+                Some(ids) => board.update_raid_list(ctx, ids, trace.clone()).await,
+                // also catches board messages deleted by hand
+                None => board.update_raids(ctx, trace.clone()).await,
+            };
+            // the overview is updated even if some raids failed
+            board.update_overview(ctx, trace).await?;
+            raids
+        },
+    )
+    .await;
+}
+
+pub async fn raid_schedule_task(ctx: Context) {
+    let ctx = &ctx;
+    loop {
+        for guild_id in configured_guilds(ctx).await {
+            log_discord_err_only(
+                ctx,
+                LogInfo::automatic("Create scheduled raids").with_guild(guild_id),
+                |trace| async move {
+                    trace.step("Loading schedules");
+                    let now = chrono::Utc::now().naive_utc();
+                    let tz = timezone::load(ctx, guild_id).await?;
+                    for schedule in db::RaidSchedule::all_active(ctx, guild_id).await? {
+                        let skips = schedule.get_skips(ctx).await?;
+                        let existing = schedule
+                            .get_raids_after(ctx, now)
+                            .await?
+                            .into_iter()
+                            .map(|r| r.date)
+                            .collect::<Vec<_>>();
+
+                        for date in schedule.upcoming(&tz, now) {
+                            let day = timezone::to_local(&tz, date).date();
+                            if existing.contains(&date) || skips.contains(&day) {
+                                continue;
+                            }
+                            trace.step("Creating raid");
+                            let raid = schedule.create_raid(ctx, date).await?;
+                            signup_board::notify_change(ctx, guild_id, &[raid.id]).await;
+                        }
+                    }
+                    Ok(())
+                },
+            )
+            .await;
+        }
+        tokio::time::sleep(Duration::from_secs(60 * 15)).await;
+    }
+}
+
+pub async fn raid_state_task(ctx: Context) {
     let ctx = &ctx;
     loop {
-        log_discord_err_only(
-            ctx,
-            LogInfo::automatic("Update Signup Board"),
-            |trace| async move {
-                trace.step("Updating board");
-                SignupBoard::get(ctx)
-                    .await
-                    .read()
-                    .await
-                    .update_overview(ctx, trace)
-                    .await?;
-                Ok(())
-            },
-        )
-        .await;
-        tokio::time::sleep(Duration::from_secs(60 * 5)).await;
+        for guild_id in configured_guilds(ctx).await {
+            log_discord_err_only(
+                ctx,
+                LogInfo::automatic("Check automatic raid state changes").with_guild(guild_id),
+                |trace| async move {
+                    trace.step("Loading configuration");
+                    let auto = AutoTransitions::load(ctx, guild_id).await?;
+
+                    trace.step("Loading raids");
+                    let now = chrono::Utc::now().naive_utc();
+                    let mut changes: Vec<(db::Raid, RaidState)> = Vec::new();
+                    for state in [
+                        RaidState::Created,
+                        RaidState::Open,
+                        RaidState::Closed,
+                        RaidState::Started,
+                    ] {
+                        for raid in db::Raid::by_state(ctx, guild_id, state).await? {
+                            if let Some(next) = auto.next_state(&raid, now) {
+                                changes.push((raid, next));
+                            }
+                        }
+                    }
+
+                    if !changes.is_empty() {
+                        apply_state_changes(ctx, guild_id, changes).await;
+                    }
+                    Ok(())
+                },
+            )
+            .await;
+        }
+        tokio::time::sleep(Duration::from_secs(60)).await;
     }
 }
+
+pub async fn reminder_task(ctx: Context) {
+    let ctx = &ctx;
+    loop {
+        for guild_id in configured_guilds(ctx).await {
+            log_discord_err_only(
+                ctx,
+                LogInfo::automatic("Send raid reminders").with_guild(guild_id),
+                |trace| async move { reminders::send_reminders(ctx, guild_id, trace).await },
+            )
+            .await;
+        }
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+async fn apply_state_changes(
+    ctx: &Context,
+    guild_id: GuildId,
+    changes: Vec<(db::Raid, RaidState)>,
+) {
+    let what = changes
+        .iter()
+        .map(|(r, s)| format!("{} | {}: {} => {}", r.id, r.title, r.state, s))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    log_discord(
+        ctx,
+        LogInfo::automatic(format!("Raid state changes\n{}", what)).with_guild(guild_id),
+        |trace| async move {
+            trace.step("Updating raids");
+            let mut raid_ids = Vec::with_capacity(changes.len());
+            let mut finished = Vec::new();
+            for (raid, state) in changes {
+                let raid = raid.set_state(ctx, state).await?;
+                raid_ids.push(raid.id);
+                if raid.state == RaidState::Finished {
+                    raid.prefill_attendances(ctx).await?;
+                    finished.push(raid);
+                }
+            }
+
+            signup_board::notify_change(ctx, guild_id, &raid_ids).await;
+
+            if !finished.is_empty() {
+                trace.step("Asking for attendance");
+                request_attendance(ctx, guild_id, &finished).await?;
+            }
+
+            trace.step("Updating status");
+            status::update_status(ctx).await;
+            Ok(())
+        },
+    )
+    .await;
+}
+
+// Everyone signed up counts as attended once a raid finishes. Nobody is around
+// to see the hint of /raid set here, so the squadmakers are pinged instead
+async fn request_attendance(ctx: &Context, guild_id: GuildId, raids: &[db::Raid]) -> Result<()> {
+    let chan = match log_channel(ctx, guild_id).await {
+        Some(chan) => chan,
+        None => bail!("No log channel set up to ask for the attendance"),
+    };
+    let guild = db::Guild::by_id(ctx, guild_id).await?;
+
+    let commands = raids
+        .iter()
+        .map(|r| format!("{} | {}: `/raid attendance id:{}`", r.id, r.title, r.id))
+        .collect::<Vec<_>>()
+        .join("\n");
+    chan.send_message(ctx, |m| {
+        m.content(format!(
+            "{} Everyone signed up for these raids was marked as attended. \
+            Record no-shows and late arrivals with:\n{}",
+            Mention::from(RoleId::from(guild.squadmaker_role_id())),
+            commands
+        ))
+    })
+    .await?;
+
+    Ok(())
+}
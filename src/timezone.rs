@@ -0,0 +1,153 @@
+// The guild time zone. Raids are always stored in UTC and only converted
+// for input and display
+use crate::db;
+use anyhow::{anyhow, Context as ErrContext, Result};
+use chrono::{Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serenity::{client::Context, model::id::GuildId};
+
+pub const TIMEZONE_NAME: &str = "timezone";
+
+pub fn parse(name: &str) -> Result<Tz> {
+    name.parse::<Tz>()
+        .map_err(|e| anyhow!(e))
+        .with_context(|| format!("Unknown time zone: {}", name))
+}
+
+/// Loads the configured guild time zone. Defaults to UTC
+pub async fn load(ctx: &Context, guild_id: GuildId) -> Result<Tz> {
+    match db::Config::load(ctx, guild_id, TIMEZONE_NAME.to_string()).await {
+        Ok(conf) => parse(&conf.value),
+        Err(diesel::NotFound) => Ok(Tz::UTC),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Converts a date and time in the guild time zone to UTC
+pub fn to_utc(tz: &Tz, local: NaiveDateTime) -> Result<NaiveDateTime> {
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(dt) => Ok(dt.naive_utc()),
+        // clocks were turned back, so the time exists twice. Take the first one
+        LocalResult::Ambiguous(dt, _) => Ok(dt.naive_utc()),
+        LocalResult::None => Err(anyhow!(
+            "{} does not exist in {} because of daylight saving time",
+            local,
+            tz.name()
+        )),
+    }
+}
+
+/// Like `to_utc`, but a time skipped by daylight saving time is moved forward
+/// to the earliest time that exists
+pub fn to_utc_earliest(tz: &Tz, local: NaiveDateTime) -> NaiveDateTime {
+    let mut local = local;
+    loop {
+        match tz.from_local_datetime(&local) {
+            LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => return dt.naive_utc(),
+            LocalResult::None => local += Duration::minutes(1),
+        }
+    }
+}
+
+/// Converts a UTC date and time to the guild time zone
+pub fn to_local(tz: &Tz, utc: NaiveDateTime) -> NaiveDateTime {
+    tz.from_utc_datetime(&utc).naive_local()
+}
+
+/// The current day in the guild time zone
+pub fn today(tz: &Tz) -> NaiveDate {
+    to_local(tz, Utc::now().naive_utc()).date()
+}
+
+/// Start and end in UTC of a day in the guild time zone. The end is exclusive.
+/// Some zones skip midnight, those days start at the earliest existing time
+pub fn day_bounds(tz: &Tz, date: NaiveDate) -> (NaiveDateTime, NaiveDateTime) {
+    let start = to_utc_earliest(tz, date.and_hms(0, 0, 0));
+    let end = to_utc_earliest(tz, date.succ().and_hms(0, 0, 0));
+    (start, end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn berlin() -> Tz {
+        parse("Europe/Berlin").unwrap()
+    }
+
+    fn at(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(y, m, d).and_hms(h, min, 0)
+    }
+
+    #[test]
+    fn unknown_zones_are_rejected() {
+        assert!(parse("Europe/Nowhere").is_err());
+        assert_eq!(parse("UTC").unwrap(), Tz::UTC);
+    }
+
+    #[test]
+    fn converts_both_ways() {
+        let local = at(2022, 6, 17, 20, 0);
+        let utc = to_utc(&berlin(), local).unwrap();
+        assert_eq!(utc, at(2022, 6, 17, 18, 0));
+        assert_eq!(to_local(&berlin(), utc), local);
+        assert_eq!(
+            to_local(&berlin(), at(2022, 1, 10, 12, 0)),
+            at(2022, 1, 10, 13, 0)
+        );
+    }
+
+    #[test]
+    fn skipped_times_are_rejected_or_moved_forward() {
+        // clocks go from 02:00 to 03:00 on the last sunday of march
+        let skipped = at(2022, 3, 27, 2, 30);
+        assert!(to_utc(&berlin(), skipped).is_err());
+        assert_eq!(to_utc_earliest(&berlin(), skipped), at(2022, 3, 27, 1, 0));
+        assert_eq!(
+            to_utc_earliest(&berlin(), at(2022, 3, 27, 1, 30)),
+            at(2022, 3, 27, 0, 30)
+        );
+    }
+
+    #[test]
+    fn repeated_times_resolve_to_the_first() {
+        // clocks go from 03:00 back to 02:00 on the last sunday of october
+        let repeated = at(2022, 10, 30, 2, 30);
+        assert_eq!(
+            to_utc(&berlin(), repeated).unwrap(),
+            at(2022, 10, 30, 0, 30)
+        );
+        assert_eq!(
+            to_utc_earliest(&berlin(), repeated),
+            at(2022, 10, 30, 0, 30)
+        );
+    }
+
+    #[test]
+    fn days_around_daylight_saving_time() {
+        let day = |d| NaiveDate::from_ymd(2022, 3, d);
+        assert_eq!(
+            day_bounds(&berlin(), day(27)),
+            (at(2022, 3, 26, 23, 0), at(2022, 3, 27, 22, 0))
+        );
+        let day = |d| NaiveDate::from_ymd(2022, 10, d);
+        assert_eq!(
+            day_bounds(&berlin(), day(30)),
+            (at(2022, 10, 29, 22, 0), at(2022, 10, 30, 23, 0))
+        );
+    }
+
+    #[test]
+    fn days_without_midnight_start_at_the_first_existing_time() {
+        // clocks go from 00:00 to 01:00 on the second sunday of march
+        let havana = parse("America/Havana").unwrap();
+        let (start, end) = day_bounds(&havana, NaiveDate::from_ymd(2022, 3, 13));
+        assert_eq!(to_local(&havana, start), at(2022, 3, 13, 1, 0));
+        assert_eq!(start, at(2022, 3, 13, 5, 0));
+        assert_eq!(end, at(2022, 3, 14, 4, 0));
+        assert_eq!(
+            day_bounds(&havana, NaiveDate::from_ymd(2022, 3, 12)).1,
+            start
+        );
+    }
+}
@@ -0,0 +1,86 @@
+// automatic raid state changes relative to the raid date
+use anyhow::{Context as ErrContext, Result};
+use chrono::{Duration, NaiveDateTime};
+use serenity::{client::Context, model::id::GuildId};
+
+use crate::db::{self, RaidState};
+
+pub static AUTO_OPEN_NAME: &str = "auto_open_minutes";
+pub static AUTO_CLOSE_NAME: &str = "auto_close_minutes";
+pub static AUTO_START_NAME: &str = "auto_start_minutes";
+pub static AUTO_FINISH_NAME: &str = "auto_finish_minutes";
+
+/// Offsets in minutes relative to the raid date, finishing is relative to
+/// the expected end. Transitions that are not set are never done automatically
+#[derive(Debug, Default)]
+pub struct AutoTransitions {
+    /// Minutes before the raid sign-ups open
+    pub open: Option<i64>,
+    /// Minutes before the raid sign-ups close
+    pub close: Option<i64>,
+    /// Minutes after the raid date it is started
+    pub start: Option<i64>,
+    /// Minutes after the expected end of the raid it is finished
+    pub finish: Option<i64>,
+}
+
+pub(crate) async fn load_config_i64(
+    ctx: &Context,
+    guild_id: GuildId,
+    name: &str,
+) -> Result<Option<i64>> {
+    match db::Config::load(ctx, guild_id, name.to_string()).await {
+        Ok(conf) => Ok(Some(
+            conf.value
+                .parse()
+                .with_context(|| format!("Invalid value for {}", name))?,
+        )),
+        Err(diesel::NotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+impl AutoTransitions {
+    pub async fn load(ctx: &Context, guild_id: GuildId) -> Result<Self> {
+        Ok(AutoTransitions {
+            open: load_config_i64(ctx, guild_id, AUTO_OPEN_NAME).await?,
+            close: load_config_i64(ctx, guild_id, AUTO_CLOSE_NAME).await?,
+            start: load_config_i64(ctx, guild_id, AUTO_START_NAME).await?,
+            finish: load_config_i64(ctx, guild_id, AUTO_FINISH_NAME).await?,
+        })
+    }
+
+    /// The state the raid has to be moved to next, if any
+    pub fn next_state(&self, raid: &db::Raid, now: NaiveDateTime) -> Option<RaidState> {
+        let reached = |offset: Option<i64>| match offset {
+            Some(m) => now >= raid.date + Duration::minutes(m),
+            None => false,
+        };
+
+        match raid.state {
+            // Never open raids that already took place
+            RaidState::Created if now < raid.date => {
+                let open = match raid.open_at {
+                    Some(at) => now >= at,
+                    None => reached(self.open.map(|m| -m)),
+                };
+                if open {
+                    Some(RaidState::Open)
+                } else {
+                    None
+                }
+            }
+            RaidState::Open if reached(self.close.map(|m| -m)) => Some(RaidState::Closed),
+            RaidState::Open | RaidState::Closed if reached(self.start) => Some(RaidState::Started),
+            // longer raids finish later, the offset is grace time after the end
+            RaidState::Started
+                if self
+                    .finish
+                    .map_or(false, |m| now >= raid.end() + Duration::minutes(m)) =>
+            {
+                Some(RaidState::Finished)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+// promotes waitlisted sign-ups once a raid has free slots again
+use anyhow::anyhow;
+use serenity::{builder::CreateEmbed, model::id::UserId, prelude::*};
+
+use crate::{
+    db,
+    embeds::{self, CrossroadsEmbeds},
+    logging::*,
+    timezone,
+};
+
+/// Moves as many sign-ups from the waitlist onto the raid as there are free
+/// slots and informs the promoted users by DM
+pub async fn promote(ctx: &Context, raid: &db::Raid) {
+    log_discord_err_only(
+        ctx,
+        LogInfo::automatic(format!("Waitlist promotion for raid {}", raid.id))
+            .with_guild(raid.guild()),
+        |trace| async move {
+            trace.step("Promoting sign-ups");
+            let promoted = raid.promote_waitlist(ctx).await?;
+            if promoted.is_empty() {
+                return Ok(());
+            }
+
+            let tz = timezone::load(ctx, raid.guild()).await?;
+            let mut failed: Vec<String> = Vec::new();
+            for signup in promoted {
+                let user = signup.get_user(ctx).await?;
+
+                let mut emb = CreateEmbed::xdefault();
+                emb.title(&raid.title);
+                emb.description(
+                    "🎉 A slot opened up. You were moved from the waitlist onto the raid",
+                );
+                let (a, b, c) = embeds::field_raid_date(raid, &tz);
+                emb.field(a, b, c);
+
+                trace.step("Sending DM");
+                let dm = match UserId::from(user.discord_id()).to_user(ctx).await {
+                    Ok(u) => u.dm(ctx, |m| m.set_embed(emb)).await.map(|_| ()),
+                    Err(e) => Err(e),
+                };
+                if dm.is_err() {
+                    failed.push(user.gw2_id);
+                }
+            }
+
+            if !failed.is_empty() {
+                return Err(anyhow!("Promoted but failed to DM: {}", failed.join(", ")));
+            }
+
+            Ok(())
+        },
+    )
+    .await;
+}